@@ -17,7 +17,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     ntex::rt::spawn(async move {
         loop {
             match pubsub.recv().await {
-                Some(Ok(cmd::SubscribeItem::Subscribed(channel))) => {
+                Some(Ok(cmd::SubscribeItem::Subscribed { channel, .. })) => {
                     println!("sub: subscribed to {:?}", channel)
                 }
                 Some(Ok(cmd::SubscribeItem::Message {
@@ -27,7 +27,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 })) => {
                     println!("sub: {:?} from {:?}", payload, channel)
                 }
-                Some(Ok(cmd::SubscribeItem::UnSubscribed(channel))) => {
+                Some(Ok(cmd::SubscribeItem::UnSubscribed { channel, .. })) => {
                     println!("sub: unsubscribed from {:?}", channel)
                 }
                 Some(Err(e)) => {
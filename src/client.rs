@@ -1,15 +1,158 @@
-use std::collections::VecDeque;
-use std::{cell::RefCell, fmt, future::poll_fn, rc::Rc, task::Context, task::Poll};
+use std::{cell::Cell, cell::RefCell, fmt, future::poll_fn, io, rc::Rc};
+use std::{collections::HashSet, collections::VecDeque, convert::TryFrom};
+use std::{pin::Pin, task::Context, task::Poll};
+use std::{time::Duration, time::Instant};
 
 use ntex::io::{IoBoxed, IoRef, OnDisconnect, RecvError};
-use ntex::util::ready;
-use ntex::{channel::pool, service::Service, service::ServiceCtx};
+use ntex::time::{now, Millis};
+use ntex::util::{join, join_all, ready, ByteString, Bytes, Stream};
+use ntex::{channel::mpsc, channel::pool, service::Service, service::ServiceCtx};
 
-use super::cmd::Command;
-use super::codec::{Codec, Request, Response};
+use super::cmd::commands::BoolOutputCommand;
+use super::cmd::{self, Command, RedisType, TtlResult};
+use super::codec::{BulkString, Codec, Protocol, Request, Response, StreamDecoder, StreamItem};
 use super::errors::{CommandError, Error};
+use super::pipeline::Pipeline;
 
-type Queue = Rc<RefCell<VecDeque<pool::Sender<Result<Response, Error>>>>>;
+/// Default number of keys in a single `DEL` request
+const DEL_BATCH_SIZE: usize = 500;
+
+/// Delete `KEYS[1]` if its type is `ARGV[1]`
+const DEL_IF_TYPE_SCRIPT: &str = "\
+if redis.call('TYPE', KEYS[1]).ok == ARGV[1] then
+  return redis.call('DEL', KEYS[1])
+end
+return 0";
+
+type Queue = Rc<RefCell<VecDeque<Waiter>>>;
+type Invalidations = Rc<RefCell<Option<mpsc::Sender<Option<Vec<Bytes>>>>>>;
+
+/// Pending request
+enum Waiter {
+    Response(pool::Sender<Result<Response, Error>>),
+    /// Array response elements are delivered one by one
+    Stream(mpsc::Sender<Result<Response, CommandError>>),
+    /// Bulk string response is delivered in chunks, `None` for nil response
+    Chunks(mpsc::Sender<Result<Option<Bytes>, CommandError>>),
+}
+
+impl Waiter {
+    fn send(self, item: Result<Response, Error>) {
+        match self {
+            Waiter::Response(tx) => {
+                let _ = tx.send(item);
+            }
+            Waiter::Stream(tx) => match item {
+                // nil array, stream is empty
+                Ok(Response::Nil) => (),
+                Ok(Response::Error(err)) => {
+                    let _ = tx.send(Err(err.into()));
+                }
+                // response is not split by decoder
+                Ok(Response::Array(items)) => {
+                    for item in items {
+                        let _ = tx.send(Ok(item));
+                    }
+                }
+                Ok(Response::Map(items)) => {
+                    for (key, value) in items {
+                        let _ = tx.send(Ok(key));
+                        let _ = tx.send(Ok(value));
+                    }
+                }
+                Ok(item) => {
+                    let _ = tx.send(Err(CommandError::Output("Array response expected", item)));
+                }
+                Err(err) => {
+                    let _ = tx.send(Err(CommandError::Protocol(err)));
+                }
+            },
+            Waiter::Chunks(tx) => {
+                let _ = tx.send(match item {
+                    Ok(Response::Nil) => Ok(None),
+                    Ok(Response::Error(err)) => Err(err.into()),
+                    Ok(item) => Err(CommandError::Output("Bulk string response expected", item)),
+                    Err(err) => Err(CommandError::Protocol(err)),
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Per-call context for `Client::exec_ctx()`
+pub struct CommandContext {
+    deadline: Option<Instant>,
+    correlation_id: Option<ByteString>,
+}
+
+impl CommandContext {
+    /// Create new empty context
+    pub fn new() -> Self {
+        CommandContext::default()
+    }
+
+    /// Set deadline for command execution
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set deadline relative to current time
+    pub fn timeout(self, timeout: Millis) -> Self {
+        self.deadline(now() + Duration::from(timeout))
+    }
+
+    /// Set correlation id for log records
+    pub fn correlation_id<T>(mut self, id: T) -> Self
+    where
+        ByteString: From<T>,
+    {
+        self.correlation_id = Some(id.into());
+        self
+    }
+}
+
+/// Stream of invalidated keys
+///
+/// Created by `Client::invalidation_stream()`.
+pub struct InvalidationStream {
+    rx: mpsc::Receiver<Option<Vec<Bytes>>>,
+    keys: RefCell<VecDeque<Bytes>>,
+}
+
+impl InvalidationStream {
+    /// Receive next invalidated key
+    ///
+    /// Returns `None` if connection is closed.
+    pub async fn recv(&self) -> Option<Bytes> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempt to pull out the next invalidated key
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        loop {
+            if let Some(key) = self.keys.borrow_mut().pop_front() {
+                return Poll::Ready(Some(key));
+            }
+            match ready!(self.rx.poll_recv(cx)) {
+                Some(Some(keys)) => self.keys.borrow_mut().extend(keys),
+                Some(None) => {
+                    log::trace!("All keys are invalidated, message is not delivered to stream")
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Stream for InvalidationStream {
+    type Item = Bytes;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        self.poll_recv(cx)
+    }
+}
 
 #[derive(Clone)]
 /// Shared redis client
@@ -18,21 +161,96 @@ pub struct Client {
     queue: Queue,
     disconnect: OnDisconnect,
     pool: pool::Pool<Result<Response, Error>>,
+    codec: Rc<Cell<Codec>>,
+    invalidations: Invalidations,
+    last_used: Rc<Cell<Instant>>,
+    version: Rc<Cell<Option<(u32, u32, u32)>>>,
 }
 
 impl Client {
-    pub(crate) fn new(io: IoBoxed) -> Self {
+    pub(crate) fn new(io: IoBoxed, codec: Codec, strict: bool) -> Self {
         let queue: Queue = Rc::new(RefCell::new(VecDeque::new()));
+        let codec = Rc::new(Cell::new(codec));
+        let invalidations: Invalidations = Rc::new(RefCell::new(None));
 
         // read redis response task
         let io_ref = io.get_ref();
         let queue2 = queue.clone();
+        let codec2 = codec.clone();
+        let invalidations2 = invalidations.clone();
         ntex::rt::spawn(async move {
+            // number of array elements left for streaming request
+            let mut remaining = 0;
+            // number of bulk string bytes left for chunked request
+            let mut bulk = None;
+
             poll_fn(|cx| loop {
-                match ready!(io.poll_recv(&Codec, cx)) {
-                    Ok(item) => {
-                        if let Some(tx) = queue2.borrow_mut().pop_front() {
+                let (header, bulk_header) = match queue2.borrow().front() {
+                    Some(Waiter::Stream(_)) => (remaining == 0, false),
+                    Some(Waiter::Chunks(_)) => (false, bulk.is_none()),
+                    _ => (false, false),
+                };
+                // protocol could be switched by HELLO command
+                let decoder = StreamDecoder {
+                    codec: codec2.get(),
+                    header,
+                    bulk_header,
+                    bulk,
+                };
+
+                match ready!(io.poll_recv(&decoder, cx)) {
+                    Ok(StreamItem::Header(len)) => {
+                        if len == 0 {
+                            queue2.borrow_mut().pop_front();
+                        }
+                        remaining = len;
+                        continue;
+                    }
+                    Ok(StreamItem::BulkHeader(len)) => {
+                        bulk = Some(len);
+                        continue;
+                    }
+                    Ok(StreamItem::Chunk(chunk)) => {
+                        bulk = bulk.map(|size| size - chunk.len());
+                        if let Some(Waiter::Chunks(tx)) = queue2.borrow().front() {
+                            let _ = tx.send(Ok(Some(chunk)));
+                        }
+                        continue;
+                    }
+                    Ok(StreamItem::BulkEnd) => {
+                        bulk = None;
+                        queue2.borrow_mut().pop_front();
+                        continue;
+                    }
+                    Ok(StreamItem::Response(item)) if remaining > 0 => {
+                        remaining -= 1;
+                        let mut queue = queue2.borrow_mut();
+                        if let Some(Waiter::Stream(tx)) = queue.front() {
                             let _ = tx.send(Ok(item));
+                        }
+                        if remaining == 0 {
+                            queue.pop_front();
+                        }
+                        continue;
+                    }
+                    // out-of-band message, it does not match any request
+                    Ok(StreamItem::Response(Response::Push(items))) => {
+                        handle_push(&invalidations2, items);
+                        continue;
+                    }
+                    Ok(StreamItem::Response(item)) => {
+                        if let Some(waiter) = queue2.borrow_mut().pop_front() {
+                            waiter.send(Ok(item));
+                        } else if strict {
+                            // stream is out of sync, all following responses would mismatch
+                            log::error!("Unexpected redis response, closing: {:?}", item);
+                            let err =
+                                Error::Parse(format!("Unexpected redis response: {:?}", item));
+                            for waiter in queue2.borrow_mut().drain(..) {
+                                waiter.send(Err(err.clone()));
+                            }
+                            let _ = ready!(io.poll_shutdown(cx));
+                            return Poll::Ready(());
                         } else {
                             log::error!("Unexpected redis response: {:?}", item);
                         }
@@ -49,51 +267,586 @@ impl Client {
                         }
                     }
                     Err(RecvError::Decoder(e)) => {
-                        if let Some(tx) = queue2.borrow_mut().pop_front() {
-                            let _ = tx.send(Err(e));
+                        // all awaiting callers get the same protocol error
+                        for waiter in queue2.borrow_mut().drain(..) {
+                            waiter.send(Err(e.clone()));
                         }
-                        queue2.borrow_mut().clear();
                         let _ = ready!(io.poll_shutdown(cx));
                         return Poll::Ready(());
                     }
                     Err(RecvError::PeerGone(e)) => {
                         log::info!("Redis connection is dropped: {:?}", e);
-                        queue2.borrow_mut().clear();
+                        for waiter in queue2.borrow_mut().drain(..) {
+                            // unfinished streams must not look complete
+                            match waiter {
+                                Waiter::Stream(tx) => {
+                                    let _ = tx
+                                        .send(Err(CommandError::Protocol(Error::PeerGone(None))));
+                                }
+                                Waiter::Chunks(tx) => {
+                                    let _ = tx
+                                        .send(Err(CommandError::Protocol(Error::PeerGone(None))));
+                                }
+                                Waiter::Response(_) => (),
+                            }
+                        }
                         return Poll::Ready(());
                     }
                 }
             })
-            .await
+            .await;
+
+            // close invalidations receiver
+            invalidations2.borrow_mut().take();
         });
 
         let disconnect = io_ref.on_disconnect();
 
         Client {
             queue,
+            codec,
+            invalidations,
             disconnect,
             io: io_ref,
             pool: pool::new(),
+            last_used: Rc::new(Cell::new(now())),
+            version: Rc::new(Cell::new(None)),
         }
     }
 
+    /// Reserve capacity for pending commands queue
+    pub(crate) fn reserve_queue(&self, capacity: usize) {
+        self.queue.borrow_mut().reserve(capacity);
+    }
+
     /// Execute redis command
+    ///
+    /// Subscribe and unsubscribe commands are rejected with
+    /// `CommandError::Unsupported`, use `SimpleClient::subscribe()` for pubsub.
+    /// Blocking commands (`BLPOP`, `WAIT`, `XREAD` with `BLOCK` option, etc)
+    /// are rejected as well, use `SimpleClient`. See `codec::BLOCKING_COMMANDS`.
+    /// Requests sent through `Service` interface are checked the same way.
+    ///
+    /// Returned future is cancellation safe. If it is dropped after command
+    /// is sent, command's response is read and discarded, so responses
+    /// of following commands are not affected.
     pub async fn exec<T>(&self, cmd: T) -> Result<T::Output, CommandError>
     where
         T: Command,
+    {
+        self.exec_request::<T>(cmd.to_request()).await
+    }
+
+    pub(crate) async fn exec_request<T>(&self, req: Request) -> Result<T::Output, CommandError>
+    where
+        T: Command,
+    {
+        self.check_request(&req)?;
+        self._call(req)
+            .await
+            .map_err(CommandError::Protocol)
+            .and_then(|res| T::to_output(res.into_result().map_err(T::to_error)?))
+    }
+
+    /// Execute redis command with per-call context
+    ///
+    /// Command fails with `CommandError::Timeout` if response is not received
+    /// before context's deadline, command is not sent at all if deadline is
+    /// already expired. Correlation id is added to command's log records.
+    ///
+    /// ```rust
+    /// use ntex::time::Millis;
+    /// use ntex_redis::{cmd, CommandContext, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///
+    ///     let ctx = CommandContext::new()
+    ///         .timeout(Millis(500))
+    ///         .correlation_id("request-1");
+    ///     redis.exec_ctx(cmd::Get("key"), &ctx).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn exec_ctx<T>(
+        &self,
+        cmd: T,
+        ctx: &CommandContext,
+    ) -> Result<T::Output, CommandError>
+    where
+        T: Command,
+    {
+        let id = ctx
+            .correlation_id
+            .as_ref()
+            .map(|id| id.as_ref())
+            .unwrap_or("-");
+        let res = if let Some(deadline) = ctx.deadline {
+            let now = now();
+            if deadline <= now {
+                Err(CommandError::Timeout)
+            } else {
+                ntex::time::timeout(deadline - now, self.exec(cmd))
+                    .await
+                    .unwrap_or(Err(CommandError::Timeout))
+            }
+        } else {
+            self.exec(cmd).await
+        };
+
+        if let Err(ref err) = res {
+            log::debug!("Redis command failed, correlation id: {}: {}", id, err);
+        } else {
+            log::trace!("Redis command succeeded, correlation id: {}", id);
+        }
+        res
+    }
+
+    /// Create pipeline of commands with typed results
+    ///
+    /// All queued commands are sent at once and executed in one round trip.
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        Pipeline::new(self)
+    }
+
+    /// Execute redis command and return response without conversion
+    ///
+    /// Could be used to inspect unexpected responses. Redis error
+    /// responses are converted to errors.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, codec::Response, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     redis.exec(cmd::Set("key", "value")).await?;
+    ///
+    ///     let response = redis.exec_raw(cmd::Get("key")).await?;
+    ///     assert!(matches!(response, Response::Bytes(_)));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn exec_raw<T>(&self, cmd: T) -> Result<Response, CommandError>
+    where
+        T: Command,
+    {
+        let req = cmd.to_request();
+        self.check_request(&req)?;
+        self._call(req)
+            .await
+            .map_err(CommandError::Protocol)
+            .and_then(|res| Ok(T::to_output_raw(res.into_result().map_err(T::to_error)?)))
+    }
+
+    /// Send redis command without waiting for response
+    ///
+    /// Response is read and dropped, errors returned by redis are ignored.
+    pub fn notify<T>(&self, cmd: T) -> Result<(), CommandError>
+    where
+        T: Command,
+    {
+        let req = cmd.to_request();
+        self.check_request(&req)?;
+        self.io
+            .encode(req, &self.codec.get())
+            .map_err(CommandError::Protocol)?;
+
+        // keep response order, response is dropped with receiver
+        let (tx, _) = self.pool.channel();
+        self.enqueue(Waiter::Response(tx));
+        Ok(())
+    }
+
+    /// Execute redis command and stream elements of array response
+    ///
+    /// Elements are delivered as soon as they are decoded, whole array is
+    /// never decoded into single response. RESP3 sets are streamed as arrays, maps are
+    /// streamed as flat key, value sequence. Nil response produces empty
+    /// stream, error response or non-array response is delivered as single
+    /// error item.
+    ///
+    /// Elements are read from connection regardless of consumer speed,
+    /// elements that are not received yet are buffered by returned
+    /// channel. Slow consumer of large response does not reduce memory
+    /// usage, it only avoids decoding whole response at once.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///
+    ///     let elements = redis.exec_stream(cmd::LRange("list", 0, -1))?;
+    ///     while let Some(item) = elements.recv().await {
+    ///         println!("element: {:?}", item?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn exec_stream<T>(
+        &self,
+        cmd: T,
+    ) -> Result<mpsc::Receiver<Result<Response, CommandError>>, CommandError>
+    where
+        T: Command,
+    {
+        let req = cmd.to_request();
+        self.check_request(&req)?;
+        self.io
+            .encode(req, &self.codec.get())
+            .map_err(CommandError::Protocol)?;
+
+        let (tx, rx) = mpsc::channel();
+        self.enqueue(Waiter::Stream(tx));
+        Ok(rx)
+    }
+
+    /// Switch connection protocol with `HELLO` command.
+    ///
+    /// Codec protocol is updated for all subsequent responses.
+    ///
+    /// ```rust
+    /// use ntex_redis::{codec::Protocol, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///
+    ///     // upgrade connection to RESP3
+    ///     redis.hello(Protocol::Resp3).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn hello(&self, protocol: Protocol) -> Result<(), CommandError> {
+        let prev = self.codec.get().protocol();
+
+        // RESP3 decoder is able to parse RESP2 responses, so use it for
+        // in-flight responses and for HELLO response itself
+        self.set_protocol(Protocol::Resp3);
+        let res = self.exec(cmd::Hello(protocol)).await;
+        self.set_protocol(if res.is_ok() { protocol } else { prev });
+        res
+    }
+
+    /// Get receiver for client-side caching invalidation messages.
+    ///
+    /// Invalidation messages are delivered as RESP3 push messages, so
+    /// connection must use RESP3 protocol and tracking must be enabled with
+    /// `CLIENT TRACKING` command. Each message contains list of invalidated keys,
+    /// `None` means all keys are invalidated (i.e. after `FLUSHALL`).
+    ///
+    /// Only last created receiver gets messages. Receiver is closed
+    /// when connection is closed.
+    pub fn invalidations(&self) -> mpsc::Receiver<Option<Vec<Bytes>>> {
+        let (tx, rx) = mpsc::channel();
+        *self.invalidations.borrow_mut() = Some(tx);
+        rx
+    }
+
+    /// Get stream of invalidated keys.
+    ///
+    /// Same as `invalidations()`, but invalidation messages are flattened
+    /// into a stream of key names. Messages that invalidate all keys
+    /// (i.e. after `FLUSHALL`) are not delivered, use `invalidations()`
+    /// if such messages should be handled. Only last created stream
+    /// or receiver gets messages.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, codec::Protocol, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     redis.hello(Protocol::Resp3).await?;
+    ///     redis.exec(cmd::ClientTracking().on()).await?;
+    ///
+    ///     let invalidations = redis.invalidation_stream();
+    ///     redis.exec(cmd::Get("key")).await?;
+    ///
+    ///     // wait until "key" is modified
+    ///     let key = invalidations.recv().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn invalidation_stream(&self) -> InvalidationStream {
+        InvalidationStream {
+            rx: self.invalidations(),
+            keys: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns current protocol version
+    pub fn protocol(&self) -> Protocol {
+        self.codec.get().protocol()
+    }
+
+    fn set_protocol(&self, protocol: Protocol) {
+        let mut codec = self.codec.get();
+        codec.set_protocol(protocol);
+        self.codec.set(codec);
+    }
+
+    /// Delete keys in batches of 500 keys.
+    ///
+    /// Returns total number of deleted keys.
+    pub async fn del_many<T>(
+        &self,
+        keys: impl IntoIterator<Item = T>,
+    ) -> Result<usize, CommandError>
+    where
+        BulkString: From<T>,
+    {
+        self.del_many_batched(keys, DEL_BATCH_SIZE).await
+    }
+
+    /// Delete keys in batches of `batch_size` keys.
+    ///
+    /// Each batch is sent as separate `DEL` request, all requests
+    /// are pipelined. Returns total number of deleted keys.
+    pub async fn del_many_batched<T>(
+        &self,
+        keys: impl IntoIterator<Item = T>,
+        batch_size: usize,
+    ) -> Result<usize, CommandError>
+    where
+        BulkString: From<T>,
+    {
+        let batch_size = batch_size.max(1);
+        let mut keys = keys.into_iter();
+        let mut batches = Vec::new();
+        while let Some(key) = keys.next() {
+            batches.push(cmd::Del(key).keys(keys.by_ref().take(batch_size - 1)));
+        }
+
+        let mut deleted = 0;
+        for res in join_all(batches.into_iter().map(|cmd| self.exec(cmd))).await {
+            deleted += res?;
+        }
+        Ok(deleted)
+    }
+
+    /// Delete `key` only if it holds a value of type `tp`.
+    ///
+    /// Type check and deletion are executed atomically with a lua script.
+    /// Returns true if key is deleted.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, cmd::RedisType, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     redis.exec(cmd::Set("key", "value")).await?;
+    ///
+    ///     assert!(!redis.del_if_type("key", RedisType::List).await?);
+    ///     assert!(redis.del_if_type("key", RedisType::String).await?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn del_if_type<T>(&self, key: T, tp: RedisType) -> Result<bool, CommandError>
+    where
+        BulkString: From<T>,
+    {
+        self.exec(BoolOutputCommand(Request::Array(vec![
+            Request::from_static("EVAL"),
+            Request::from_static(DEL_IF_TYPE_SCRIPT),
+            Request::BulkInteger(1),
+            Request::BulkString(key.into()),
+            Request::from_static(tp.as_str()),
+        ])))
+        .await
+    }
+
+    /// Get value and remaining time to live of `key`.
+    ///
+    /// `GET` and `TTL` commands are pipelined and executed in one round
+    /// trip, but not atomically.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     redis.exec(cmd::Set("key", "value").expire_secs(10)).await?;
+    ///
+    ///     let (value, ttl) = redis.get_with_ttl("key").await?;
+    ///     assert_eq!(value.unwrap(), "value");
+    ///     assert!(matches!(ttl, cmd::TtlResult::Seconds(_)));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_with_ttl<T>(&self, key: T) -> Result<(Option<Bytes>, TtlResult), CommandError>
+    where
+        BulkString: From<T>,
+    {
+        let key = BulkString::from(key);
+        let (value, ttl) = join(
+            self.exec(cmd::Get::<BulkString>(key.clone())),
+            self.exec(cmd::Ttl::<BulkString>(key)),
+        )
+        .await;
+        Ok((value?, ttl?))
+    }
+
+    /// Get all keys matching glob-style `pattern`.
+    ///
+    /// Unlike `KEYS` command, keys are collected with incremental `SCAN`
+    /// iteration, so redis server is not blocked for the whole iteration.
+    /// Order of keys is not guaranteed. `SCAN` could return the same key
+    /// more than once if database is resized during iteration, such
+    /// duplicates are removed.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     redis.exec(cmd::Set("user:1", "value")).await?;
+    ///
+    ///     let keys = redis.scan_keys("user:*").await?;
+    ///     assert!(keys.contains(&"user:1".into()));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn scan_keys<T>(&self, pattern: T) -> Result<Vec<Bytes>, CommandError>
+    where
+        BulkString: From<T>,
+    {
+        let pattern = BulkString::from(pattern);
+        let mut seen = HashSet::new();
+        let mut keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, batch) = self
+                .exec(cmd::Scan(cursor).pattern::<BulkString>(pattern.clone()))
+                .await?;
+            for key in batch {
+                if seen.insert(key.clone()) {
+                    keys.push(key);
+                }
+            }
+            if next == 0 {
+                return Ok(keys);
+            }
+            cursor = next;
+        }
+    }
+
+    /// Get value of `key` and write it to `writer`.
+    ///
+    /// Value is written in chunks as soon as they are received from redis,
+    /// value is never decoded into single response, so codec's bulk string
+    /// size limit does not apply. Returns number of written bytes or `None`
+    /// if key does not exist. Writer errors are returned as
+    /// `CommandError::Io`.
+    ///
+    /// Chunks are read from connection regardless of writer speed, chunks
+    /// that are not written yet are buffered in memory. With slow writer
+    /// whole value could be buffered.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     redis.exec(cmd::Set("key", "value")).await?;
+    ///
+    ///     let mut buf = Vec::new();
+    ///     let size = redis.get_to("key", &mut buf).await?;
+    ///     assert_eq!(size, Some(5));
+    ///     assert_eq!(buf, b"value");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_to<T, W>(&self, key: T, writer: &mut W) -> Result<Option<usize>, CommandError>
+    where
+        BulkString: From<T>,
+        W: io::Write,
     {
         if self.io.is_closed() {
-            Err(CommandError::Protocol(Error::PeerGone(None)))
+            return Err(CommandError::NotSent);
+        }
+        self.io
+            .encode(cmd::Get(key).to_request(), &self.codec.get())
+            .map_err(CommandError::Protocol)?;
+
+        let (tx, rx) = mpsc::channel();
+        self.enqueue(Waiter::Chunks(tx));
+
+        let mut size = 0;
+        while let Some(item) = rx.recv().await {
+            if let Some(chunk) = item? {
+                writer.write_all(&chunk).map_err(CommandError::Io)?;
+                size += chunk.len();
+            } else {
+                return Ok(None);
+            }
+        }
+        Ok(Some(size))
+    }
+
+    /// Get redis server version as `(major, minor, patch)`.
+    ///
+    /// Version is requested with `INFO server` command once and cached.
+    pub async fn server_version(&self) -> Result<(u32, u32, u32), CommandError> {
+        if let Some(version) = self.version.get() {
+            return Ok(version);
+        }
+
+        let info = self
+            .exec(cmd::BulkRaw(Request::Array(vec![
+                Request::from_static("INFO"),
+                Request::from_static("server"),
+            ])))
+            .await?
+            .unwrap_or_default();
+        let version = parse_version(&info).ok_or_else(|| {
+            CommandError::Output("Cannot parse redis server version", Response::Bytes(info))
+        })?;
+        self.version.set(Some(version));
+        Ok(version)
+    }
+
+    /// Check that redis server version is at least `required`,
+    /// returns `CommandError::UnsupportedVersion` otherwise.
+    ///
+    /// Could be used to guard commands that are not supported
+    /// by older servers.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///
+    ///     // SINTERCARD is available since redis 7.0
+    ///     redis.require_version((7, 0, 0)).await?;
+    ///     redis.exec(cmd::SInterCard(["key1", "key2"])).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn require_version(&self, required: (u32, u32, u32)) -> Result<(), CommandError> {
+        let version = self.server_version().await?;
+        if version < required {
+            Err(CommandError::UnsupportedVersion(version, required))
         } else {
-            self._call(cmd.to_request())
-                .await
-                .map_err(CommandError::Protocol)
-                .and_then(|res| T::to_output(res.into_result().map_err(CommandError::Error)?))
+            Ok(())
         }
     }
 
     /// Delete all the keys of the currently selected DB.
-    pub async fn flushdb(&self) -> Result<(), Error> {
-        self._call("FLUSHDB".into()).await?;
+    pub async fn flushdb(&self) -> Result<(), CommandError> {
+        self.exec(cmd::StatusRaw(Request::Array(vec![Request::from_static(
+            "FLUSHDB",
+        )])))
+        .await?;
         Ok(())
     }
 
@@ -102,12 +855,52 @@ impl Client {
         !self.io.is_closed()
     }
 
-    async fn _call(&self, req: Request) -> Result<Response, Error> {
-        if let Err(e) = self.io.encode(req, &Codec) {
+    /// Returns time elapsed since last command is sent
+    pub fn idle_time(&self) -> Duration {
+        now().saturating_duration_since(self.last_used.get())
+    }
+
+    /// Close connection
+    ///
+    /// Pending commands fail with protocol error.
+    pub fn close(&self) {
+        self.io.close();
+    }
+
+    /// Returns number of commands awaiting a reply from redis
+    pub fn pending(&self) -> usize {
+        self.queue.borrow().len()
+    }
+
+    /// Returns true if there are no commands awaiting a reply
+    pub fn is_idle(&self) -> bool {
+        self.queue.borrow().is_empty()
+    }
+
+    /// Check if request could be sent over shared connection
+    pub(crate) fn check_request(&self, req: &Request) -> Result<(), CommandError> {
+        if self.io.is_closed() {
+            Err(CommandError::NotSent)
+        } else if req.is_pubsub() {
+            Err(CommandError::Unsupported(PUBSUB_ERROR))
+        } else if req.is_blocking() {
+            Err(CommandError::Unsupported(BLOCKING_ERROR))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn enqueue(&self, waiter: Waiter) {
+        self.queue.borrow_mut().push_back(waiter);
+        self.last_used.set(now());
+    }
+
+    pub(crate) async fn _call(&self, req: Request) -> Result<Response, Error> {
+        if let Err(e) = self.io.encode(req, &self.codec.get()) {
             Err(e)
         } else {
             let (tx, rx) = self.pool.channel();
-            self.queue.borrow_mut().push_back(tx);
+            self.enqueue(Waiter::Response(tx));
             poll_fn(|cx| rx.poll_recv(cx))
                 .await
                 .map_err(|_| Error::PeerGone(None))
@@ -116,6 +909,66 @@ impl Client {
     }
 }
 
+/// Subscription pushes do not match requests, it breaks response queue
+const PUBSUB_ERROR: &str =
+    "Subscribe commands are not supported by shared client, use SimpleClient::subscribe()";
+
+/// Parse `redis_version` field of `INFO` response
+fn parse_version(info: &[u8]) -> Option<(u32, u32, u32)> {
+    let line = info
+        .split(|b| *b == b'\n')
+        .find_map(|line| line.strip_prefix(b"redis_version:"))?;
+    let mut parts = std::str::from_utf8(line)
+        .ok()?
+        .trim()
+        .split('.')
+        .map(|part| part.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+/// Blocking command stalls all requests of shared connection
+const BLOCKING_ERROR: &str =
+    "Blocking commands are not supported by shared client, use SimpleClient";
+
+fn handle_push(invalidations: &Invalidations, items: Vec<Response>) {
+    let mut items = items.into_iter();
+
+    match items.next().map(ByteString::try_from) {
+        Some(Ok(kind)) if kind == "invalidate" => {
+            let keys = match items.next() {
+                Some(Response::Nil) => None,
+                Some(keys) => match Vec::<Bytes>::try_from(keys) {
+                    Ok(keys) => Some(keys),
+                    Err((_, keys)) => {
+                        log::error!("Cannot parse invalidation message: {:?}", keys);
+                        return;
+                    }
+                },
+                None => {
+                    log::error!("Invalidation message without keys");
+                    return;
+                }
+            };
+
+            let mut tx = invalidations.borrow_mut();
+            if let Some(sender) = tx.as_ref() {
+                if sender.send(keys).is_err() {
+                    // receiver is dropped
+                    *tx = None;
+                }
+            }
+        }
+        kind => log::debug!("Unhandled redis push message: {:?}", kind),
+    }
+}
+
+/// Raw request interface of shared client
+///
+/// Subscribe and blocking requests are rejected with `Error::Parse`,
+/// same as `Client::exec()` rejects them.
 impl Service<Request> for Client {
     type Response = Response;
     type Error = Error;
@@ -129,7 +982,13 @@ impl Service<Request> for Client {
     }
 
     async fn call(&self, req: Request, _: ServiceCtx<'_, Self>) -> Result<Response, Error> {
-        self._call(req).await
+        if req.is_pubsub() {
+            Err(Error::Parse(PUBSUB_ERROR.to_string()))
+        } else if req.is_blocking() {
+            Err(Error::Parse(BLOCKING_ERROR.to_string()))
+        } else {
+            self._call(req).await
+        }
     }
 }
 
@@ -140,3 +999,634 @@ impl fmt::Debug for Client {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, future::Future, task::Poll};
+
+    use ntex::{io::Io, testing::IoTest, util::join};
+
+    use super::*;
+    use crate::cmd;
+
+    /// Create client connected to test io
+    fn test_client(codec: Codec, strict: bool) -> (Client, IoTest) {
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1 << 21);
+        server.remote_buffer_cap(1 << 21);
+        (Client::new(Io::new(client).into(), codec, strict), server)
+    }
+
+    #[ntex::test]
+    async fn test_decoder_error_delivered_to_all() {
+        let (redis, server) = test_client(Codec::new(), false);
+
+        let mut f1 = Box::pin(redis.exec(cmd::Get("key1")));
+        let mut f2 = Box::pin(redis.exec(cmd::Get("key2")));
+        poll_fn(|cx| {
+            let _ = f1.as_mut().poll(cx);
+            let _ = f2.as_mut().poll(cx);
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(redis.pending(), 2);
+
+        server.write(b"@malformed\r\n");
+        let (r1, r2) = join(f1, f2).await;
+        assert!(matches!(r1, Err(CommandError::Protocol(Error::Parse(_)))));
+        assert!(matches!(r2, Err(CommandError::Protocol(Error::Parse(_)))));
+    }
+
+    #[ntex::test]
+    async fn test_reject_subscribe() {
+        let (redis, server) = test_client(Codec::new(), false);
+
+        let res = redis.exec(cmd::Subscribe(["channel"])).await;
+        assert!(matches!(res, Err(CommandError::Unsupported(_))));
+        let res = redis.exec(cmd::PUnSubscribe::<&str>(None)).await;
+        assert!(matches!(res, Err(CommandError::Unsupported(_))));
+        let res = redis.exec(cmd::SSubscribe(["channel"])).await;
+        assert!(matches!(res, Err(CommandError::Unsupported(_))));
+
+        // nothing is sent to redis
+        assert_eq!(redis.pending(), 0);
+        assert!(server.read_any().is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_hello() {
+        let (redis, server) = test_client(Codec::new(), false);
+        assert_eq!(redis.protocol(), Protocol::Resp2);
+
+        let server = Rc::new(server);
+        let srv = server.clone();
+        ntex::rt::spawn(async move {
+            let _ = srv.read().await;
+            srv.write(b"%1\r\n+proto\r\n:3\r\n");
+        });
+        redis.hello(Protocol::Resp3).await.unwrap();
+        assert_eq!(redis.protocol(), Protocol::Resp3);
+
+        // map response is decoded natively
+        let srv = server.clone();
+        ntex::rt::spawn(async move {
+            let _ = srv.read().await;
+            srv.write(b"%1\r\n$1\r\na\r\n$1\r\nb\r\n");
+        });
+        let res = redis.exec(cmd::HGetAll("key")).await.unwrap();
+        assert_eq!(res.get(&b"a"[..]).unwrap(), &b"b"[..]);
+
+        // failed switch keeps current protocol
+        let srv = server.clone();
+        ntex::rt::spawn(async move {
+            let _ = srv.read().await;
+            srv.write(b"-NOPROTO unsupported protocol version\r\n");
+        });
+        assert!(redis.hello(Protocol::Resp2).await.is_err());
+        assert_eq!(redis.protocol(), Protocol::Resp3);
+    }
+
+    #[ntex::test]
+    async fn test_push_invalidations() {
+        let (redis, server) = test_client(Codec::resp3(), true);
+        let rx = redis.invalidations();
+
+        let mut fut = Box::pin(redis.exec(cmd::Get("key")));
+        poll_fn(|cx| {
+            let _ = fut.as_mut().poll(cx);
+            Poll::Ready(())
+        })
+        .await;
+
+        // push message arrives before response, it must not be matched to request
+        server.write(b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nkey\r\n");
+        server.write(b"$5\r\nvalue\r\n");
+        assert_eq!(fut.await.unwrap(), Some(Bytes::from_static(b"value")));
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            Some(vec![Bytes::from_static(b"key")])
+        );
+
+        // flush invalidates all keys
+        server.write(b">2\r\n$10\r\ninvalidate\r\n_\r\n");
+        assert_eq!(rx.recv().await.unwrap(), None);
+
+        // unknown push messages are ignored
+        server.write(b">2\r\n$7\r\nunknown\r\n:1\r\n");
+        ntex::time::sleep(ntex::time::Millis(50)).await;
+        assert!(redis.is_connected());
+        assert!(redis.is_idle());
+    }
+
+    #[ntex::test]
+    async fn test_del_many() {
+        let (redis, server) = test_client(Codec::new(), false);
+
+        ntex::rt::spawn(async move {
+            let mut data = Vec::new();
+            // 2000 keys in 4 batches
+            while data.windows(3).filter(|w| w == b"DEL").count() < 4 {
+                data.extend_from_slice(&server.read().await.unwrap());
+            }
+            server.write(b":500\r\n:500\r\n:499\r\n:500\r\n");
+            ntex::time::sleep(ntex::time::Millis(100)).await;
+        });
+
+        let keys: Vec<_> = (0..2000).map(|i| format!("key{}", i)).collect();
+        assert_eq!(redis.del_many(&keys).await.unwrap(), 1999);
+    }
+
+    #[ntex::test]
+    async fn test_notify() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        for _ in 0..100 {
+            redis.notify(cmd::Publish("channel", "message")).unwrap();
+        }
+        assert_eq!(redis.pending(), 100);
+
+        ntex::rt::spawn(async move {
+            let mut data = Vec::new();
+            while data.windows(3).filter(|w| w == b"GET").count() < 1 {
+                data.extend_from_slice(&server.read().await.unwrap());
+            }
+            for _ in 0..100 {
+                server.write(b":0\r\n");
+            }
+            server.write(b"$5\r\nvalue\r\n");
+            ntex::time::sleep(ntex::time::Millis(100)).await;
+        });
+
+        let res = redis.exec(cmd::Get("key")).await.unwrap();
+        assert_eq!(res, Some(Bytes::from_static(b"value")));
+        assert!(redis.is_idle());
+        assert!(redis.is_connected());
+    }
+
+    #[ntex::test]
+    async fn test_exec_stream() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let elements = redis.exec_stream(cmd::LRange("list", 0, -1)).unwrap();
+        let empty = redis.exec_stream(cmd::LRange("empty", 0, -1)).unwrap();
+        let nil = redis.exec_stream(cmd::LRange("nil", 0, -1)).unwrap();
+        let error = redis.exec_stream(cmd::LRange("string", 0, -1)).unwrap();
+        assert_eq!(redis.pending(), 4);
+
+        // elements are delivered before whole array is received
+        server.write(b"*3\r\n$1\r\na\r\n*1\r\n:1\r\n");
+        assert_eq!(
+            elements.recv().await.unwrap().unwrap(),
+            Response::Bytes(Bytes::from_static(b"a"))
+        );
+        assert_eq!(
+            elements.recv().await.unwrap().unwrap(),
+            Response::Array(vec![Response::Integer(1)])
+        );
+        assert_eq!(redis.pending(), 4);
+
+        server.write(b"$-1\r\n*0\r\n*-1\r\n-WRONGTYPE Operation\r\n");
+        assert_eq!(elements.recv().await.unwrap().unwrap(), Response::Nil);
+        assert!(elements.recv().await.is_none());
+        assert!(empty.recv().await.is_none());
+        assert!(nil.recv().await.is_none());
+        assert!(matches!(
+            error.recv().await.unwrap(),
+            Err(CommandError::WrongType(_))
+        ));
+        assert!(error.recv().await.is_none());
+
+        // following responses are not affected
+        let (res, _) = join(redis.exec(cmd::Get("key")), async {
+            server.write(b"$5\r\nvalue\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Some(Bytes::from_static(b"value")));
+        assert!(redis.is_idle());
+    }
+
+    #[ntex::test]
+    async fn test_exec_stream_resp3() {
+        let (redis, server) = test_client(Codec::resp3(), true);
+
+        let set = redis.exec_stream(cmd::SMembers("set")).unwrap();
+        let map = redis.exec_stream(cmd::HGetAll("hash")).unwrap();
+        let empty = redis.exec_stream(cmd::SMembers("empty")).unwrap();
+
+        // set elements are delivered before whole set is received
+        server.write(b"~2\r\n$1\r\na\r\n");
+        assert_eq!(
+            set.recv().await.unwrap().unwrap(),
+            Response::Bytes(Bytes::from_static(b"a"))
+        );
+        assert_eq!(redis.pending(), 3);
+
+        server.write(b"$1\r\nb\r\n%1\r\n$1\r\nf\r\n:1\r\n~0\r\n");
+        assert_eq!(
+            set.recv().await.unwrap().unwrap(),
+            Response::Bytes(Bytes::from_static(b"b"))
+        );
+        assert!(set.recv().await.is_none());
+
+        // map entries are streamed as key, value sequence
+        assert_eq!(
+            map.recv().await.unwrap().unwrap(),
+            Response::Bytes(Bytes::from_static(b"f"))
+        );
+        assert_eq!(map.recv().await.unwrap().unwrap(), Response::Integer(1));
+        assert!(map.recv().await.is_none());
+        assert!(empty.recv().await.is_none());
+        assert!(redis.is_idle());
+    }
+
+    #[ntex::test]
+    async fn test_queue_capacity() {
+        let (redis, server) = test_client(Codec::new(), true);
+        redis.reserve_queue(1000);
+        assert!(redis.queue.borrow().capacity() >= 1000);
+
+        ntex::rt::spawn(async move {
+            let mut data = Vec::new();
+            while data.windows(4).filter(|w| w == b"INCR").count() < 1000 {
+                data.extend_from_slice(&server.read().await.unwrap());
+            }
+            for i in 1..=1000 {
+                server.write(format!(":{}\r\n", i));
+            }
+            ntex::time::sleep(ntex::time::Millis(100)).await;
+        });
+
+        let results = join_all((0..1000).map(|_| redis.exec(cmd::IncrBy("key", 1)))).await;
+        for (i, res) in results.into_iter().enumerate() {
+            assert_eq!(res.unwrap(), i as i64 + 1);
+        }
+        assert!(redis.is_idle());
+    }
+
+    #[ntex::test]
+    async fn test_exec_ctx_deadline() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        // no response in time
+        let ctx = CommandContext::new()
+            .timeout(Millis(50))
+            .correlation_id("test-1");
+        let res = redis.exec_ctx(cmd::Get("key"), &ctx).await;
+        assert!(matches!(res, Err(CommandError::Timeout)));
+        assert!(!server.read_any().is_empty());
+
+        // expired deadline, command is not sent
+        let res = redis.exec_ctx(cmd::Get("key"), &ctx).await;
+        assert!(matches!(res, Err(CommandError::Timeout)));
+        assert!(server.read_any().is_empty());
+
+        // late response is dropped, next command gets own response
+        server.write(b"$4\r\nlate\r\n");
+        let ctx = CommandContext::new().timeout(Millis(1000));
+        let (res, _) = join(redis.exec_ctx(cmd::Get("key"), &ctx), async {
+            server.read().await.unwrap();
+            server.write(b"$5\r\nvalue\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Some(Bytes::from_static(b"value")));
+    }
+
+    #[ntex::test]
+    async fn test_exec_dropped() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        // command is sent and future is dropped before response
+        let mut fut = Box::pin(redis.exec(cmd::Get("key1")));
+        poll_fn(|cx| {
+            assert!(fut.as_mut().poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        drop(fut);
+        assert_eq!(redis.pending(), 1);
+
+        let (res, _) = join(redis.exec(cmd::Get("key2")), async {
+            let mut data = Vec::new();
+            while !data.ends_with(b"key2\r\n") {
+                data.extend_from_slice(&server.read().await.unwrap());
+            }
+            server.write(b"$6\r\nvalue1\r\n$6\r\nvalue2\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Some(Bytes::from_static(b"value2")));
+        assert!(redis.is_idle());
+        assert!(redis.is_connected());
+    }
+
+    #[ntex::test]
+    async fn test_require_version() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let info = "# Server\r\nredis_version:7.2.4\r\nredis_mode:standalone\r\n";
+        let (res, _) = join(redis.require_version((99, 0, 0)), async {
+            let req = server.read().await.unwrap();
+            assert!(req.ends_with(b"INFO\r\n$6\r\nserver\r\n"));
+            server.write(format!("${}\r\n{}\r\n", info.len(), info));
+        })
+        .await;
+        match res {
+            Err(err @ CommandError::UnsupportedVersion((7, 2, 4), (99, 0, 0))) => assert_eq!(
+                err.to_string(),
+                "Redis server version 7.2.4 is lower than required 99.0.0"
+            ),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        // version is cached
+        redis.require_version((7, 2, 4)).await.unwrap();
+        redis.require_version((6, 2, 0)).await.unwrap();
+        assert_eq!(redis.server_version().await.unwrap(), (7, 2, 4));
+        assert!(server.read_any().is_empty());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version(b"redis_version:7.0.11\r\n"), Some((7, 0, 11)));
+        assert_eq!(
+            parse_version(b"# Server\nredis_version:6.2"),
+            Some((6, 2, 0))
+        );
+        assert_eq!(parse_version(b"redis_version:abc\r\n"), None);
+        assert_eq!(parse_version(b"redis_mode:standalone\r\n"), None);
+    }
+
+    #[ntex::test]
+    async fn test_blocking_rejected() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let res = redis
+            .exec(cmd::BLMove(
+                "a",
+                "b",
+                cmd::ListSide::Left,
+                cmd::ListSide::Left,
+                0.0,
+            ))
+            .await;
+        assert!(matches!(res, Err(CommandError::Unsupported(_))));
+        assert!(redis.notify(cmd::BLMPop(1.0, ["a"])).is_err());
+        assert!(matches!(
+            redis.exec(cmd::WaitAof(1, 0, 100)).await,
+            Err(CommandError::Unsupported(_))
+        ));
+        match redis.exec(cmd::BLPop(["a"], 0.0)).await {
+            Err(CommandError::Unsupported(err)) => assert!(err.contains("use SimpleClient")),
+            res => panic!("unexpected result: {:?}", res),
+        }
+        let res = redis
+            .exec(cmd::ArrayRaw(crate::array![
+                "XREAD", "BLOCK", "0", "STREAMS", "s", "$"
+            ]))
+            .await;
+        assert!(matches!(res, Err(CommandError::Unsupported(_))));
+        assert!(redis.is_idle());
+        assert!(server.read_any().is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_not_sent() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        // connection is lost after command is sent
+        let (res, _) = join(redis.exec(cmd::IncrBy("key", 1)), async {
+            server.read().await.unwrap();
+            server.close().await;
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(CommandError::Protocol(Error::PeerGone(_)))
+        ));
+
+        // connection is closed, command is not sent
+        ntex::time::sleep(ntex::time::Millis(50)).await;
+        assert!(!redis.is_connected());
+        let res = redis.exec(cmd::IncrBy("key", 1)).await;
+        assert!(matches!(res, Err(CommandError::NotSent)));
+        assert!(matches!(
+            redis.notify(cmd::IncrBy("key", 1)),
+            Err(CommandError::NotSent)
+        ));
+    }
+
+    #[ntex::test]
+    async fn test_get_with_ttl() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        ntex::rt::spawn(async move {
+            // both commands are sent before any response
+            let mut data = Vec::new();
+            while !data.ends_with(b"TTL\r\n$3\r\nkey\r\n") {
+                data.extend_from_slice(&server.read().await.unwrap());
+            }
+            assert!(data.starts_with(b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n"));
+            server.write(b"$5\r\nvalue\r\n:10\r\n");
+            ntex::time::sleep(ntex::time::Millis(100)).await;
+        });
+
+        let (value, ttl) = redis.get_with_ttl("key").await.unwrap();
+        assert_eq!(value, Some(Bytes::from_static(b"value")));
+        assert_eq!(ttl, TtlResult::Seconds(10));
+    }
+
+    #[ntex::test]
+    async fn test_del_if_type() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let (res, _) = join(redis.del_if_type("key", RedisType::Hash), async {
+            let req = server.read().await.unwrap();
+            assert!(req.starts_with(b"*5\r\n$4\r\nEVAL\r\n"));
+            assert!(req.ends_with(b"$1\r\n1\r\n$3\r\nkey\r\n$4\r\nhash\r\n"));
+            server.write(b":1\r\n");
+        })
+        .await;
+        assert!(res.unwrap());
+    }
+
+    #[ntex::test]
+    async fn test_invalidation_stream() {
+        let (redis, server) = test_client(Codec::resp3(), true);
+        let invalidations = redis.invalidation_stream();
+
+        server.write(
+            ">2\r\n$10\r\ninvalidate\r\n*2\r\n$1\r\na\r\n$1\r\nb\r\n\
+             >2\r\n$10\r\ninvalidate\r\n_\r\n\
+             >2\r\n$10\r\ninvalidate\r\n*1\r\n$1\r\nc\r\n",
+        );
+        assert_eq!(invalidations.recv().await.unwrap(), "a");
+        assert_eq!(invalidations.recv().await.unwrap(), "b");
+        assert_eq!(invalidations.recv().await.unwrap(), "c");
+
+        server.close().await;
+        assert_eq!(invalidations.recv().await, None);
+    }
+
+    #[ntex::test]
+    async fn test_scan_keys() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let (res, _) = join(redis.scan_keys("key:*"), async {
+            let req = server.read().await.unwrap();
+            assert_eq!(
+                &req[..],
+                b"*4\r\n$4\r\nSCAN\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$5\r\nkey:*\r\n"
+            );
+            server.write(b"*2\r\n$2\r\n17\r\n*2\r\n$5\r\nkey:1\r\n$5\r\nkey:2\r\n");
+            let req = server.read().await.unwrap();
+            assert!(req.starts_with(b"*4\r\n$4\r\nSCAN\r\n$2\r\n17\r\n"));
+            server.write(b"*2\r\n$1\r\n0\r\n*2\r\n$5\r\nkey:2\r\n$5\r\nkey:3\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), vec!["key:1", "key:2", "key:3"]);
+    }
+
+    #[ntex::test]
+    async fn test_get_to() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let value: Vec<u8> = (0..1 << 20).map(|i| (i % 251) as u8).collect();
+        let value2 = value.clone();
+        let mut sink = Vec::new();
+        let (res, _) = join(redis.get_to("key", &mut sink), async {
+            server.read().await.unwrap();
+            server.write(format!("${}\r\n", value2.len()));
+            for chunk in value2.chunks(64 * 1024) {
+                server.write(chunk);
+                ntex::time::sleep(ntex::time::Millis(1)).await;
+            }
+            server.write(b"\r");
+            ntex::time::sleep(ntex::time::Millis(1)).await;
+            server.write(b"\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Some(value.len()));
+        assert_eq!(sink, value);
+
+        // missing key
+        let mut sink = Vec::new();
+        let (res, _) = join(redis.get_to("key", &mut sink), async {
+            server.read().await.unwrap();
+            server.write(b"$-1\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), None);
+        assert!(sink.is_empty());
+
+        // following responses are still in sync
+        let (res, _) = join(redis.exec(cmd::Get("key")), async {
+            server.read().await.unwrap();
+            server.write(b"$5\r\nvalue\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Some(Bytes::from_static(b"value")));
+    }
+
+    #[ntex::test]
+    async fn test_get_to_writer_error() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let mut buf = [0; 2];
+        let (res, _) = join(redis.get_to("key", &mut &mut buf[..]), async {
+            server.read().await.unwrap();
+            server.write(b"$5\r\nvalue\r\n");
+        })
+        .await;
+        assert!(matches!(
+            res,
+            Err(CommandError::Io(ref err)) if err.kind() == io::ErrorKind::WriteZero
+        ));
+        assert!(redis.is_connected());
+
+        let (res, _) = join(redis.exec(cmd::Get("key")), async {
+            server.read().await.unwrap();
+            server.write(b"$6\r\nvalue2\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Some(Bytes::from_static(b"value2")));
+    }
+
+    #[ntex::test]
+    async fn test_service_checks_request() {
+        let (redis, server) = test_client(Codec::new(), true);
+        let svc = ntex::service::Pipeline::new(redis.clone());
+
+        let res = svc.call(cmd::Subscribe(["test"]).to_request()).await;
+        assert!(matches!(res, Err(Error::Parse(ref msg)) if msg == PUBSUB_ERROR));
+        let res = svc.call(cmd::BLPop(["list"], 1.0).to_request()).await;
+        assert!(matches!(res, Err(Error::Parse(ref msg)) if msg == BLOCKING_ERROR));
+        assert!(server.read_any().is_empty());
+        assert!(redis.is_idle());
+
+        let (res, _) = join(svc.call(crate::array!["GET", "key"]), async {
+            server.read().await.unwrap();
+            server.write(b"$5\r\nvalue\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Response::Bytes(Bytes::from_static(b"value")));
+    }
+
+    #[ntex::test]
+    async fn test_flushdb() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let (res, _) = join(redis.flushdb(), async {
+            assert_eq!(server.read().await.unwrap(), "*1\r\n$7\r\nFLUSHDB\r\n");
+            server.write(b"+OK\r\n");
+        })
+        .await;
+        assert!(res.is_ok());
+
+        let (res, _) = join(redis.flushdb(), async {
+            server.read().await.unwrap();
+            server.write(b"-READONLY You can't write against a read only replica.\r\n");
+        })
+        .await;
+        assert!(matches!(res, Err(CommandError::Error(_))));
+    }
+
+    #[ntex::test]
+    async fn test_exec_raw() {
+        let (redis, server) = test_client(Codec::new(), true);
+
+        let (res, _) = join(redis.exec_raw(cmd::Get("key")), async {
+            server.read().await.unwrap();
+            server.write(b"$5\r\nvalue\r\n");
+        })
+        .await;
+        match res.unwrap() {
+            Response::Bytes(val) => assert_eq!(val, "value"),
+            res => panic!("Unexpected response: {:?}", res),
+        }
+
+        // unexpected response for typed command
+        let (res, _) = join(redis.exec_raw(cmd::Get("key")), async {
+            server.read().await.unwrap();
+            server.write(b":1\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Response::Integer(1));
+
+        let (res, _) = join(redis.exec_raw(cmd::Get("key")), async {
+            server.read().await.unwrap();
+            server.write(b"-ERR error\r\n");
+        })
+        .await;
+        assert!(matches!(res, Err(CommandError::Error(_))));
+    }
+
+    #[ntex::test]
+    async fn test_strict_protocol_unexpected_response() {
+        let (redis, server) = test_client(Codec::new(), true);
+        assert!(redis.is_connected());
+
+        // unsolicited reply, nothing is pending
+        server.write(b"+OK\r\n");
+        ntex::time::sleep(ntex::time::Millis(50)).await;
+        assert!(!redis.is_connected());
+    }
+}
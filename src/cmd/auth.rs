@@ -12,6 +12,18 @@ where
     ]))
 }
 
+/// AUTH redis command with username (ACL)
+pub fn AuthUser<U, T>(username: U, password: T) -> AuthCommand
+where
+    BulkString: From<U> + From<T>,
+{
+    AuthCommand(Request::Array(vec![
+        Request::from_static("AUTH"),
+        Request::BulkString(username.into()),
+        Request::BulkString(password.into()),
+    ]))
+}
+
 pub struct AuthCommand(Request);
 
 impl Command for AuthCommand {
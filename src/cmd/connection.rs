@@ -1,7 +1,7 @@
 use ntex::util::ByteString;
 
-use super::{Command, CommandError};
-use crate::codec::{Request, Response};
+use super::{utils::BulkOutputCommand, Command, CommandError};
+use crate::codec::{BulkString, Protocol, Request, Response};
 
 /// SELECT redis command
 ///
@@ -83,7 +83,7 @@ impl Command for PingCommand {
     fn to_output(val: Response) -> Result<Self::Output, CommandError> {
         match val {
             Response::String(val) => Ok(val),
-            Response::Error(val) => Err(CommandError::Error(val)),
+            Response::Error(val) => Err(val.into()),
             _ => Err(CommandError::Output("Unknown response", val)),
         }
     }
@@ -122,8 +122,294 @@ impl Command for ResetCommand {
     fn to_output(val: Response) -> Result<Self::Output, CommandError> {
         match val {
             Response::String(val) => Ok(val),
-            Response::Error(val) => Err(CommandError::Error(val)),
+            Response::Error(val) => Err(val.into()),
             _ => Err(CommandError::Output("Unknown response", val)),
         }
     }
 }
+
+/// CLIENT TRACKING redis command
+///
+/// Controls server-assisted client side caching. Tracking is enabled by default,
+/// invalidation messages are available via `Client::invalidations()`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, codec::Protocol, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     redis.hello(Protocol::Resp3).await?;
+///
+///     // enable tracking for all keys with "user:" prefix
+///     redis.exec(cmd::ClientTracking().bcast().prefix("user:")).await?;
+///     let invalidations = redis.invalidations();
+///
+///     Ok(())
+/// }
+/// ```
+pub fn ClientTracking() -> ClientTrackingCommand {
+    ClientTrackingCommand {
+        on: true,
+        redirect: None,
+        prefixes: Vec::new(),
+        bcast: false,
+        optin: false,
+    }
+}
+
+pub struct ClientTrackingCommand {
+    on: bool,
+    redirect: Option<i64>,
+    prefixes: Vec<BulkString>,
+    bcast: bool,
+    optin: bool,
+}
+
+impl ClientTrackingCommand {
+    /// Enable tracking
+    pub fn on(mut self) -> Self {
+        self.on = true;
+        self
+    }
+
+    /// Disable tracking
+    pub fn off(mut self) -> Self {
+        self.on = false;
+        self
+    }
+
+    /// Send invalidation messages to the connection with specified id
+    pub fn redirect(mut self, client_id: i64) -> Self {
+        self.redirect = Some(client_id);
+        self
+    }
+
+    /// Track only keys with specified prefix, could be used multiple times.
+    ///
+    /// Prefixes are supported only in broadcasting mode.
+    pub fn prefix<T>(mut self, prefix: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        self.prefixes.push(prefix.into());
+        self
+    }
+
+    /// Enable broadcasting mode
+    pub fn bcast(mut self) -> Self {
+        self.bcast = true;
+        self
+    }
+
+    /// Track keys only for commands after `CLIENT CACHING yes`
+    pub fn optin(mut self) -> Self {
+        self.optin = true;
+        self
+    }
+}
+
+impl Command for ClientTrackingCommand {
+    type Output = ();
+
+    fn to_request(self) -> Request {
+        let mut req = vec![
+            Request::from_static("CLIENT"),
+            Request::from_static("TRACKING"),
+            Request::from_static(if self.on { "ON" } else { "OFF" }),
+        ];
+        if let Some(id) = self.redirect {
+            req.push(Request::from_static("REDIRECT"));
+            req.push(Request::BulkInteger(id));
+        }
+        for prefix in self.prefixes {
+            req.push(Request::from_static("PREFIX"));
+            req.push(Request::BulkString(prefix));
+        }
+        if self.bcast {
+            req.push(Request::from_static("BCAST"));
+        }
+        if self.optin {
+            req.push(Request::from_static("OPTIN"));
+        }
+        Request::Array(req)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::String(ref s) if s == "OK" => Ok(()),
+            _ => Err(CommandError::Output("Unexpected value", val)),
+        }
+    }
+}
+
+/// CLIENT SETNAME redis command
+///
+/// Assigns a name to the current connection.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     // set connection name
+///     redis.exec(cmd::ClientSetName("worker-1")).await?;
+///
+///     let name = redis.exec(cmd::ClientGetName()).await?;
+///     assert_eq!(name.unwrap(), "worker-1");
+///
+///     Ok(())
+/// }
+/// ```
+pub fn ClientSetName<T>(name: T) -> ClientSetNameCommand
+where
+    BulkString: From<T>,
+{
+    ClientSetNameCommand(Request::Array(vec![
+        Request::from_static("CLIENT"),
+        Request::from_static("SETNAME"),
+        Request::BulkString(name.into()),
+    ]))
+}
+
+pub struct ClientSetNameCommand(Request);
+
+impl Command for ClientSetNameCommand {
+    type Output = ();
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::String(ref s) if s == "OK" => Ok(()),
+            _ => Err(CommandError::Output("Unexpected value", val)),
+        }
+    }
+}
+
+/// CLIENT GETNAME redis command
+///
+/// Returns the name of the current connection as set by `CLIENT SETNAME`.
+pub fn ClientGetName() -> BulkOutputCommand {
+    BulkOutputCommand(Request::Array(vec![
+        Request::from_static("CLIENT"),
+        Request::from_static("GETNAME"),
+    ]))
+}
+
+/// CLIENT NO-EVICT redis command
+///
+/// Sets client eviction mode for the current connection. When turned on,
+/// connection is excluded from client eviction even if `maxmemory-clients`
+/// limit is reached. Command is available since redis 7.0.
+pub fn ClientNoEvict(on: bool) -> ClientFlagCommand {
+    ClientFlagCommand::new("NO-EVICT", on)
+}
+
+/// CLIENT NO-TOUCH redis command
+///
+/// When turned on, commands sent by the current connection do not alter
+/// LRU/LFU of the keys they access, unless `TOUCH` command is sent.
+/// Command is available since redis 7.2.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+///
+///     // read keys without affecting eviction
+///     redis.exec(cmd::ClientNoTouch(true)).await?;
+///     redis.exec(cmd::Get("key")).await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn ClientNoTouch(on: bool) -> ClientFlagCommand {
+    ClientFlagCommand::new("NO-TOUCH", on)
+}
+
+pub struct ClientFlagCommand(Request);
+
+impl ClientFlagCommand {
+    fn new(flag: &'static str, on: bool) -> Self {
+        ClientFlagCommand(Request::Array(vec![
+            Request::from_static("CLIENT"),
+            Request::from_static(flag),
+            Request::from_static(if on { "ON" } else { "OFF" }),
+        ]))
+    }
+}
+
+impl Command for ClientFlagCommand {
+    type Output = ();
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        ClientSetNameCommand::to_output(val)
+    }
+}
+
+/// HELLO redis command
+///
+/// Switches connection protocol. Protocol switch must be coordinated
+/// with connection's codec, use `Client::hello()` or `SimpleClient::hello()`.
+pub(crate) fn Hello(protocol: Protocol) -> HelloCommand {
+    let version = match protocol {
+        Protocol::Resp2 => 2,
+        Protocol::Resp3 => 3,
+    };
+    HelloCommand(Request::Array(vec![
+        Request::from_static("HELLO"),
+        Request::BulkInteger(version),
+    ]))
+}
+
+pub(crate) struct HelloCommand(Request);
+
+impl Command for HelloCommand {
+    type Output = ();
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Map(_) | Response::Array(_) => Ok(()),
+            _ => Err(CommandError::Output("Unexpected HELLO response", val)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_client_flags() {
+        let req = ClientNoTouch(true).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*3\r\n$6\r\nCLIENT\r\n$8\r\nNO-TOUCH\r\n$2\r\nON\r\n"[..]
+        );
+        let req = ClientNoEvict(false).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*3\r\n$6\r\nCLIENT\r\n$8\r\nNO-EVICT\r\n$3\r\nOFF\r\n"[..]
+        );
+
+        assert!(ClientFlagCommand::to_output(Response::String("OK".into())).is_ok());
+        let res = ClientFlagCommand::to_output(Response::Integer(1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+}
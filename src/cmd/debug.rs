@@ -0,0 +1,61 @@
+use std::convert::TryFrom;
+
+use ntex::util::ByteString;
+
+use super::{Command, CommandError};
+use crate::codec::{BulkString, Request, Response};
+
+/// DEBUG SLEEP redis command
+///
+/// Blocks redis server for the specified number of seconds.
+/// Redis server must be started with `enable-debug-command` option.
+pub fn DebugSleep(seconds: f64) -> DebugSleepCommand {
+    DebugSleepCommand(Request::Array(vec![
+        Request::from_static("DEBUG"),
+        Request::from_static("SLEEP"),
+        Request::BulkString(seconds.to_string().into()),
+    ]))
+}
+
+pub struct DebugSleepCommand(Request);
+
+impl Command for DebugSleepCommand {
+    type Output = ();
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(<()>::try_from(val)?)
+    }
+}
+
+/// DEBUG OBJECT redis command
+///
+/// Returns debugging information about the object stored at `key`.
+/// Redis server must be started with `enable-debug-command` option.
+pub fn DebugObject<T>(key: T) -> DebugObjectCommand
+where
+    BulkString: From<T>,
+{
+    DebugObjectCommand(Request::Array(vec![
+        Request::from_static("DEBUG"),
+        Request::from_static("OBJECT"),
+        Request::BulkString(key.into()),
+    ]))
+}
+
+pub struct DebugObjectCommand(Request);
+
+impl Command for DebugObjectCommand {
+    type Output = ByteString;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(ByteString::try_from(val)?)
+    }
+}
@@ -0,0 +1,112 @@
+use std::convert::TryFrom;
+
+use super::{Command, CommandError};
+use crate::codec::{BulkString, Request, Response};
+
+/// GEODIST redis command
+///
+/// Returns the distance between two members of the geospatial index
+/// at `key`, or `None` if one or both members are missing. Distance is
+/// returned in meters by default.
+///
+/// ```rust
+/// use ntex_redis::{cmd, cmd::GeoUnit, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let distance = redis
+///         .exec(cmd::GeoDist("cities", "Palermo", "Catania").unit(GeoUnit::Km))
+///         .await?;
+///     println!("distance: {:?}", distance);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn GeoDist<T, V, U>(key: T, member1: V, member2: U) -> GeoDistCommand
+where
+    BulkString: From<T> + From<V> + From<U>,
+{
+    GeoDistCommand {
+        req: vec![
+            Request::from_static("GEODIST"),
+            Request::BulkString(key.into()),
+            Request::BulkString(member1.into()),
+            Request::BulkString(member2.into()),
+        ],
+        unit: None,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Unit of distance
+pub enum GeoUnit {
+    M,
+    Km,
+    Mi,
+    Ft,
+}
+
+pub struct GeoDistCommand {
+    req: Vec<Request>,
+    unit: Option<GeoUnit>,
+}
+
+impl GeoDistCommand {
+    /// Return distance in specified unit
+    pub fn unit(mut self, unit: GeoUnit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+}
+
+impl Command for GeoDistCommand {
+    type Output = Option<f64>;
+
+    fn to_request(mut self) -> Request {
+        if let Some(unit) = self.unit {
+            self.req.push(Request::from_static(match unit {
+                GeoUnit::M => "m",
+                GeoUnit::Km => "km",
+                GeoUnit::Mi => "mi",
+                GeoUnit::Ft => "ft",
+            }));
+        }
+        Request::Array(self.req)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::{codec::Decoder, util::BytesMut};
+
+    use super::*;
+    use crate::codec::{encode_request, Codec};
+
+    #[test]
+    fn test_geodist() {
+        let req = GeoDist("key", "a", "b").unit(GeoUnit::Km).to_request();
+        assert_eq!(
+            encode_request(&req),
+            encode_request(&crate::array!["GEODIST", "key", "a", "b", "km"])
+        );
+
+        let resp2 = Codec::new()
+            .decode(&mut BytesMut::copy_from_slice(b"$9\r\n166.27415\r\n"))
+            .unwrap()
+            .unwrap();
+        let resp3 = Codec::resp3()
+            .decode(&mut BytesMut::copy_from_slice(b",166.27415\r\n"))
+            .unwrap()
+            .unwrap();
+        let dist2 = GeoDistCommand::to_output(resp2).unwrap();
+        let dist3 = GeoDistCommand::to_output(resp3).unwrap();
+        assert_eq!(dist2, Some(166.27415));
+        assert_eq!(dist2, dist3);
+    }
+}
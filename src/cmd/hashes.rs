@@ -1,8 +1,8 @@
-use std::convert::TryFrom;
+use std::{convert::TryFrom, iter::FromIterator, marker::PhantomData};
 
 use ntex::util::{Bytes, HashMap};
 
-use super::{utils, Command, CommandError};
+use super::{commands::ScanCommand, utils, Arg, Command, CommandError};
 use crate::codec::{BulkString, Request, Response};
 
 /// HGET redis command
@@ -33,12 +33,13 @@ use crate::codec::{BulkString, Request, Response};
 /// ```
 pub fn HGet<T, V>(key: T, field: V) -> utils::BulkOutputCommand
 where
-    BulkString: From<T> + From<V>,
+    BulkString: From<T>,
+    V: Arg,
 {
     utils::BulkOutputCommand(Request::Array(vec![
         Request::from_static("HGET"),
         Request::BulkString(key.into()),
-        Request::BulkString(field.into()),
+        field.to_request(),
     ]))
 }
 
@@ -69,6 +70,81 @@ impl Command for HGetAllCommand {
     }
 }
 
+impl HGetAllCommand {
+    /// Collect fields and values into custom collection
+    ///
+    /// Collection type defines types of fields and values, i.e.
+    /// `BTreeMap<String, Bytes>` or `Vec<(ByteString, i64)>`.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///
+    ///     let fields = redis
+    ///         .exec(cmd::HGetAll("user").collect::<BTreeMap<String, String>>())
+    ///         .await?;
+    ///     for (field, value) in fields {
+    ///         println!("{}: {}", field, value);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn collect<M>(self) -> HGetAllCollectCommand<M> {
+        HGetAllCollectCommand(self.0, PhantomData)
+    }
+}
+
+pub struct HGetAllCollectCommand<M>(Vec<Request>, PhantomData<M>);
+
+impl<M, K, V> Command for HGetAllCollectCommand<M>
+where
+    M: FromIterator<(K, V)> + IntoIterator<Item = (K, V)>,
+    K: TryFrom<Response, Error = (&'static str, Response)>,
+    V: TryFrom<Response, Error = (&'static str, Response)>,
+{
+    type Output = M;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(val.into_pairs::<K, V>()?.into_iter().collect())
+    }
+}
+
+/// HGETALL redis command
+///
+/// Returns all fields and values of the hash stored at `key` in the order
+/// they are returned by redis.
+pub fn HGetAllOrdered<T>(key: T) -> HGetAllOrderedCommand
+where
+    BulkString: From<T>,
+{
+    HGetAllOrderedCommand(vec![
+        Request::from_static("HGETALL"),
+        Request::BulkString(key.into()),
+    ])
+}
+
+pub struct HGetAllOrderedCommand(Vec<Request>);
+
+impl Command for HGetAllOrderedCommand {
+    type Output = Vec<(Bytes, Bytes)>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(val.into_pairs()?)
+    }
+}
+
 /// HSET redis command
 ///
 /// Sets field in the hash stored at key to value.
@@ -97,36 +173,93 @@ impl Command for HGetAllCommand {
 /// ```
 pub fn HSet<T, K, V>(key: T, field: K, value: V) -> HSetCommand
 where
-    BulkString: From<T> + From<K> + From<V>,
+    BulkString: From<T>,
+    K: Arg,
+    V: Arg,
+{
+    HSetCommand(vec![
+        Request::from_static("HSET"),
+        Request::BulkString(key.into()),
+        field.to_request(),
+        value.to_request(),
+    ])
+}
+
+/// HSET redis command for many fields
+///
+/// Sets all `entries` of the hash stored at `key`. Returns number of
+/// added fields. At least one entry is required.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     let mut map = HashMap::new();
+///     map.insert("field1", "value1");
+///     map.insert("field2", "value2");
+///
+///     let added = redis.exec(cmd::HSetMap(&key, map)).await?;
+///     assert_eq!(added, 2);
+///     Ok(())
+/// }
+/// ```
+pub fn HSetMap<T, K, V>(key: T, entries: impl IntoIterator<Item = (K, V)>) -> HSetCommand
+where
+    BulkString: From<T>,
+    K: Arg,
+    V: Arg,
 {
     HSetCommand(vec![
         Request::from_static("HSET"),
         Request::BulkString(key.into()),
-        Request::BulkString(field.into()),
-        Request::BulkString(value.into()),
     ])
+    .entries(entries)
 }
 
 pub struct HSetCommand(Vec<Request>);
 
 impl HSetCommand {
+    /// Insert many entries to a redis hashmap
+    pub fn entries<K, V>(mut self, entries: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Arg,
+        V: Arg,
+    {
+        for (field, value) in entries {
+            self.0.push(field.to_request());
+            self.0.push(value.to_request());
+        }
+        self
+    }
+
     /// Insert new entry to a redis hashmap
     pub fn entry<K, V>(mut self, field: K, value: V) -> Self
     where
-        BulkString: From<K> + From<V>,
+        K: Arg,
+        V: Arg,
     {
-        self.0.push(field.into());
-        self.0.push(value.into());
+        self.0.push(field.to_request());
+        self.0.push(value.to_request());
         self
     }
 
     /// Insert new entry to a redis hashmap
     pub fn add_entry<K, V>(&mut self, field: K, value: V)
     where
-        BulkString: From<K> + From<V>,
+        K: Arg,
+        V: Arg,
     {
-        self.0.push(field.into());
-        self.0.push(value.into());
+        self.0.push(field.to_request());
+        self.0.push(value.to_request());
     }
 }
 
@@ -267,6 +400,335 @@ where
         Request::from_static("HINCRBY"),
         Request::BulkString(key.into()),
         Request::BulkString(field.into()),
-        Request::BulkString(i64::from(increment).to_string().into()),
+        Request::BulkInteger(i64::from(increment)),
     ]))
 }
+
+/// HRANDFIELD redis command
+///
+/// Returns a random field from the hash value stored at `key`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // create hashmap
+///     redis.exec(cmd::HSet(&key, "field1", "1").entry("field2", "2")).await?;
+///
+///     // get two random fields with values
+///     let entries = redis.exec(cmd::HRandField(&key).count(2).with_values()).await?;
+///
+///     assert_eq!(entries.len(), 2);
+///     Ok(())
+/// }
+/// ```
+pub fn HRandField<T>(key: T) -> HRandFieldCommand
+where
+    BulkString: From<T>,
+{
+    HRandFieldCommand(vec![
+        Request::from_static("HRANDFIELD"),
+        Request::BulkString(key.into()),
+    ])
+}
+
+pub struct HRandFieldCommand(Vec<Request>);
+
+impl HRandFieldCommand {
+    /// Return up to `count` distinct fields.
+    ///
+    /// If `count` is negative, the same field can be returned multiple times.
+    pub fn count(mut self, count: i64) -> HRandFieldCountCommand {
+        self.0.push(Request::BulkInteger(count));
+        HRandFieldCountCommand(self.0)
+    }
+}
+
+impl Command for HRandFieldCommand {
+    type Output = Option<Bytes>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        utils::BulkOutputCommand::to_output(val)
+    }
+}
+
+pub struct HRandFieldCountCommand(Vec<Request>);
+
+impl HRandFieldCountCommand {
+    /// Return fields together with their values.
+    pub fn with_values(mut self) -> HRandFieldWithValuesCommand {
+        self.0.push(Request::from_static("WITHVALUES"));
+        HRandFieldWithValuesCommand(self.0)
+    }
+}
+
+impl Command for HRandFieldCountCommand {
+    type Output = Vec<Bytes>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+pub struct HRandFieldWithValuesCommand(Vec<Request>);
+
+impl Command for HRandFieldWithValuesCommand {
+    type Output = Vec<(Bytes, Bytes)>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            // resp3 returns list of [field, value] arrays
+            Response::Array(ref ary) if matches!(ary.first(), Some(Response::Array(_))) => {
+                Ok(Vec::try_from(val)?)
+            }
+            val => Ok(val.into_pairs()?),
+        }
+    }
+}
+
+/// HSCAN redis command
+///
+/// Incrementally iterates over fields and values of the hash stored at `key`.
+/// Command returns next cursor and a batch of entries, iteration is complete
+/// when returned cursor is 0.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // create hashmap
+///     redis.exec(cmd::HSet(&key, "field1", "1").entry("field2", "2")).await?;
+///
+///     // iterate over hash fields
+///     let mut cursor = 0;
+///     loop {
+///         let (next, fields) = redis.exec(cmd::HScan(&key, cursor).novalues()).await?;
+///         println!("fields: {:?}", fields);
+///
+///         if next == 0 {
+///             break;
+///         }
+///         cursor = next;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn HScan<T>(key: T, cursor: u64) -> HScanCommand
+where
+    BulkString: From<T>,
+{
+    HScanCommand(vec![
+        Request::from_static("HSCAN"),
+        Request::BulkString(key.into()),
+        cursor.to_request(),
+    ])
+}
+
+pub struct HScanCommand(Vec<Request>);
+
+impl HScanCommand {
+    /// Return only fields matching glob-style pattern
+    pub fn pattern<T>(mut self, pattern: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        self.0.push(Request::from_static("MATCH"));
+        self.0.push(Request::BulkString(pattern.into()));
+        self
+    }
+
+    /// Amount of work done at every call, it is a hint for redis
+    pub fn count(mut self, count: usize) -> Self {
+        self.0.push(Request::from_static("COUNT"));
+        self.0.push(count.to_request());
+        self
+    }
+
+    /// Return only field names, without values.
+    ///
+    /// Option is available since redis 7.4.
+    pub fn novalues(mut self) -> HScanNoValuesCommand {
+        self.0.push(Request::from_static("NOVALUES"));
+        HScanNoValuesCommand(self.0)
+    }
+}
+
+impl Command for HScanCommand {
+    type Output = (u64, Vec<(Bytes, Bytes)>);
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        let (cursor, items) = ScanCommand::to_output(val)?;
+        if items.len() % 2 != 0 {
+            return Err(CommandError::Output(
+                "Cannot convert an odd number of elements into pairs",
+                Response::Nil,
+            ));
+        }
+        let mut entries = Vec::with_capacity(items.len() / 2);
+        let mut items = items.into_iter();
+        while let (Some(field), Some(value)) = (items.next(), items.next()) {
+            entries.push((field, value));
+        }
+        Ok((cursor, entries))
+    }
+}
+
+pub struct HScanNoValuesCommand(Vec<Request>);
+
+impl Command for HScanNoValuesCommand {
+    type Output = (u64, Vec<Bytes>);
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        ScanCommand::to_output(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_hrandfield_with_values_output() {
+        let expected = vec![
+            (Bytes::from_static(b"a"), Bytes::from_static(b"1")),
+            (Bytes::from_static(b"b"), Bytes::from_static(b"2")),
+        ];
+
+        let res = HRandFieldWithValuesCommand::to_output(Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"a")),
+            Response::Bytes(Bytes::from_static(b"1")),
+            Response::Bytes(Bytes::from_static(b"b")),
+            Response::Bytes(Bytes::from_static(b"2")),
+        ]));
+        assert_eq!(res.unwrap(), expected);
+
+        // resp3 nested pairs
+        let res = HRandFieldWithValuesCommand::to_output(Response::Array(vec![
+            Response::Array(vec![
+                Response::Bytes(Bytes::from_static(b"a")),
+                Response::Bytes(Bytes::from_static(b"1")),
+            ]),
+            Response::Array(vec![
+                Response::Bytes(Bytes::from_static(b"b")),
+                Response::Bytes(Bytes::from_static(b"2")),
+            ]),
+        ]));
+        assert_eq!(res.unwrap(), expected);
+
+        let res = HRandFieldWithValuesCommand::to_output(Response::Array(vec![]));
+        assert!(res.unwrap().is_empty());
+
+        let res = HRandFieldWithValuesCommand::to_output(Response::Array(vec![Response::Bytes(
+            Bytes::from_static(b"a"),
+        )]));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+
+    #[test]
+    fn test_hset_map() {
+        let mut map = BTreeMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+
+        let req = HSetMap("key", map).entries([("c", 3)]).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*8\r\n$4\r\nHSET\r\n$3\r\nkey\r\n$1\r\na\r\n$1\r\n1\r\n\
+               $1\r\nb\r\n$1\r\n2\r\n$1\r\nc\r\n$1\r\n3\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_hgetall_collect() {
+        let response = Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"b")),
+            Response::Bytes(Bytes::from_static(b"2")),
+            Response::Bytes(Bytes::from_static(b"a")),
+            Response::Bytes(Bytes::from_static(b"1")),
+        ]);
+
+        let map = HGetAllCollectCommand::<BTreeMap<String, String>>::to_output(response.clone())
+            .unwrap();
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(map["a"], "1");
+
+        let pairs = HGetAllCollectCommand::<Vec<(Bytes, Bytes)>>::to_output(response).unwrap();
+        assert_eq!(
+            pairs[0],
+            (Bytes::from_static(b"b"), Bytes::from_static(b"2"))
+        );
+    }
+
+    #[test]
+    fn test_hscan() {
+        let req = HScan("key", 0).pattern("f*").count(10).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*7\r\n$5\r\nHSCAN\r\n$3\r\nkey\r\n$1\r\n0\r\n$5\r\nMATCH\r\n\
+               $2\r\nf*\r\n$5\r\nCOUNT\r\n$2\r\n10\r\n"[..]
+        );
+        let req = HScan("key", 5).novalues().to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*4\r\n$5\r\nHSCAN\r\n$3\r\nkey\r\n$1\r\n5\r\n$8\r\nNOVALUES\r\n"[..]
+        );
+
+        let bytes = |s: &'static str| Response::Bytes(Bytes::from_static(s.as_bytes()));
+        let response = Response::Array(vec![
+            bytes("7"),
+            Response::Array(vec![bytes("a"), bytes("1"), bytes("b"), bytes("2")]),
+        ]);
+        let (cursor, entries) = HScanCommand::to_output(response).unwrap();
+        assert_eq!(cursor, 7);
+        assert_eq!(
+            entries,
+            vec![("a".into(), "1".into()), ("b".into(), "2".into())]
+        );
+
+        let response = Response::Array(vec![bytes("0"), Response::Array(vec![bytes("a")])]);
+        assert!(HScanCommand::to_output(response.clone()).is_err());
+        let (cursor, fields) = HScanNoValuesCommand::to_output(response).unwrap();
+        assert_eq!(cursor, 0);
+        assert_eq!(fields, vec!["a"]);
+    }
+}
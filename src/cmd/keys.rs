@@ -1,6 +1,7 @@
 use std::convert::{TryFrom, TryInto};
+use std::time::Duration;
 
-use ntex::util::ByteString;
+use ntex::util::{ByteString, Bytes};
 
 use super::{utils, Command, CommandError};
 use crate::codec::{BulkString, Request, Response};
@@ -81,6 +82,40 @@ where
     ])
 }
 
+/// EXISTS redis command for single key
+///
+/// Returns true if key exists.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     assert!(!redis.exec(cmd::KeyExists(&key)).await?);
+///
+///     // set string value
+///     redis.exec(cmd::Set(&key, "value")).await?;
+///     assert!(redis.exec(cmd::KeyExists(&key)).await?);
+///     Ok(())
+/// }
+/// ```
+pub fn KeyExists<T>(key: T) -> utils::BoolOutputCommand
+where
+    BulkString: From<T>,
+{
+    utils::BoolOutputCommand(Request::Array(vec![
+        Request::from_static("EXISTS"),
+        Request::BulkString(key.into()),
+    ]))
+}
+
 pub struct KeysCommand(Vec<Request>);
 
 impl KeysCommand {
@@ -129,7 +164,7 @@ where
     utils::BoolOutputCommand(Request::Array(vec![
         Request::from_static("EXPIRE"),
         Request::BulkString(key.into()),
-        Request::BulkString(i64::from(seconds).to_string().into()),
+        Request::BulkInteger(i64::from(seconds)),
     ]))
 }
 
@@ -144,7 +179,64 @@ where
     utils::BoolOutputCommand(Request::Array(vec![
         Request::from_static("EXPIREAT"),
         Request::BulkString(key.into()),
-        Request::BulkString(i64::from(timestamp).to_string().into()),
+        Request::BulkInteger(i64::from(timestamp)),
+    ]))
+}
+
+/// PEXPIREAT redis command
+///
+/// Set a timeout on `key`, timestamp is specified in milliseconds.
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // set string value
+///     redis.exec(cmd::Set(&key, "value")).await?;
+///
+///     // expire key in 5 seconds
+///     let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?
+///         + Duration::from_secs(5);
+///     let result = redis.exec(cmd::PExpireAt(&key, timestamp.as_millis() as i64)).await?;
+///
+///     assert!(result);
+///     Ok(())
+/// }
+/// ```
+pub fn PExpireAt<T, S>(key: T, timestamp: S) -> utils::BoolOutputCommand
+where
+    BulkString: From<T>,
+    i64: From<S>,
+{
+    utils::BoolOutputCommand(Request::Array(vec![
+        Request::from_static("PEXPIREAT"),
+        Request::BulkString(key.into()),
+        Request::BulkInteger(i64::from(timestamp)),
+    ]))
+}
+
+/// MOVE redis command
+///
+/// Move `key` from the currently selected database to the specified
+/// destination database. Command returns false if key does not exist
+/// or already exists in the destination database.
+pub fn Move<T>(key: T, db: u32) -> utils::BoolOutputCommand
+where
+    BulkString: From<T>,
+{
+    utils::BoolOutputCommand(Request::Array(vec![
+        Request::from_static("MOVE"),
+        Request::BulkString(key.into()),
+        Request::BulkInteger(i64::from(db)),
     ]))
 }
 
@@ -168,6 +260,18 @@ pub enum TtlResult {
     NotFound,
 }
 
+impl TtlResult {
+    /// Returns remaining time to live as `Duration`
+    ///
+    /// Returns `None` if key does not exist or has no associated expire.
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            TtlResult::Seconds(s) => u64::try_from(*s).ok().map(Duration::from_secs),
+            TtlResult::NoExpire | TtlResult::NotFound => None,
+        }
+    }
+}
+
 pub struct TtlCommand(Vec<Request>);
 
 impl Command for TtlCommand {
@@ -187,6 +291,57 @@ impl Command for TtlCommand {
     }
 }
 
+/// PTTL redis command
+///
+/// Returns the remaining time to live of a `key` that has a timeout, in milliseconds.
+pub fn PTtl<T>(key: T) -> PTtlCommand
+where
+    BulkString: From<T>,
+{
+    PTtlCommand(vec![
+        Request::from_static("PTTL"),
+        Request::BulkString(key.into()),
+    ])
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PTtlResult {
+    Millis(i64),
+    NoExpire,
+    NotFound,
+}
+
+impl PTtlResult {
+    /// Returns remaining time to live as `Duration`
+    ///
+    /// Returns `None` if key does not exist or has no associated expire.
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            PTtlResult::Millis(ms) => u64::try_from(*ms).ok().map(Duration::from_millis),
+            PTtlResult::NoExpire | PTtlResult::NotFound => None,
+        }
+    }
+}
+
+pub struct PTtlCommand(Vec<Request>);
+
+impl Command for PTtlCommand {
+    type Output = PTtlResult;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        let result = i64::try_from(val)?;
+        Ok(match result {
+            -1 => PTtlResult::NoExpire,
+            -2 => PTtlResult::NotFound,
+            s => PTtlResult::Millis(s),
+        })
+    }
+}
+
 /// KEYS redis command
 ///
 /// Returns all keys matching pattern.
@@ -240,3 +395,508 @@ impl Command for KeysPatternCommand {
         }
     }
 }
+
+/// Escape glob-style special characters in `key`
+///
+/// Resulting string could be used as a `KEYS` pattern that matches
+/// only the literal `key`.
+///
+/// ```rust
+/// use ntex_redis::cmd;
+///
+/// assert_eq!(cmd::keys::escape_pattern("a*b[c]"), "a\\*b\\[c\\]");
+/// ```
+pub fn escape_pattern(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    for ch in key.chars() {
+        if matches!(ch, '*' | '?' | '[' | ']' | '\\') {
+            result.push('\\');
+        }
+        result.push(ch);
+    }
+    result
+}
+
+/// SCAN redis command
+///
+/// Incrementally iterates over keys of the currently selected database.
+/// Command returns next cursor and a batch of keys, iteration is complete
+/// when returned cursor is 0.
+///
+/// ```rust
+/// use ntex_redis::{cmd, cmd::RedisType, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     // iterate over hash keys
+///     let mut cursor = 0;
+///     loop {
+///         let (next, keys) = redis
+///             .exec(cmd::Scan(cursor).pattern("user:*").type_filter(RedisType::Hash))
+///             .await?;
+///         println!("keys: {:?}", keys);
+///
+///         if next == 0 {
+///             break;
+///         }
+///         cursor = next;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn Scan(cursor: u64) -> ScanCommand {
+    ScanCommand {
+        cursor,
+        pattern: None,
+        count: None,
+        tp: None,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Type of value stored at key
+pub enum RedisType {
+    String,
+    List,
+    Set,
+    ZSet,
+    Hash,
+    Stream,
+}
+
+impl RedisType {
+    /// Type name as returned by `TYPE` command
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RedisType::String => "string",
+            RedisType::List => "list",
+            RedisType::Set => "set",
+            RedisType::ZSet => "zset",
+            RedisType::Hash => "hash",
+            RedisType::Stream => "stream",
+        }
+    }
+}
+
+pub struct ScanCommand {
+    cursor: u64,
+    pattern: Option<BulkString>,
+    count: Option<usize>,
+    tp: Option<RedisType>,
+}
+
+impl ScanCommand {
+    /// Return only keys matching glob-style pattern
+    pub fn pattern<T>(mut self, pattern: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Amount of work done at every call, it is a hint for redis
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Return only keys holding value of specified type
+    pub fn type_filter(mut self, tp: RedisType) -> Self {
+        self.tp = Some(tp);
+        self
+    }
+}
+
+impl Command for ScanCommand {
+    type Output = (u64, Vec<Bytes>);
+
+    fn to_request(self) -> Request {
+        let mut req = vec![
+            Request::from_static("SCAN"),
+            Request::BulkString(self.cursor.to_string().into()),
+        ];
+        if let Some(pattern) = self.pattern {
+            req.push(Request::from_static("MATCH"));
+            req.push(Request::BulkString(pattern));
+        }
+        if let Some(count) = self.count {
+            req.push(Request::from_static("COUNT"));
+            req.push(Request::BulkInteger(count as i64));
+        }
+        if let Some(tp) = self.tp {
+            req.push(Request::from_static("TYPE"));
+            req.push(Request::from_static(tp.as_str()));
+        }
+        Request::Array(req)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        let (cursor, keys) = <(Bytes, Vec<Bytes>)>::try_from(val)?;
+        match btoi::btou(&cursor) {
+            Ok(cursor) => Ok((cursor, keys)),
+            Err(_) => Err(CommandError::Output(
+                "Cannot parse cursor",
+                Response::Bytes(cursor),
+            )),
+        }
+    }
+}
+
+/// OBJECT FREQ redis command
+///
+/// Returns the logarithmic access frequency counter of the object stored at `key`.
+/// Command is available only if `maxmemory-policy` is set to an LFU policy,
+/// otherwise command fails with descriptive error.
+pub fn ObjectFreq<T>(key: T) -> ObjectFreqCommand
+where
+    BulkString: From<T>,
+{
+    ObjectFreqCommand(Request::Array(vec![
+        Request::from_static("OBJECT"),
+        Request::from_static("FREQ"),
+        Request::BulkString(key.into()),
+    ]))
+}
+
+pub struct ObjectFreqCommand(Request);
+
+impl Command for ObjectFreqCommand {
+    type Output = Option<i64>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+
+    fn to_error(err: ByteString) -> CommandError {
+        if err.starts_with("ERR An LFU maxmemory policy is not selected") {
+            CommandError::Error(ByteString::from_static(
+                "OBJECT FREQ requires an LFU maxmemory-policy (allkeys-lfu or volatile-lfu)",
+            ))
+        } else {
+            err.into()
+        }
+    }
+}
+
+/// OBJECT IDLETIME redis command
+///
+/// Returns the number of seconds since the object stored at `key` was
+/// last accessed, `None` if key does not exist. Command fails if
+/// `maxmemory-policy` is set to an LFU policy.
+pub fn ObjectIdleTime<T>(key: T) -> ObjectIdleTimeCommand
+where
+    BulkString: From<T>,
+{
+    ObjectIdleTimeCommand(Request::Array(vec![
+        Request::from_static("OBJECT"),
+        Request::from_static("IDLETIME"),
+        Request::BulkString(key.into()),
+    ]))
+}
+
+pub struct ObjectIdleTimeCommand(Request);
+
+impl Command for ObjectIdleTimeCommand {
+    type Output = Option<i64>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
+
+/// SORT redis command
+///
+/// Returns the elements contained in the list, set or sorted set at `key`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // create list
+///     redis.exec(cmd::RPush(&key, "b").extend(vec!["c", "a"])).await?;
+///
+///     // sort list
+///     let values = redis.exec(cmd::Sort(&key).alpha().desc()).await?;
+///
+///     assert_eq!(values, vec![Some("c".into()), Some("b".into()), Some("a".into())]);
+///     Ok(())
+/// }
+/// ```
+pub fn Sort<T>(key: T) -> SortCommand
+where
+    BulkString: From<T>,
+{
+    SortCommand(SortOptions::new("SORT", key.into()))
+}
+
+/// SORT_RO redis command
+///
+/// Read-only variant of the `SORT` command, it could be executed on read-only replicas.
+pub fn SortRo<T>(key: T) -> SortRoCommand
+where
+    BulkString: From<T>,
+{
+    SortRoCommand(SortOptions::new("SORT_RO", key.into()))
+}
+
+struct SortOptions {
+    req: Vec<Request>,
+    by: Option<BulkString>,
+    limit: Option<(i64, i64)>,
+    get: Vec<BulkString>,
+    desc: bool,
+    alpha: bool,
+}
+
+impl SortOptions {
+    fn new(cmd: &'static str, key: BulkString) -> Self {
+        SortOptions {
+            req: vec![Request::from_static(cmd), Request::BulkString(key)],
+            by: None,
+            limit: None,
+            get: Vec::new(),
+            desc: false,
+            alpha: false,
+        }
+    }
+
+    fn into_request(mut self) -> Vec<Request> {
+        // BY
+        if let Some(by) = self.by {
+            self.req.push(Request::from_static("BY"));
+            self.req.push(Request::BulkString(by));
+        }
+
+        // LIMIT
+        if let Some((offset, count)) = self.limit {
+            self.req.push(Request::from_static("LIMIT"));
+            self.req.push(Request::BulkInteger(offset));
+            self.req.push(Request::BulkInteger(count));
+        }
+
+        // GET
+        for pattern in self.get {
+            self.req.push(Request::from_static("GET"));
+            self.req.push(Request::BulkString(pattern));
+        }
+
+        // DESC
+        if self.desc {
+            self.req.push(Request::from_static("DESC"));
+        }
+
+        // ALPHA
+        if self.alpha {
+            self.req.push(Request::from_static("ALPHA"));
+        }
+
+        self.req
+    }
+}
+
+pub struct SortCommand(SortOptions);
+
+impl SortCommand {
+    /// Sort by external keys matching `pattern`.
+    pub fn by<T>(mut self, pattern: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        self.0.by = Some(pattern.into());
+        self
+    }
+
+    /// Limit number of returned elements.
+    pub fn limit(mut self, offset: i64, count: i64) -> Self {
+        self.0.limit = Some((offset, count));
+        self
+    }
+
+    /// Return external keys matching `pattern` instead of elements.
+    pub fn get<T>(mut self, pattern: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        self.0.get.push(pattern.into());
+        self
+    }
+
+    /// Sort elements in descending order.
+    pub fn desc(mut self) -> Self {
+        self.0.desc = true;
+        self
+    }
+
+    /// Sort elements lexicographically.
+    pub fn alpha(mut self) -> Self {
+        self.0.alpha = true;
+        self
+    }
+
+    /// Store result to the `destination` key instead of returning it.
+    pub fn store<T>(self, destination: T) -> SortStoreCommand
+    where
+        BulkString: From<T>,
+    {
+        let mut req = self.0.into_request();
+        req.push(Request::from_static("STORE"));
+        req.push(Request::BulkString(destination.into()));
+        SortStoreCommand(req)
+    }
+}
+
+impl Command for SortCommand {
+    type Output = Vec<Option<Bytes>>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0.into_request())
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+pub struct SortRoCommand(SortOptions);
+
+impl SortRoCommand {
+    /// Sort by external keys matching `pattern`.
+    pub fn by<T>(self, pattern: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        SortRoCommand(SortCommand(self.0).by(pattern).0)
+    }
+
+    /// Limit number of returned elements.
+    pub fn limit(self, offset: i64, count: i64) -> Self {
+        SortRoCommand(SortCommand(self.0).limit(offset, count).0)
+    }
+
+    /// Return external keys matching `pattern` instead of elements.
+    pub fn get<T>(self, pattern: T) -> Self
+    where
+        BulkString: From<T>,
+    {
+        SortRoCommand(SortCommand(self.0).get(pattern).0)
+    }
+
+    /// Sort elements in descending order.
+    pub fn desc(self) -> Self {
+        SortRoCommand(SortCommand(self.0).desc().0)
+    }
+
+    /// Sort elements lexicographically.
+    pub fn alpha(self) -> Self {
+        SortRoCommand(SortCommand(self.0).alpha().0)
+    }
+}
+
+impl Command for SortRoCommand {
+    type Output = Vec<Option<Bytes>>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0.into_request())
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+pub struct SortStoreCommand(Vec<Request>);
+
+impl Command for SortStoreCommand {
+    type Output = usize;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(usize::try_from(val)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_key_exists() {
+        let req = KeyExists("key").to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*2\r\n$6\r\nEXISTS\r\n$3\r\nkey\r\n"[..]
+        );
+        assert!(utils::BoolOutputCommand::to_output(Response::Integer(1)).unwrap());
+        assert!(!utils::BoolOutputCommand::to_output(Response::Integer(0)).unwrap());
+    }
+
+    #[test]
+    fn test_ttl_as_duration() {
+        assert_eq!(
+            TtlResult::Seconds(10).as_duration(),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(TtlResult::NoExpire.as_duration(), None);
+        assert_eq!(TtlResult::NotFound.as_duration(), None);
+
+        assert_eq!(
+            PTtlResult::Millis(1500).as_duration(),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(PTtlResult::NoExpire.as_duration(), None);
+        assert_eq!(PTtlResult::NotFound.as_duration(), None);
+    }
+
+    #[test]
+    fn test_scan() {
+        let req = Scan(10)
+            .pattern("key:*")
+            .count(100)
+            .type_filter(RedisType::ZSet)
+            .to_request();
+        assert_eq!(
+            encode_request(&req),
+            encode_request(&crate::array![
+                "SCAN", "10", "MATCH", "key:*", "COUNT", "100", "TYPE", "zset"
+            ])
+        );
+
+        let res = ScanCommand::to_output(Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"18446744073709551615")),
+            Response::Array(vec![Response::Bytes(Bytes::from_static(b"key:1"))]),
+        ]))
+        .unwrap();
+        assert_eq!(res, (u64::MAX, vec![Bytes::from_static(b"key:1")]));
+    }
+
+    #[test]
+    fn test_escape_pattern() {
+        assert_eq!(escape_pattern("key"), "key");
+        assert_eq!(escape_pattern("a*b[c]"), "a\\*b\\[c\\]");
+        assert_eq!(escape_pattern("a?b\\c"), "a\\?b\\\\c");
+        assert_eq!(escape_pattern(""), "");
+    }
+}
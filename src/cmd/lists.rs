@@ -1,6 +1,20 @@
-use super::{utils, Command, CommandError};
+use std::{convert::TryFrom, num::NonZeroU32};
+
+use ntex::util::Bytes;
+
+use super::{utils, Arg, Command, CommandError};
 use crate::codec::{BulkString, Request, Response};
 
+/// Values are pushed one by one, lua stack is too small for unpacking
+/// of large pushes
+const CAPPED_PUSH_SCRIPT: &str = "\
+local len = 0
+for i = 5, #ARGV do
+  len = redis.call(ARGV[1], KEYS[1], ARGV[i])
+end
+redis.call('LTRIM', KEYS[1], ARGV[2], ARGV[3])
+return math.min(len, tonumber(ARGV[4]))";
+
 /// LINDEX redis command
 ///
 /// Returns the element at index index in the list stored at key.
@@ -64,14 +78,14 @@ where
 ///     Ok(())
 /// }
 /// ```
-pub fn LPop<T>(key: T) -> utils::BulkOutputCommand
+pub fn LPop<T>(key: T) -> PopCommand
 where
     BulkString: From<T>,
 {
-    utils::BulkOutputCommand(Request::Array(vec![
+    PopCommand(vec![
         Request::from_static("LPOP"),
         Request::BulkString(key.into()),
-    ]))
+    ])
 }
 
 /// RPOP redis command
@@ -100,14 +114,76 @@ where
 ///     Ok(())
 /// }
 /// ```
-pub fn RPop<T>(key: T) -> utils::BulkOutputCommand
+pub fn RPop<T>(key: T) -> PopCommand
 where
     BulkString: From<T>,
 {
-    utils::BulkOutputCommand(Request::Array(vec![
+    PopCommand(vec![
         Request::from_static("RPOP"),
         Request::BulkString(key.into()),
-    ]))
+    ])
+}
+
+pub struct PopCommand(Vec<Request>);
+
+impl PopCommand {
+    /// Pop up to `count` elements (Redis 6.2).
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    /// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+    /// # fn gen_random_key() -> String {
+    /// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+    /// # }
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     let key = gen_random_key();
+    ///
+    ///     // create list with three values
+    ///     redis.exec(cmd::RPush(&key, "value1").extend(vec!["value2", "value3"])).await?;
+    ///
+    ///     // pop first two elements from the list
+    ///     let values = redis.exec(cmd::LPop(&key).count(2)).await?;
+    ///
+    ///     assert_eq!(values, vec!["value1", "value2"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn count(mut self, count: usize) -> PopCountCommand {
+        self.0.push(count.to_request());
+        PopCountCommand(self.0)
+    }
+}
+
+impl Command for PopCommand {
+    type Output = Option<Bytes>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        utils::BulkOutputCommand::to_output(val)
+    }
+}
+
+pub struct PopCountCommand(Vec<Request>);
+
+impl Command for PopCountCommand {
+    type Output = Vec<Bytes>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Nil => Ok(Vec::new()),
+            val => Ok(Vec::try_from(val)?),
+        }
+    }
 }
 
 /// LPUSH redis command
@@ -220,6 +296,76 @@ impl LPushCommand {
         self.0.extend(other.into_iter().map(|t| t.into()));
         self
     }
+
+    /// Keep only `max` most recently pushed elements of the list.
+    ///
+    /// Push and `LTRIM` are executed atomically with a lua script in one
+    /// round trip. Command returns resulting length of the list.
+    ///
+    /// ```rust
+    /// use std::num::NonZeroU32;
+    /// use ntex_redis::{cmd, RedisConnector};
+    /// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+    /// # fn gen_random_key() -> String {
+    /// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+    /// # }
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     let key = gen_random_key();
+    ///
+    ///     // keep last 2 log records
+    ///     let max = NonZeroU32::new(2).unwrap();
+    ///     let len = redis.exec(cmd::RPush(&key, "1").extend(["2", "3"]).capped(max)).await?;
+    ///     assert_eq!(len, 2);
+    ///
+    ///     let values = redis.exec(cmd::LRange(&key, 0, -1)).await?;
+    ///     assert_eq!(values, vec!["2", "3"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn capped(self, max: NonZeroU32) -> LPushCappedCommand {
+        // push command always starts with command name and key
+        let mut items = self.0.into_iter();
+        let (cmd, key) = (items.next().unwrap(), items.next().unwrap());
+
+        let max = i64::from(max.get());
+        let (start, stop) =
+            if cmd == Request::from_static("RPUSH") || cmd == Request::from_static("RPUSHX") {
+                (-max, -1)
+            } else {
+                (0, max - 1)
+            };
+
+        let mut req = vec![
+            Request::from_static("EVAL"),
+            Request::from_static(CAPPED_PUSH_SCRIPT),
+            Request::BulkInteger(1),
+            key,
+            cmd,
+            Request::BulkInteger(start),
+            Request::BulkInteger(stop),
+            Request::BulkInteger(max),
+        ];
+        req.extend(items);
+        LPushCappedCommand(req)
+    }
+}
+
+/// Push command with list size limit, see `LPushCommand::capped()`
+pub struct LPushCappedCommand(Vec<Request>);
+
+impl Command for LPushCappedCommand {
+    type Output = usize;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        LPushCommand::to_output(val)
+    }
 }
 
 impl Command for LPushCommand {
@@ -236,3 +382,632 @@ impl Command for LPushCommand {
         }
     }
 }
+
+/// LPOS redis command
+///
+/// Returns the index of the first element matching `element` in the list stored at `key`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // create list
+///     redis.exec(cmd::RPush(&key, "a").extend(vec!["b", "a"])).await?;
+///
+///     // find position of the second occurrence
+///     let pos = redis.exec(cmd::LPos(&key, "a").rank(2)).await?;
+///     assert_eq!(pos, Some(2));
+///
+///     // find all positions
+///     let pos = redis.exec(cmd::LPos(&key, "a").count(0)).await?;
+///     assert_eq!(pos, vec![0, 2]);
+///     Ok(())
+/// }
+/// ```
+pub fn LPos<T, V>(key: T, element: V) -> LPosCommand
+where
+    BulkString: From<T> + From<V>,
+{
+    LPosCommand(vec![
+        Request::from_static("LPOS"),
+        Request::BulkString(key.into()),
+        Request::BulkString(element.into()),
+    ])
+}
+
+pub struct LPosCommand(Vec<Request>);
+
+impl LPosCommand {
+    /// Skip first `rank - 1` matches. Negative rank searches from the tail.
+    pub fn rank(mut self, rank: i64) -> Self {
+        self.0.push(Request::from_static("RANK"));
+        self.0.push(Request::BulkInteger(rank));
+        self
+    }
+
+    /// Return positions of up to `count` matches, 0 means all matches.
+    pub fn count(mut self, count: usize) -> LPosCountCommand {
+        self.0.push(Request::from_static("COUNT"));
+        self.0.push(count.to_request());
+        LPosCountCommand(self.0)
+    }
+}
+
+impl Command for LPosCommand {
+    type Output = Option<i64>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
+
+pub struct LPosCountCommand(Vec<Request>);
+
+impl Command for LPosCountCommand {
+    type Output = Vec<i64>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+/// LRANGE redis command
+///
+/// Returns the specified elements of the list stored at `key`.
+pub fn LRange<T>(key: T, start: i64, stop: i64) -> LRangeCommand
+where
+    BulkString: From<T>,
+{
+    LRangeCommand(Request::Array(vec![
+        Request::from_static("LRANGE"),
+        Request::BulkString(key.into()),
+        Request::BulkInteger(start),
+        Request::BulkInteger(stop),
+    ]))
+}
+
+pub struct LRangeCommand(Request);
+
+impl Command for LRangeCommand {
+    type Output = Vec<Bytes>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+/// LINSERT redis command
+///
+/// Inserts `element` in the list stored at `key` either before or after
+/// the reference value `pivot`. Command returns the length of the list after
+/// the insert operation, -1 when the `pivot` was not found
+/// and 0 when `key` does not exist.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // create list
+///     redis.exec(cmd::RPush(&key, "a").value("c")).await?;
+///
+///     // insert value after pivot
+///     let len = redis.exec(cmd::LInsert(&key, "a", "b").after()).await?;
+///
+///     assert_eq!(len, 3);
+///     Ok(())
+/// }
+/// ```
+pub fn LInsert<T, P, V>(key: T, pivot: P, element: V) -> LInsertCommand
+where
+    BulkString: From<T> + From<P> + From<V>,
+{
+    LInsertCommand {
+        key: key.into(),
+        pivot: pivot.into(),
+        element: element.into(),
+        before: true,
+    }
+}
+
+pub struct LInsertCommand {
+    key: BulkString,
+    pivot: BulkString,
+    element: BulkString,
+    before: bool,
+}
+
+impl LInsertCommand {
+    /// Insert element before pivot, default.
+    pub fn before(mut self) -> Self {
+        self.before = true;
+        self
+    }
+
+    /// Insert element after pivot.
+    pub fn after(mut self) -> Self {
+        self.before = false;
+        self
+    }
+}
+
+impl Command for LInsertCommand {
+    type Output = i64;
+
+    fn to_request(self) -> Request {
+        Request::Array(vec![
+            Request::from_static("LINSERT"),
+            Request::BulkString(self.key),
+            if self.before {
+                Request::from_static("BEFORE")
+            } else {
+                Request::from_static("AFTER")
+            },
+            Request::BulkString(self.pivot),
+            Request::BulkString(self.element),
+        ])
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        utils::IntOutputCommand::to_output(val)
+    }
+}
+
+/// LMPOP redis command
+///
+/// Pops elements from the first non-empty list among provided `keys`.
+/// Command returns name of the list and popped elements, or `None`
+/// if all lists are empty. Elements are popped from the left side by default.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key1 = gen_random_key();
+///     let key2 = gen_random_key();
+///
+///     // create second list
+///     redis.exec(cmd::RPush(&key2, "a").extend(vec!["b", "c"])).await?;
+///
+///     // pop two elements from the right side
+///     let (key, values) = redis.exec(cmd::LMPop([&key1, &key2]).right().count(2)).await?.unwrap();
+///
+///     assert_eq!(key, key2);
+///     assert_eq!(values, vec!["c", "b"]);
+///     Ok(())
+/// }
+/// ```
+pub fn LMPop<T>(keys: impl IntoIterator<Item = T>) -> LMPopCommand
+where
+    BulkString: From<T>,
+{
+    LMPopCommand {
+        keys: keys
+            .into_iter()
+            .map(|k| Request::BulkString(k.into()))
+            .collect(),
+        left: true,
+        count: None,
+        timeout: None,
+    }
+}
+
+/// BLMPOP redis command
+///
+/// Blocking version of `LMPOP` command, blocks connection until
+/// element is available or `timeout` (in seconds) expires. Zero timeout
+/// blocks indefinitely. Blocking commands could be executed only
+/// by `SimpleClient`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+///     let key = gen_random_key();
+///
+///     // lists are empty, wait for 0.1 second
+///     let result = redis.exec(cmd::BLMPop(0.1, [&key]).right().count(2)).await?;
+///
+///     assert_eq!(result, None);
+///     Ok(())
+/// }
+/// ```
+pub fn BLMPop<T>(timeout: f64, keys: impl IntoIterator<Item = T>) -> LMPopCommand
+where
+    BulkString: From<T>,
+{
+    LMPopCommand {
+        timeout: Some(timeout),
+        ..LMPop(keys)
+    }
+}
+
+pub struct LMPopCommand {
+    keys: Vec<Request>,
+    left: bool,
+    count: Option<Request>,
+    timeout: Option<f64>,
+}
+
+impl LMPopCommand {
+    /// Pop elements from the head of the list.
+    pub fn left(mut self) -> Self {
+        self.left = true;
+        self
+    }
+
+    /// Pop elements from the tail of the list.
+    pub fn right(mut self) -> Self {
+        self.left = false;
+        self
+    }
+
+    /// Pop up to `count` elements, by default one element is popped.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count.to_request());
+        self
+    }
+}
+
+impl Command for LMPopCommand {
+    type Output = Option<(Bytes, Vec<Bytes>)>;
+
+    fn to_request(self) -> Request {
+        let mut req = Vec::with_capacity(self.keys.len() + 6);
+        if let Some(timeout) = self.timeout {
+            req.push(Request::from_static("BLMPOP"));
+            req.push(timeout.to_request());
+        } else {
+            req.push(Request::from_static("LMPOP"));
+        }
+        req.push(Request::BulkInteger(self.keys.len() as i64));
+        req.extend(self.keys);
+        req.push(Request::from_static(if self.left {
+            "LEFT"
+        } else {
+            "RIGHT"
+        }));
+        if let Some(count) = self.count {
+            req.push(Request::from_static("COUNT"));
+            req.push(count);
+        }
+        Request::Array(req)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Side of the list
+pub enum ListSide {
+    Left,
+    Right,
+}
+
+impl ListSide {
+    fn to_request(self) -> Request {
+        match self {
+            ListSide::Left => Request::from_static("LEFT"),
+            ListSide::Right => Request::from_static("RIGHT"),
+        }
+    }
+}
+
+/// LMOVE redis command
+///
+/// Atomically pops element from `from` side of the `source` list and
+/// pushes it to `to` side of the `destination` list. Returns moved
+/// element or `None` if source list is empty.
+///
+/// ```rust
+/// use ntex_redis::{cmd, cmd::ListSide, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let src = gen_random_key();
+///     let dst = gen_random_key();
+///
+///     redis.exec(cmd::RPush(&src, "a").value("b")).await?;
+///
+///     let value = redis
+///         .exec(cmd::LMove(&src, &dst, ListSide::Right, ListSide::Left))
+///         .await?;
+///     assert_eq!(value.unwrap(), "b");
+///     Ok(())
+/// }
+/// ```
+pub fn LMove<T, U>(
+    source: T,
+    destination: U,
+    from: ListSide,
+    to: ListSide,
+) -> utils::BulkOutputCommand
+where
+    BulkString: From<T> + From<U>,
+{
+    utils::BulkOutputCommand(Request::Array(vec![
+        Request::from_static("LMOVE"),
+        Request::BulkString(source.into()),
+        Request::BulkString(destination.into()),
+        from.to_request(),
+        to.to_request(),
+    ]))
+}
+
+/// BLPOP redis command
+///
+/// Blocking version of `LPOP` command, pops an element from the head
+/// of the first non-empty list. Blocks connection until element is available
+/// or `timeout` (in seconds) expires, zero timeout blocks indefinitely.
+/// Returns key and popped element or `None` on timeout. Blocking commands
+/// could be executed only by `SimpleClient`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+///     let key = gen_random_key();
+///
+///     redis.exec(cmd::RPush(&key, "value")).await?;
+///
+///     let (list, value) = redis.exec(cmd::BLPop([&key], 0.1)).await?.unwrap();
+///     assert_eq!(list, key);
+///     assert_eq!(value, "value");
+///     Ok(())
+/// }
+/// ```
+pub fn BLPop<T>(keys: impl IntoIterator<Item = T>, timeout: f64) -> BPopCommand
+where
+    BulkString: From<T>,
+{
+    BPopCommand::new("BLPOP", keys, timeout)
+}
+
+/// BRPOP redis command
+///
+/// Blocking version of `RPOP` command, pops an element from the tail
+/// of the first non-empty list. See `BLPop` for details.
+pub fn BRPop<T>(keys: impl IntoIterator<Item = T>, timeout: f64) -> BPopCommand
+where
+    BulkString: From<T>,
+{
+    BPopCommand::new("BRPOP", keys, timeout)
+}
+
+pub struct BPopCommand(Vec<Request>);
+
+impl BPopCommand {
+    fn new<T>(cmd: &'static str, keys: impl IntoIterator<Item = T>, timeout: f64) -> Self
+    where
+        BulkString: From<T>,
+    {
+        let mut req = vec![Request::from_static(cmd)];
+        req.extend(keys.into_iter().map(|key| Request::BulkString(key.into())));
+        req.push(timeout.to_request());
+        BPopCommand(req)
+    }
+}
+
+impl Command for BPopCommand {
+    type Output = Option<(Bytes, Bytes)>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
+
+/// BLMOVE redis command
+///
+/// Blocking version of `LMOVE` command, blocks connection until
+/// element is available or `timeout` (in seconds) expires. Zero timeout
+/// blocks indefinitely. Returns `None` on timeout. Blocking commands could
+/// be executed only by `SimpleClient`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, cmd::ListSide, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+///     let src = gen_random_key();
+///     let dst = gen_random_key();
+///
+///     // source list is empty, wait for 0.1 second
+///     let value = redis
+///         .exec(cmd::BLMove(&src, &dst, ListSide::Left, ListSide::Right, 0.1))
+///         .await?;
+///     assert_eq!(value, None);
+///     Ok(())
+/// }
+/// ```
+pub fn BLMove<T, U>(
+    source: T,
+    destination: U,
+    from: ListSide,
+    to: ListSide,
+    timeout: f64,
+) -> utils::BulkOutputCommand
+where
+    BulkString: From<T> + From<U>,
+{
+    utils::BulkOutputCommand(Request::Array(vec![
+        Request::from_static("BLMOVE"),
+        Request::BulkString(source.into()),
+        Request::BulkString(destination.into()),
+        from.to_request(),
+        to.to_request(),
+        timeout.to_request(),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_blocking_requests() {
+        let req = BLPop(["a", "b"], 0.0).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*4\r\n$5\r\nBLPOP\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\n0\r\n"[..]
+        );
+        let req = BRPop(["a"], 2.5).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*3\r\n$5\r\nBRPOP\r\n$1\r\na\r\n$3\r\n2.5\r\n"[..]
+        );
+        let res = BPopCommand::to_output(Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"a")),
+            Response::Bytes(Bytes::from_static(b"value")),
+        ]))
+        .unwrap();
+        assert_eq!(res, Some(("a".into(), "value".into())));
+        assert_eq!(BPopCommand::to_output(Response::Nil).unwrap(), None);
+
+        let req = BLMove("src", "dst", ListSide::Left, ListSide::Right, 1.5).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*6\r\n$6\r\nBLMOVE\r\n$3\r\nsrc\r\n$3\r\ndst\r\n\
+               $4\r\nLEFT\r\n$5\r\nRIGHT\r\n$3\r\n1.5\r\n"[..]
+        );
+        assert!(req.is_blocking());
+
+        let req = BLMPop(0.0, ["a", "b"]).right().count(2).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*8\r\n$6\r\nBLMPOP\r\n$1\r\n0\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n\
+               $5\r\nRIGHT\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n"[..]
+        );
+        assert!(req.is_blocking());
+
+        let req = LMPop(["a"]).to_request();
+        assert!(!req.is_blocking());
+    }
+
+    #[test]
+    fn test_capped() {
+        let max = NonZeroU32::new(2).unwrap();
+        let req = RPush("key", "1")
+            .extend(["2", "3"])
+            .capped(max)
+            .to_request();
+        assert_eq!(
+            req,
+            Request::Array(vec![
+                Request::from_static("EVAL"),
+                Request::from_static(CAPPED_PUSH_SCRIPT),
+                Request::BulkInteger(1),
+                "key".into(),
+                Request::from_static("RPUSH"),
+                Request::BulkInteger(-2),
+                Request::BulkInteger(-1),
+                Request::BulkInteger(2),
+                "1".into(),
+                "2".into(),
+                "3".into(),
+            ])
+        );
+
+        let max = NonZeroU32::new(3).unwrap();
+        match LPush("key", "1").if_exists().capped(max).to_request() {
+            Request::Array(items) => {
+                assert_eq!(items[4], Request::from_static("LPUSHX"));
+                assert_eq!(
+                    &items[5..8],
+                    &[
+                        Request::BulkInteger(0),
+                        Request::BulkInteger(2),
+                        Request::BulkInteger(3)
+                    ]
+                );
+            }
+            req => panic!("Unexpected request: {:?}", req),
+        }
+
+        let max = NonZeroU32::new(u32::MAX).unwrap();
+        match RPush("key", "1").capped(max).to_request() {
+            Request::Array(items) => {
+                assert_eq!(items[5], Request::BulkInteger(-(u32::MAX as i64)));
+            }
+            req => panic!("Unexpected request: {:?}", req),
+        }
+    }
+
+    #[test]
+    fn test_count() {
+        let req = LPop("key").count(3).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*3\r\n$4\r\nLPOP\r\n$3\r\nkey\r\n$1\r\n3\r\n"[..]
+        );
+
+        // counts out of i64 range are not wrapped
+        let req = LPos("key", "a").count(i64::MAX as usize + 1).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*5\r\n$4\r\nLPOS\r\n$3\r\nkey\r\n$1\r\na\r\n$5\r\nCOUNT\r\n\
+               $19\r\n9223372036854775808\r\n"[..]
+        );
+    }
+}
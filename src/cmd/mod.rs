@@ -1,28 +1,61 @@
 //! Redis commands
 #![allow(non_snake_case, clippy::wrong_self_convention)]
 
-use super::codec::{Request, Response};
+use std::convert::TryFrom;
+
+use ntex::util::{ByteString, Bytes};
+
+use super::codec::{BulkString, Request, Response};
 use super::errors::CommandError;
 
 mod auth;
 mod connection;
+#[cfg(feature = "debug-commands")]
+mod debug;
+mod geo;
 mod hashes;
-mod keys;
+pub mod keys;
 mod lists;
 mod pubsub;
+mod raw;
+mod server;
+mod sets;
+mod sorted_sets;
 mod strings;
 mod utils;
 
-pub use self::auth::Auth;
-pub use self::connection::{Ping, Reset, Select};
-pub use self::hashes::{HDel, HGet, HGetAll, HIncrBy, HLen, HSet};
-pub use self::keys::{Del, Exists, Expire, ExpireAt, Keys, Ttl, TtlResult};
-pub use self::lists::{LIndex, LPop, LPush, RPop, RPush};
+pub use self::auth::{Auth, AuthUser};
+pub(crate) use self::connection::Hello;
+pub use self::connection::{
+    ClientGetName, ClientNoEvict, ClientNoTouch, ClientSetName, ClientTracking, Ping, Reset,
+    Select,
+};
+#[cfg(feature = "debug-commands")]
+pub use self::debug::{DebugObject, DebugSleep};
+pub use self::geo::{GeoDist, GeoUnit};
+pub use self::hashes::{
+    HDel, HGet, HGetAll, HGetAllOrdered, HIncrBy, HLen, HRandField, HScan, HSet, HSetMap,
+};
+pub use self::keys::{
+    Del, Exists, Expire, ExpireAt, KeyExists, Keys, Move, ObjectFreq, ObjectIdleTime, PExpireAt,
+    PTtl, PTtlResult, RedisType, Scan, Sort, SortRo, Ttl, TtlResult,
+};
+pub use self::lists::{
+    BLMPop, BLMove, BLPop, BRPop, LIndex, LInsert, LMPop, LMove, LPop, LPos, LPush, LRange,
+    ListSide, RPop, RPush,
+};
 pub use self::pubsub::{
     PSubscribe, PUnSubscribe, Publish, SPublish, SSubscribe, SUnSubscribe, Subscribe,
     SubscribeItem, UnSubscribe,
 };
-pub use self::strings::{Get, IncrBy, Set};
+pub use self::raw::{ArrayRaw, BulkRaw, IntRaw, Raw, StatusRaw};
+pub use self::server::{CommandGetKeys, SwapDb, WaitAof};
+pub use self::sets::{SAdd, SInterCard, SMembers};
+pub use self::sorted_sets::{ZIncrBy, ZMPop, ZScore};
+pub use self::strings::{
+    BitField, BitFieldOverflow, BitFieldType, Get, GetRange, GetSet, GetStr, IncrBy, IncrByFloat,
+    MSetNx, Set, SetOutcome, SetRange,
+};
 
 /// Trait implemented by types that can be used as redis commands
 pub trait Command {
@@ -34,15 +67,122 @@ pub trait Command {
 
     /// Create command response from a redis response
     fn to_output(val: Response) -> Result<Self::Output, CommandError>;
+
+    /// Create command error from a redis error response
+    fn to_error(err: ByteString) -> CommandError {
+        err.into()
+    }
+
+    /// Create untyped command response from a redis response
+    ///
+    /// By default response is returned as is.
+    fn to_output_raw(val: Response) -> Response {
+        val
+    }
+}
+
+/// Member, field or value argument of a command
+///
+/// Strings are sent as is, integers and floats are sent as their decimal
+/// representation. Infinite floats are sent as `inf` and `-inf`, NaN as `nan`.
+pub trait Arg {
+    /// Convert argument to a redis request
+    fn to_request(self) -> Request;
+}
+
+impl<T> Arg for T
+where
+    BulkString: From<T>,
+{
+    fn to_request(self) -> Request {
+        Request::BulkString(self.into())
+    }
+}
+
+macro_rules! int_arg {
+    ($($t:ty),*) => {
+        $(
+            impl Arg for $t {
+                fn to_request(self) -> Request {
+                    Request::BulkInteger(i64::from(self))
+                }
+            }
+        )*
+    };
+}
+
+int_arg!(i8, i16, i32, i64, u8, u16, u32);
+
+macro_rules! wide_int_arg {
+    ($($t:ty),*) => {
+        $(
+            impl Arg for $t {
+                fn to_request(self) -> Request {
+                    // values out of i64 range are rendered by itoa
+                    match i64::try_from(self) {
+                        Ok(val) => Request::BulkInteger(val),
+                        Err(_) => {
+                            let mut buf = itoa::Buffer::new();
+                            let val = Bytes::copy_from_slice(buf.format(self).as_bytes());
+                            Request::BulkString(val.into())
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+wide_int_arg!(isize, u64, usize);
+
+impl Arg for f64 {
+    fn to_request(self) -> Request {
+        if self.is_nan() {
+            Request::from_static("nan")
+        } else if self.is_infinite() {
+            Request::from_static(if self > 0.0 { "inf" } else { "-inf" })
+        } else {
+            Request::BulkString(self.to_string().into())
+        }
+    }
+}
+
+impl Arg for f32 {
+    fn to_request(self) -> Request {
+        f64::from(self).to_request()
+    }
 }
 
 pub mod commands {
     //! Command implementations
     pub use super::auth::AuthCommand;
-    pub use super::hashes::{HDelCommand, HGetAllCommand, HSetCommand};
-    pub use super::keys::{KeysCommand, KeysPatternCommand, TtlCommand};
-    pub use super::lists::LPushCommand;
+    pub use super::connection::{ClientFlagCommand, ClientSetNameCommand, ClientTrackingCommand};
+    #[cfg(feature = "debug-commands")]
+    pub use super::debug::{DebugObjectCommand, DebugSleepCommand};
+    pub use super::geo::GeoDistCommand;
+    pub use super::hashes::{
+        HDelCommand, HGetAllCollectCommand, HGetAllCommand, HGetAllOrderedCommand,
+        HRandFieldCommand, HRandFieldCountCommand, HRandFieldWithValuesCommand, HScanCommand,
+        HScanNoValuesCommand, HSetCommand,
+    };
+    pub use super::keys::{
+        KeysCommand, KeysPatternCommand, ObjectFreqCommand, ObjectIdleTimeCommand, PTtlCommand,
+        ScanCommand, SortCommand, SortRoCommand, SortStoreCommand, TtlCommand,
+    };
+    pub use super::lists::{
+        BPopCommand, LInsertCommand, LMPopCommand, LPosCommand, LPosCountCommand,
+        LPushCappedCommand, LPushCommand, LRangeCommand, PopCommand, PopCountCommand,
+    };
     pub use super::pubsub::{PubSubCommand, SubscribeOutputCommand};
-    pub use super::strings::SetCommand;
-    pub use super::utils::{BulkOutputCommand, IntOutputCommand};
+    pub use super::raw::{ArrayCommand, BulkCommand, IntCommand, RawCommand, StatusCommand};
+    pub use super::server::{CommandGetKeysCommand, SwapDbCommand, WaitAofCommand};
+    pub use super::sets::{
+        SAddCommand, SInterCardCommand, SMembersCollectCommand, SMembersCommand,
+    };
+    pub use super::sorted_sets::ZMPopCommand;
+    pub use super::strings::{BitFieldCommand, GetStrCommand, SetCommand, SetStatusCommand};
+    pub use super::utils::{
+        BoolOutputCommand, BulkOutputCommand, FloatOutputCommand, IntOutputCommand,
+        OptionalFloatOutputCommand,
+    };
 }
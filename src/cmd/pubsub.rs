@@ -16,19 +16,36 @@ const TYPE_PMESSAGE: Bytes = Bytes::from_static(b"pmessage");
 
 pub trait PubSubCommand {}
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SubscribeItem {
-    Subscribed(Bytes),
-    UnSubscribed(Bytes),
+    /// Subscription confirmation with the number of channels client is subscribed to
+    Subscribed { channel: Bytes, count: i64 },
+    /// Unsubscription confirmation with the number of channels client is still subscribed to
+    UnSubscribed { channel: Bytes, count: i64 },
     Message {
         pattern: Option<Bytes>,
         channel: Bytes,
         payload: Bytes,
     },
+    /// Message with integer payload
+    IntMessage {
+        pattern: Option<Bytes>,
+        channel: Bytes,
+        payload: i64,
+    },
 }
 
 struct MessagePayload(Either<Bytes, i64>);
 
+impl MessagePayload {
+    fn count(self) -> Result<i64, CommandError> {
+        self.0.right().ok_or(CommandError::Output(
+            "Subscription count is not integer",
+            Response::Nil,
+        ))
+    }
+}
+
 impl TryFrom<Response> for MessagePayload {
     type Error = (&'static str, Response);
 
@@ -46,7 +63,8 @@ impl TryFrom<Response> for SubscribeItem {
 
     fn try_from(val: Response) -> Result<Self, Self::Error> {
         let (mtype, pattern, channel, payload) = match val {
-            Response::Array(ary) => match ary.len() {
+            // RESP3 connection delivers pubsub messages as push type
+            Response::Array(ary) | Response::Push(ary) => match ary.len() {
                 // subscribe or ssubscribe message
                 3 => {
                     let mut ary_iter = ary.into_iter();
@@ -79,24 +97,30 @@ impl TryFrom<Response> for SubscribeItem {
 
         match &mtype {
             s if s == &TYPE_SUBSCRIBE || s == &TYPE_SSUBSCRIBE || s == &TYPE_PSUBSCRIBE => {
-                Ok(SubscribeItem::Subscribed(channel))
+                Ok(SubscribeItem::Subscribed {
+                    channel,
+                    count: payload.count()?,
+                })
             }
             s if s == &TYPE_UNSUBSCRIBE || s == &TYPE_SUNSUBSCRIBE || s == &TYPE_PUNSUBSCRIBE => {
-                Ok(SubscribeItem::UnSubscribed(channel))
+                Ok(SubscribeItem::UnSubscribed {
+                    channel,
+                    count: payload.count()?,
+                })
             }
             s if s == &TYPE_MESSAGE || s == &TYPE_SMESSAGE || s == &TYPE_PMESSAGE => {
-                if let Some(payload) = payload.0.left() {
-                    Ok(SubscribeItem::Message {
+                Ok(match payload.0 {
+                    Either::Left(payload) => SubscribeItem::Message {
                         pattern,
                         channel,
                         payload,
-                    })
-                } else {
-                    Err(CommandError::Output(
-                        "Subscription message payload is not bytes",
-                        Response::Nil,
-                    ))
-                }
+                    },
+                    Either::Right(payload) => SubscribeItem::IntMessage {
+                        pattern,
+                        channel,
+                        payload,
+                    },
+                })
             }
             _ => Err(CommandError::Output(
                 "Subscription message type unknown",
@@ -159,7 +183,29 @@ where
 }
 
 /// SUBSCRIBE redis command
-pub fn Subscribe<T>(channels: Vec<T>) -> SubscribeOutputCommand
+///
+/// Subscribes the client to the specified channels. Channels could be
+/// provided as any iterator, i.e. `Vec` or array.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+///
+///     // subscribe to single channel
+///     let subscriber = redis.subscribe(cmd::Subscribe(["test"]))?;
+///
+///     let msg = subscriber.recv().await.unwrap()?;
+///     assert_eq!(
+///         msg,
+///         cmd::SubscribeItem::Subscribed { channel: "test".into(), count: 1 }
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn Subscribe<T>(channels: impl IntoIterator<Item = T>) -> SubscribeOutputCommand
 where
     BulkString: From<T>,
 {
@@ -185,7 +231,7 @@ where
 }
 
 /// SSUBSCRIBE redis command
-pub fn SSubscribe<T>(channels: Vec<T>) -> SubscribeOutputCommand
+pub fn SSubscribe<T>(channels: impl IntoIterator<Item = T>) -> SubscribeOutputCommand
 where
     BulkString: From<T>,
 {
@@ -211,7 +257,7 @@ where
 }
 
 /// PSUBSCRIBE redis command
-pub fn PSubscribe<T>(channels: Vec<T>) -> SubscribeOutputCommand
+pub fn PSubscribe<T>(channels: impl IntoIterator<Item = T>) -> SubscribeOutputCommand
 where
     BulkString: From<T>,
 {
@@ -238,3 +284,60 @@ where
 
 impl PubSubCommand for SubscribeOutputCommand {}
 impl PubSubCommand for UnSubscribeOutputCommand {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int_message() {
+        let item = SubscribeItem::try_from(Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"smessage")),
+            Response::Bytes(Bytes::from_static(b"channel")),
+            Response::Integer(10),
+        ]))
+        .unwrap();
+        assert_eq!(
+            item,
+            SubscribeItem::IntMessage {
+                pattern: None,
+                channel: Bytes::from_static(b"channel"),
+                payload: 10
+            }
+        );
+
+        let item = SubscribeItem::try_from(Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"pmessage")),
+            Response::Bytes(Bytes::from_static(b"chan*")),
+            Response::Bytes(Bytes::from_static(b"channel")),
+            Response::Integer(-1),
+        ]))
+        .unwrap();
+        assert_eq!(
+            item,
+            SubscribeItem::IntMessage {
+                pattern: Some(Bytes::from_static(b"chan*")),
+                channel: Bytes::from_static(b"channel"),
+                payload: -1
+            }
+        );
+    }
+
+    #[test]
+    fn test_resp3_push_message() {
+        let item = SubscribeItem::try_from(Response::Push(vec![
+            Response::Bytes(Bytes::from_static(b"message")),
+            Response::Bytes(Bytes::from_static(b"channel")),
+            Response::Bytes(Bytes::from_static(b"payload")),
+        ]))
+        .unwrap();
+        assert_eq!(
+            item,
+            SubscribeItem::Message {
+                pattern: None,
+                channel: Bytes::from_static(b"channel"),
+                payload: Bytes::from_static(b"payload")
+            }
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::{convert::TryFrom, marker::PhantomData};
+
+use ntex::util::{ByteString, Bytes};
+
+use super::{Command, CommandError};
+use crate::codec::{Request, Response};
+
+/// Arbitrary redis command with simple string reply
+///
+/// Returns status reply as is, could be used for commands
+/// that are not supported by the crate.
+///
+/// ```rust
+/// use ntex_redis::{array, cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let status = redis.exec(cmd::StatusRaw(array!["PING"])).await?;
+///     assert_eq!(status, "PONG");
+///
+///     Ok(())
+/// }
+/// ```
+pub fn StatusRaw(req: Request) -> StatusCommand {
+    StatusCommand(req)
+}
+
+pub struct StatusCommand(Request);
+
+impl Command for StatusCommand {
+    type Output = ByteString;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::String(val) => Ok(val),
+            _ => Err(CommandError::Output("Status reply expected", val)),
+        }
+    }
+}
+
+/// Arbitrary redis command with bulk string reply
+///
+/// Returns `None` for nil reply.
+///
+/// ```rust
+/// use ntex_redis::{array, cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let info = redis.exec(cmd::BulkRaw(array!["INFO", "server"])).await?;
+///     assert!(info.is_some());
+///
+///     Ok(())
+/// }
+/// ```
+pub fn BulkRaw(req: Request) -> BulkCommand {
+    BulkCommand(req)
+}
+
+pub struct BulkCommand(Request);
+
+impl Command for BulkCommand {
+    type Output = Option<Bytes>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Bytes(val) => Ok(Some(val)),
+            Response::Nil => Ok(None),
+            _ => Err(CommandError::Output("Bulk string reply expected", val)),
+        }
+    }
+}
+
+/// Arbitrary redis command with integer reply
+///
+/// ```rust
+/// use ntex_redis::{array, cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let size = redis.exec(cmd::IntRaw(array!["DBSIZE"])).await?;
+///     assert!(size >= 0);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn IntRaw(req: Request) -> IntCommand {
+    IntCommand(req)
+}
+
+pub struct IntCommand(Request);
+
+impl Command for IntCommand {
+    type Output = i64;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Integer(val) => Ok(val),
+            _ => Err(CommandError::Output("Integer reply expected", val)),
+        }
+    }
+}
+
+/// Arbitrary redis command with array reply
+///
+/// Returns array items as is, nil reply is converted to empty vector.
+///
+/// ```rust
+/// use ntex_redis::{array, cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let items = redis.exec(cmd::ArrayRaw(array!["CONFIG", "GET", "maxmemory"])).await?;
+///     assert_eq!(items.len(), 2);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn ArrayRaw(req: Request) -> ArrayCommand {
+    ArrayCommand(req)
+}
+
+pub struct ArrayCommand(Request);
+
+impl Command for ArrayCommand {
+    type Output = Vec<Response>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Array(val) => Ok(val),
+            Response::Nil => Ok(Vec::new()),
+            _ => Err(CommandError::Output("Array reply expected", val)),
+        }
+    }
+}
+
+/// Arbitrary redis command with typed reply
+///
+/// Reply is converted to `T` with `TryFrom<Response>` implementation,
+/// custom output types could implement `TryFrom<Response>` with
+/// `CommandError` as error type. Conversion is defined by command type,
+/// so it cannot be set per value with a closure.
+///
+/// ```rust
+/// use ntex_redis::{array, cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let ttl = redis.exec(cmd::Raw::<i64>(array!["TTL", "key"])).await?;
+///     assert!(ttl >= -2);
+///
+///     let value = redis.exec(cmd::Raw::<Option<String>>(array!["GET", "key"])).await?;
+///     println!("value: {:?}", value);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn Raw<T>(req: Request) -> RawCommand<T> {
+    RawCommand(req, PhantomData)
+}
+
+pub struct RawCommand<T>(Request, PhantomData<T>);
+
+impl<T> Command for RawCommand<T>
+where
+    T: TryFrom<Response>,
+    CommandError: From<T::Error>,
+{
+    type Output = T;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(T::try_from(val)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_raw() {
+        let res = StatusCommand::to_output(Response::String("QUEUED".into())).unwrap();
+        assert_eq!(res, "QUEUED");
+
+        let res = StatusCommand::to_output(Response::Bytes("QUEUED".into()));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+
+    #[test]
+    fn test_bulk_raw() {
+        let res = BulkCommand::to_output(Response::Bytes("value".into())).unwrap();
+        assert_eq!(res, Some(Bytes::from_static(b"value")));
+        assert_eq!(BulkCommand::to_output(Response::Nil).unwrap(), None);
+
+        let res = BulkCommand::to_output(Response::Integer(1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+
+    #[test]
+    fn test_int_raw() {
+        let req = IntRaw(crate::array!["DBSIZE"]).to_request();
+        assert_eq!(req, crate::array!["DBSIZE"]);
+        assert_eq!(IntCommand::to_output(Response::Integer(10)).unwrap(), 10);
+
+        let res = IntCommand::to_output(Response::String("OK".into()));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+
+    #[test]
+    fn test_array_raw() {
+        let items = vec![Response::Bytes("a".into()), Response::Integer(1)];
+        let res = ArrayCommand::to_output(Response::Array(items.clone())).unwrap();
+        assert_eq!(res, items);
+        assert!(ArrayCommand::to_output(Response::Nil).unwrap().is_empty());
+
+        let res = ArrayCommand::to_output(Response::Integer(1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct DbSize(u64);
+
+    impl TryFrom<Response> for DbSize {
+        type Error = CommandError;
+
+        fn try_from(val: Response) -> Result<Self, Self::Error> {
+            match val {
+                Response::Integer(size) if size >= 0 => Ok(DbSize(size as u64)),
+                _ => Err(CommandError::Output("Database size expected", val)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_raw() {
+        let req = Raw::<DbSize>(crate::array!["DBSIZE"]).to_request();
+        assert_eq!(req, crate::array!["DBSIZE"]);
+        let res = RawCommand::<DbSize>::to_output(Response::Integer(10)).unwrap();
+        assert_eq!(res, DbSize(10));
+        let res = RawCommand::<DbSize>::to_output(Response::Integer(-1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+
+        let res = RawCommand::<Option<String>>::to_output(Response::Nil).unwrap();
+        assert_eq!(res, None);
+        let res = RawCommand::<Vec<i64>>::to_output(Response::Integer(1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+}
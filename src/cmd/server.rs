@@ -0,0 +1,160 @@
+use std::convert::TryFrom;
+
+use ntex::util::Bytes;
+
+use super::{Arg, Command, CommandError};
+use crate::codec::{Request, Response};
+
+/// COMMAND GETKEYS redis command
+///
+/// Returns the keys from a full redis command.
+///
+/// ```rust
+/// use ntex_redis::{array, cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     // extract keys from a command
+///     let keys = redis.exec(
+///         cmd::CommandGetKeys(array!["MSET", "a", "1", "b", "2"])
+///     ).await?;
+///
+///     assert_eq!(keys, vec!["a", "b"]);
+///     Ok(())
+/// }
+/// ```
+pub fn CommandGetKeys(request: Request) -> CommandGetKeysCommand {
+    let mut req = vec![
+        Request::from_static("COMMAND"),
+        Request::from_static("GETKEYS"),
+    ];
+    flatten(request, &mut req);
+    CommandGetKeysCommand(Request::Array(req))
+}
+
+fn flatten(request: Request, req: &mut Vec<Request>) {
+    match request {
+        Request::Array(items) => {
+            for item in items {
+                flatten(item, req);
+            }
+        }
+        item => req.push(item),
+    }
+}
+
+pub struct CommandGetKeysCommand(Request);
+
+impl Command for CommandGetKeysCommand {
+    type Output = Vec<Bytes>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+/// SWAPDB redis command
+///
+/// Swaps two redis databases, so that clients connected to a given
+/// database will immediately see the data of the other database.
+pub fn SwapDb(index1: u32, index2: u32) -> SwapDbCommand {
+    SwapDbCommand(Request::Array(vec![
+        Request::from_static("SWAPDB"),
+        Request::BulkInteger(i64::from(index1)),
+        Request::BulkInteger(i64::from(index2)),
+    ]))
+}
+
+pub struct SwapDbCommand(Request);
+
+impl Command for SwapDbCommand {
+    type Output = ();
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::String(ref s) if s == "OK" => Ok(()),
+            _ => Err(CommandError::Output("Unexpected value", val)),
+        }
+    }
+}
+
+/// WAITAOF redis command
+///
+/// Blocks until all previous write commands are fsynced to the AOF of
+/// the local redis and/or at least the specified number of replicas.
+/// Returns number of local redis instances (0 or 1) and number of replicas
+/// that acknowledged the fsync. Timeout is in milliseconds, zero value
+/// blocks forever. Command is available since redis 7.2.
+///
+/// Command blocks connection, so it could be executed only with `SimpleClient`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+///
+///     // wait for replicas acknowledgement, 100 millis at most
+///     let (local, replicas) = redis.exec(cmd::WaitAof(0, 1, 100)).await?;
+///
+///     assert_eq!(local, 0);
+///     Ok(())
+/// }
+/// ```
+pub fn WaitAof(numlocal: u32, numreplicas: u32, timeout: u64) -> WaitAofCommand {
+    WaitAofCommand(Request::Array(vec![
+        Request::from_static("WAITAOF"),
+        Request::BulkInteger(i64::from(numlocal)),
+        Request::BulkInteger(i64::from(numreplicas)),
+        timeout.to_request(),
+    ]))
+}
+
+pub struct WaitAofCommand(Request);
+
+impl Command for WaitAofCommand {
+    type Output = (i64, i64);
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(<(i64, i64)>::try_from(val)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_wait_aof() {
+        let req = WaitAof(1, 2, 100).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*4\r\n$7\r\nWAITAOF\r\n$1\r\n1\r\n$1\r\n2\r\n$3\r\n100\r\n"[..]
+        );
+        assert_eq!(
+            WaitAofCommand::to_output(Response::Array(vec![
+                Response::Integer(1),
+                Response::Integer(0)
+            ]))
+            .unwrap(),
+            (1, 0)
+        );
+        assert!(WaitAofCommand::to_output(Response::Integer(1)).is_err());
+    }
+}
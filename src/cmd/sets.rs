@@ -0,0 +1,314 @@
+use std::{convert::TryFrom, iter::FromIterator, marker::PhantomData};
+
+use ntex::util::Bytes;
+
+use super::{Arg, Command, CommandError};
+use crate::codec::{BulkString, Request, Response};
+
+/// SADD redis command
+///
+/// Add the specified members to the set stored at `key`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     // create set with two members
+///     let added = redis.exec(cmd::SAdd(&key, "a").member("b")).await?;
+///
+///     assert_eq!(added, 2);
+///     Ok(())
+/// }
+/// ```
+pub fn SAdd<T, V>(key: T, member: V) -> SAddCommand
+where
+    BulkString: From<T>,
+    V: Arg,
+{
+    SAddCommand(vec![
+        Request::from_static("SADD"),
+        Request::BulkString(key.into()),
+        member.to_request(),
+    ])
+}
+
+pub struct SAddCommand(Vec<Request>);
+
+impl SAddCommand {
+    /// Add a member to this command.
+    pub fn member<T>(mut self, other: T) -> Self
+    where
+        T: Arg,
+    {
+        self.0.push(other.to_request());
+        self
+    }
+
+    /// Add more members to this command.
+    pub fn members<T>(mut self, other: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Arg,
+    {
+        self.0.extend(other.into_iter().map(|t| t.to_request()));
+        self
+    }
+}
+
+impl Command for SAddCommand {
+    type Output = usize;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Integer(val) => Ok(val as usize),
+            _ => Err(CommandError::Output("Cannot parse response", val)),
+        }
+    }
+}
+
+/// SINTERCARD redis command
+///
+/// Returns the cardinality of the set which would result from the
+/// intersection of all the given sets.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key1 = gen_random_key();
+///     let key2 = gen_random_key();
+///
+///     redis.exec(cmd::SAdd(&key1, "a").members(vec!["b", "c"])).await?;
+///     redis.exec(cmd::SAdd(&key2, "b").members(vec!["c", "d"])).await?;
+///
+///     // get intersection cardinality
+///     let count = redis.exec(cmd::SInterCard(vec![&key1, &key2])).await?;
+///
+///     assert_eq!(count, 2);
+///     Ok(())
+/// }
+/// ```
+pub fn SInterCard<T>(keys: impl IntoIterator<Item = T>) -> SInterCardCommand
+where
+    BulkString: From<T>,
+{
+    SInterCardCommand {
+        keys: keys.into_iter().map(|k| k.into()).collect(),
+        limit: None,
+    }
+}
+
+pub struct SInterCardCommand {
+    keys: Vec<Request>,
+    limit: Option<usize>,
+}
+
+impl SInterCardCommand {
+    /// Stop computation once cardinality reaches `limit`.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl Command for SInterCardCommand {
+    type Output = i64;
+
+    fn to_request(self) -> Request {
+        let mut req = Vec::with_capacity(self.keys.len() + 4);
+        req.push(Request::from_static("SINTERCARD"));
+        req.push(Request::BulkInteger(self.keys.len() as i64));
+        req.extend(self.keys);
+
+        // LIMIT
+        if let Some(limit) = self.limit {
+            req.push(Request::from_static("LIMIT"));
+            req.push(Request::BulkInteger(limit as i64));
+        }
+
+        Request::Array(req)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Integer(val) => Ok(val),
+            _ => Err(CommandError::Output("Cannot parse response", val)),
+        }
+    }
+}
+
+/// SMEMBERS redis command
+///
+/// Returns all the members of the set stored at `key`.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     redis.exec(cmd::SAdd(&key, "a").member("b")).await?;
+///
+///     let members = redis.exec(cmd::SMembers(&key)).await?;
+///     assert_eq!(members.len(), 2);
+///     Ok(())
+/// }
+/// ```
+pub fn SMembers<T>(key: T) -> SMembersCommand
+where
+    BulkString: From<T>,
+{
+    SMembersCommand(vec![
+        Request::from_static("SMEMBERS"),
+        Request::BulkString(key.into()),
+    ])
+}
+
+pub struct SMembersCommand(Vec<Request>);
+
+impl SMembersCommand {
+    /// Collect members into any collection, i.e. `HashSet`.
+    ///
+    /// ```rust
+    /// use std::collections::HashSet;
+    /// use ntex::util::ByteString;
+    /// use ntex_redis::{cmd, RedisConnector};
+    /// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+    /// # fn gen_random_key() -> String {
+    /// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+    /// # }
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     let key = gen_random_key();
+    ///
+    ///     redis.exec(cmd::SAdd(&key, "a").member("b")).await?;
+    ///
+    ///     let members = redis
+    ///         .exec(cmd::SMembers(&key).collect::<HashSet<ByteString>>())
+    ///         .await?;
+    ///     assert!(members.contains("a"));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn collect<M>(self) -> SMembersCollectCommand<M> {
+        SMembersCollectCommand(self.0, PhantomData)
+    }
+}
+
+impl Command for SMembersCommand {
+    type Output = Vec<Bytes>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+pub struct SMembersCollectCommand<M>(Vec<Request>, PhantomData<M>);
+
+impl<M, T> Command for SMembersCollectCommand<M>
+where
+    M: FromIterator<T> + IntoIterator<Item = T>,
+    T: TryFrom<Response, Error = (&'static str, Response)>,
+{
+    type Output = M;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Array(items) => Ok(items
+                .into_iter()
+                .map(T::try_from)
+                .collect::<Result<M, _>>()?),
+            _ => Err(CommandError::Output("Array response expected", val)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use ntex::util::ByteString;
+
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_numeric_members() {
+        let req = SAdd("key", 1i64)
+            .member(-20i64)
+            .members([3u32, 4])
+            .member(2.5)
+            .to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*7\r\n$4\r\nSADD\r\n$3\r\nkey\r\n$1\r\n1\r\n$3\r\n-20\r\n\
+               $1\r\n3\r\n$1\r\n4\r\n$3\r\n2.5\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_non_finite_members() {
+        let req = SAdd("key", f64::NAN)
+            .member(f64::INFINITY)
+            .member(f32::NEG_INFINITY)
+            .member(u64::MAX)
+            .to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*6\r\n$4\r\nSADD\r\n$3\r\nkey\r\n$3\r\nnan\r\n$3\r\ninf\r\n\
+               $4\r\n-inf\r\n$20\r\n18446744073709551615\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_smembers_collect() {
+        let response = Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"b")),
+            Response::Bytes(Bytes::from_static(b"a")),
+        ]);
+
+        let set =
+            SMembersCollectCommand::<HashSet<ByteString>>::to_output(response.clone()).unwrap();
+        assert!(set.contains("a"));
+        assert!(!set.contains("c"));
+
+        let set = SMembersCollectCommand::<BTreeSet<String>>::to_output(response).unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec!["a", "b"]);
+
+        let res = SMembersCollectCommand::<HashSet<Bytes>>::to_output(Response::Integer(1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+}
@@ -0,0 +1,187 @@
+use std::convert::TryFrom;
+
+use ntex::util::Bytes;
+
+use super::{utils, Arg, Command, CommandError};
+use crate::codec::{BulkString, Request, Response};
+
+/// ZMPOP redis command
+///
+/// Pops members from the first non-empty sorted set among provided `keys`.
+/// Command returns name of the sorted set and popped members with their scores,
+/// or `None` if all sorted sets are empty. Members with the lowest scores
+/// are popped by default.
+pub fn ZMPop<T>(keys: impl IntoIterator<Item = T>) -> ZMPopCommand
+where
+    BulkString: From<T>,
+{
+    ZMPopCommand {
+        keys: keys
+            .into_iter()
+            .map(|k| Request::BulkString(k.into()))
+            .collect(),
+        min: true,
+        count: None,
+    }
+}
+
+pub struct ZMPopCommand {
+    keys: Vec<Request>,
+    min: bool,
+    count: Option<Request>,
+}
+
+impl ZMPopCommand {
+    /// Pop members with the lowest scores.
+    pub fn min(mut self) -> Self {
+        self.min = true;
+        self
+    }
+
+    /// Pop members with the highest scores.
+    pub fn max(mut self) -> Self {
+        self.min = false;
+        self
+    }
+
+    /// Pop up to `count` members, by default one member is popped.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count.to_request());
+        self
+    }
+}
+
+impl Command for ZMPopCommand {
+    type Output = Option<(Bytes, Vec<(Bytes, f64)>)>;
+
+    fn to_request(self) -> Request {
+        let mut req = Vec::with_capacity(self.keys.len() + 5);
+        req.push(Request::from_static("ZMPOP"));
+        req.push(Request::BulkInteger(self.keys.len() as i64));
+        req.extend(self.keys);
+        req.push(Request::from_static(if self.min { "MIN" } else { "MAX" }));
+        if let Some(count) = self.count {
+            req.push(Request::from_static("COUNT"));
+            req.push(count);
+        }
+        Request::Array(req)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
+
+/// ZSCORE redis command
+///
+/// Returns the score of `member` in the sorted set at `key`, or `None`
+/// if member or key does not exist.
+pub fn ZScore<T, V>(key: T, member: V) -> utils::OptionalFloatOutputCommand
+where
+    BulkString: From<T>,
+    V: Arg,
+{
+    utils::OptionalFloatOutputCommand(Request::Array(vec![
+        Request::from_static("ZSCORE"),
+        Request::BulkString(key.into()),
+        member.to_request(),
+    ]))
+}
+
+/// ZINCRBY redis command
+///
+/// Increments the score of `member` in the sorted set at `key` by `increment`.
+/// Command returns the new score of member.
+pub fn ZIncrBy<T, V>(key: T, increment: f64, member: V) -> utils::FloatOutputCommand
+where
+    BulkString: From<T>,
+    V: Arg,
+{
+    utils::FloatOutputCommand(Request::Array(vec![
+        Request::from_static("ZINCRBY"),
+        Request::BulkString(key.into()),
+        Request::BulkString(increment.to_string().into()),
+        member.to_request(),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::{codec::Decoder, util::BytesMut};
+
+    use super::*;
+    use crate::codec::Codec;
+
+    fn decode(codec: Codec, data: &[u8]) -> Response {
+        codec
+            .decode(&mut BytesMut::copy_from_slice(data))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_float_output() {
+        for (resp2, resp3) in [
+            (&b"$4\r\n3.25\r\n"[..], &b",3.25\r\n"[..]),
+            (&b"$4\r\n-inf\r\n"[..], &b",-inf\r\n"[..]),
+        ] {
+            let expected = if resp2.ends_with(b"inf\r\n") {
+                f64::NEG_INFINITY
+            } else {
+                3.25
+            };
+            let res2 = decode(Codec::new(), resp2);
+            let res3 = decode(Codec::resp3(), resp3);
+
+            let score2 = utils::OptionalFloatOutputCommand::to_output(res2.clone()).unwrap();
+            let score3 = utils::OptionalFloatOutputCommand::to_output(res3.clone()).unwrap();
+            assert_eq!(score2, Some(expected));
+            assert_eq!(score2, score3);
+
+            let score2 = utils::FloatOutputCommand::to_output(res2).unwrap();
+            let score3 = utils::FloatOutputCommand::to_output(res3).unwrap();
+            assert_eq!(score2, expected);
+            assert_eq!(score2, score3);
+        }
+
+        let nil2 = decode(Codec::new(), b"$-1\r\n");
+        let nil3 = decode(Codec::resp3(), b"_\r\n");
+        assert_eq!(
+            utils::OptionalFloatOutputCommand::to_output(nil2).unwrap(),
+            None
+        );
+        assert_eq!(
+            utils::OptionalFloatOutputCommand::to_output(nil3).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_zmpop_output() {
+        let res = Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"key")),
+            Response::Array(vec![
+                Response::Array(vec![
+                    Response::Bytes(Bytes::from_static(b"a")),
+                    Response::Bytes(Bytes::from_static(b"1.5")),
+                ]),
+                Response::Array(vec![
+                    Response::Bytes(Bytes::from_static(b"b")),
+                    Response::Bytes(Bytes::from_static(b"2")),
+                ]),
+            ]),
+        ]);
+        assert_eq!(
+            ZMPopCommand::to_output(res).unwrap(),
+            Some((
+                Bytes::from_static(b"key"),
+                vec![
+                    (Bytes::from_static(b"a"), 1.5),
+                    (Bytes::from_static(b"b"), 2.0)
+                ]
+            ))
+        );
+        assert_eq!(ZMPopCommand::to_output(Response::Nil).unwrap(), None);
+        assert!(ZMPopCommand::to_output(Response::Integer(1)).is_err());
+    }
+}
@@ -1,4 +1,8 @@
-use super::{utils, Command, CommandError};
+use std::{convert::TryFrom, time::Duration};
+
+use ntex::util::ByteString;
+
+use super::{utils, Arg, Command, CommandError};
 use crate::codec::{BulkString, Request, Response};
 
 /// GET redis command
@@ -12,6 +16,63 @@ where
     ]))
 }
 
+/// GET redis command with string output
+///
+/// Same as `Get` but value is returned as utf-8 string. Command fails
+/// with `CommandError::Output` error if value is not a valid utf-8 string.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///     redis.exec(cmd::Set(&key, "value")).await?;
+///
+///     let value = redis.exec(cmd::GetStr(&key)).await?;
+///     assert_eq!(value.unwrap(), "value");
+///     Ok(())
+/// }
+/// ```
+pub fn GetStr<T>(key: T) -> GetStrCommand
+where
+    BulkString: From<T>,
+{
+    GetStrCommand(Request::Array(vec![
+        Request::from_static("GET"),
+        Request::BulkString(key.into()),
+    ]))
+}
+
+pub struct GetStrCommand(Request);
+
+impl Command for GetStrCommand {
+    type Output = Option<ByteString>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        match val {
+            Response::Bytes(val) => match ByteString::try_from(val.clone()) {
+                Ok(val) => Ok(Some(val)),
+                Err(_) => Err(CommandError::Output(
+                    "Cannot convert into a string",
+                    Response::Bytes(val),
+                )),
+            },
+            Response::Nil => Ok(None),
+            _ => Err(CommandError::Output("Cannot parse response", val)),
+        }
+    }
+}
+
 /// SET redis command
 ///
 /// Set key to hold the string value. Command returns true if value is set
@@ -58,6 +119,32 @@ impl SetCommand {
         self
     }
 
+    /// Set the specified expire time with milliseconds precision.
+    ///
+    /// Sub-millisecond part of `ttl` is truncated. Returns
+    /// `CommandError::InvalidArgument` if `ttl` in milliseconds
+    /// does not fit into `i64`.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///
+    ///     let set = cmd::Set("key", "value").expire(Duration::from_millis(1500))?;
+    ///     redis.exec(set).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn expire(self, ttl: Duration) -> Result<Self, CommandError> {
+        match i64::try_from(ttl.as_millis()) {
+            Ok(millis) => Ok(self.expire_millis(millis)),
+            Err(_) => Err(CommandError::InvalidArgument("Expire time is out of range")),
+        }
+    }
+
     /// Only set the key if it already exist.
     pub fn if_exists(mut self) -> Self {
         self.exists = Some(true);
@@ -75,6 +162,33 @@ impl SetCommand {
         self.keepttl = true;
         self
     }
+
+    /// Return `SetOutcome` instead of `bool`.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    /// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+    /// # fn gen_random_key() -> String {
+    /// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+    /// # }
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+    ///     let key = gen_random_key();
+    ///
+    ///     // set value only if key does not exist
+    ///     let result = redis.exec(
+    ///         cmd::Set(&key, "value").if_not_exists().status()
+    ///     ).await?;
+    ///
+    ///     assert_eq!(result, cmd::SetOutcome::Set);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn status(self) -> SetStatusCommand {
+        SetStatusCommand(self)
+    }
 }
 
 impl Command for SetCommand {
@@ -126,6 +240,123 @@ impl Command for SetCommand {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Outcome of SET command
+pub enum SetOutcome {
+    /// Value is set
+    Set,
+    /// Value is not set because NX or XX condition is not met
+    NotPerformed,
+}
+
+pub struct SetStatusCommand(SetCommand);
+
+impl Command for SetStatusCommand {
+    type Output = SetOutcome;
+
+    fn to_request(self) -> Request {
+        self.0.to_request()
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        if SetCommand::to_output(val)? {
+            Ok(SetOutcome::Set)
+        } else {
+            Ok(SetOutcome::NotPerformed)
+        }
+    }
+}
+
+/// GETSET redis command
+///
+/// Atomically sets `key` to `value` and returns the old value stored at `key`.
+/// Command is deprecated since redis 6.2 in favor of `SET` with `GET` option,
+/// but it is still available for compatibility with older servers.
+pub fn GetSet<T, V>(key: T, value: V) -> utils::BulkOutputCommand
+where
+    BulkString: From<T> + From<V>,
+{
+    utils::BulkOutputCommand(Request::Array(vec![
+        Request::from_static("GETSET"),
+        Request::BulkString(key.into()),
+        Request::BulkString(value.into()),
+    ]))
+}
+
+/// SETRANGE redis command
+///
+/// Overwrites part of the string stored at `key`, starting at `offset`.
+/// If string is shorter than `offset` (or key does not exist), it is padded
+/// with zero bytes to make `offset` fit. Command returns the length
+/// of the string after modification.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     let len = redis.exec(cmd::SetRange(&key, 2, "value")).await?;
+///     assert_eq!(len, 7);
+///
+///     let value = redis.exec(cmd::Get(&key)).await?;
+///     assert_eq!(value.unwrap(), &b"\0\0value"[..]);
+///     Ok(())
+/// }
+/// ```
+pub fn SetRange<T, V>(key: T, offset: usize, value: V) -> utils::IntOutputCommand
+where
+    BulkString: From<T> + From<V>,
+{
+    utils::IntOutputCommand(Request::Array(vec![
+        Request::from_static("SETRANGE"),
+        Request::BulkString(key.into()),
+        offset.to_request(),
+        Request::BulkString(value.into()),
+    ]))
+}
+
+/// GETRANGE redis command
+///
+/// Returns the substring of the string stored at `key` between `start`
+/// and `end` offsets (both inclusive). Negative offsets are counted from
+/// the end of the string. Empty string is returned for missing key.
+pub fn GetRange<T>(key: T, start: i64, end: i64) -> utils::BulkOutputCommand
+where
+    BulkString: From<T>,
+{
+    utils::BulkOutputCommand(Request::Array(vec![
+        Request::from_static("GETRANGE"),
+        Request::BulkString(key.into()),
+        Request::BulkInteger(start),
+        Request::BulkInteger(end),
+    ]))
+}
+
+/// MSETNX redis command
+///
+/// Sets the given keys to their respective values only if none of
+/// the keys exist. Command returns true if all keys are set, otherwise
+/// no operation is performed and command returns false.
+pub fn MSetNx<T, K, V>(pairs: T) -> utils::BoolOutputCommand
+where
+    T: IntoIterator<Item = (K, V)>,
+    BulkString: From<K> + From<V>,
+{
+    let mut req = vec![Request::from_static("MSETNX")];
+    for (key, value) in pairs {
+        req.push(Request::BulkString(key.into()));
+        req.push(Request::BulkString(value.into()));
+    }
+    utils::BoolOutputCommand(Request::Array(req))
+}
+
 /// INCRBY redis command
 ///
 /// Increments the number stored at `key` by `increment`.
@@ -137,6 +368,254 @@ where
     utils::IntOutputCommand(Request::Array(vec![
         Request::from_static("INCRBY"),
         Request::BulkString(key.into()),
-        Request::BulkString(i64::from(increment).to_string().into()),
+        Request::BulkInteger(i64::from(increment)),
     ]))
 }
+
+/// INCRBYFLOAT redis command
+///
+/// Increments the floating point number stored at `key` by `increment`.
+/// Command returns the value of key after the increment.
+pub fn IncrByFloat<T>(key: T, increment: f64) -> utils::FloatOutputCommand
+where
+    BulkString: From<T>,
+{
+    utils::FloatOutputCommand(Request::Array(vec![
+        Request::from_static("INCRBYFLOAT"),
+        Request::BulkString(key.into()),
+        Request::BulkString(increment.to_string().into()),
+    ]))
+}
+
+/// BITFIELD redis command
+///
+/// Performs multiple bit field operations on the string stored at `key`.
+/// Command returns one result per `GET`, `SET` and `INCRBY` operation,
+/// result is `None` if operation is not performed because of `FAIL`
+/// overflow behavior.
+///
+/// ```rust
+/// use ntex_redis::{cmd, cmd::BitFieldType, cmd::BitFieldOverflow, RedisConnector};
+/// # use rand::{thread_rng, Rng, distributions::Alphanumeric};
+/// # fn gen_random_key() -> String {
+/// #    thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect::<String>()
+/// # }
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///     let key = gen_random_key();
+///
+///     let result = redis
+///         .exec(
+///             cmd::BitField(&key)
+///                 .set(BitFieldType::U8, 0, 200)
+///                 .overflow(BitFieldOverflow::Sat)
+///                 .incrby(BitFieldType::U8, 0, 100),
+///         )
+///         .await?;
+///     assert_eq!(result, vec![Some(0), Some(255)]);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn BitField<T>(key: T) -> BitFieldCommand
+where
+    BulkString: From<T>,
+{
+    BitFieldCommand(vec![
+        Request::from_static("BITFIELD"),
+        Request::BulkString(key.into()),
+    ])
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Integer type of bit field
+///
+/// Signed integers up to 64 bits and unsigned integers
+/// up to 63 bits are supported.
+pub struct BitFieldType {
+    signed: bool,
+    bits: u8,
+}
+
+impl BitFieldType {
+    pub const I8: BitFieldType = BitFieldType::signed(8);
+    pub const I16: BitFieldType = BitFieldType::signed(16);
+    pub const I32: BitFieldType = BitFieldType::signed(32);
+    pub const I64: BitFieldType = BitFieldType::signed(64);
+    pub const U8: BitFieldType = BitFieldType::unsigned(8);
+    pub const U16: BitFieldType = BitFieldType::unsigned(16);
+    pub const U32: BitFieldType = BitFieldType::unsigned(32);
+    pub const U63: BitFieldType = BitFieldType::unsigned(63);
+
+    /// Signed integer with specified number of bits
+    pub const fn signed(bits: u8) -> Self {
+        BitFieldType { signed: true, bits }
+    }
+
+    /// Unsigned integer with specified number of bits
+    pub const fn unsigned(bits: u8) -> Self {
+        BitFieldType {
+            signed: false,
+            bits,
+        }
+    }
+
+    fn to_request(self) -> Request {
+        let sign = if self.signed { "i" } else { "u" };
+        Request::BulkString(format!("{}{}", sign, self.bits).into())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Overflow behavior of BITFIELD `SET` and `INCRBY` operations
+pub enum BitFieldOverflow {
+    /// Wrap around, default behavior
+    Wrap,
+    /// Saturate to min or max value
+    Sat,
+    /// Do not perform operation
+    Fail,
+}
+
+pub struct BitFieldCommand(Vec<Request>);
+
+impl BitFieldCommand {
+    /// Get bit field value
+    pub fn get(mut self, tp: BitFieldType, offset: u64) -> Self {
+        self.0.push(Request::from_static("GET"));
+        self.0.push(tp.to_request());
+        self.0.push(Request::BulkInteger(offset as i64));
+        self
+    }
+
+    /// Set bit field value, operation returns old value
+    pub fn set(mut self, tp: BitFieldType, offset: u64, value: i64) -> Self {
+        self.0.push(Request::from_static("SET"));
+        self.0.push(tp.to_request());
+        self.0.push(Request::BulkInteger(offset as i64));
+        self.0.push(Request::BulkInteger(value));
+        self
+    }
+
+    /// Increment bit field value, operation returns new value
+    pub fn incrby(mut self, tp: BitFieldType, offset: u64, increment: i64) -> Self {
+        self.0.push(Request::from_static("INCRBY"));
+        self.0.push(tp.to_request());
+        self.0.push(Request::BulkInteger(offset as i64));
+        self.0.push(Request::BulkInteger(increment));
+        self
+    }
+
+    /// Set overflow behavior for subsequent `SET` and `INCRBY` operations
+    pub fn overflow(mut self, overflow: BitFieldOverflow) -> Self {
+        self.0.push(Request::from_static("OVERFLOW"));
+        self.0.push(Request::from_static(match overflow {
+            BitFieldOverflow::Wrap => "WRAP",
+            BitFieldOverflow::Sat => "SAT",
+            BitFieldOverflow::Fail => "FAIL",
+        }));
+        self
+    }
+}
+
+impl Command for BitFieldCommand {
+    type Output = Vec<Option<i64>>;
+
+    fn to_request(self) -> Request {
+        Request::Array(self.0)
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Vec::try_from(val)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode_request;
+
+    #[test]
+    fn test_get_str() {
+        use ntex::util::Bytes;
+
+        let res = GetStrCommand::to_output(Response::Bytes(Bytes::from_static(b"value")));
+        assert_eq!(res.unwrap(), Some(ByteString::from_static("value")));
+        assert_eq!(GetStrCommand::to_output(Response::Nil).unwrap(), None);
+
+        let data = Bytes::from_static(b"\xff\xfe");
+        match GetStrCommand::to_output(Response::Bytes(data.clone())) {
+            Err(CommandError::Output(_, Response::Bytes(val))) => assert_eq!(val, data),
+            res => panic!("unexpected result: {:?}", res),
+        }
+        let res = GetStrCommand::to_output(Response::Integer(1));
+        assert!(matches!(res, Err(CommandError::Output(_, _))));
+    }
+
+    #[test]
+    fn test_set_expire_duration() {
+        let req = Set("key", "value")
+            .expire(Duration::from_micros(1_500_999))
+            .unwrap()
+            .to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*5\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nPX\r\n$4\r\n1500\r\n"[..]
+        );
+
+        let ttl = Duration::from_millis(i64::MAX as u64);
+        assert!(Set("key", "value").expire(ttl).is_ok());
+
+        for ttl in [
+            Duration::from_millis(i64::MAX as u64 + 1),
+            Duration::from_secs(u64::MAX / 1000),
+            Duration::MAX,
+        ] {
+            let res = Set("key", "value").expire(ttl);
+            assert!(matches!(res, Err(CommandError::InvalidArgument(_))));
+        }
+    }
+
+    #[test]
+    fn test_range_request() {
+        let req = SetRange("key", 5, "\0v").to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*4\r\n$8\r\nSETRANGE\r\n$3\r\nkey\r\n$1\r\n5\r\n$2\r\n\0v\r\n"[..]
+        );
+        let req = GetRange("key", 0, -1).to_request();
+        assert_eq!(
+            &encode_request(&req)[..],
+            &b"*4\r\n$8\r\nGETRANGE\r\n$3\r\nkey\r\n$1\r\n0\r\n$2\r\n-1\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_bitfield_request() {
+        let req = BitField("key")
+            .get(BitFieldType::I16, 8)
+            .overflow(BitFieldOverflow::Fail)
+            .set(BitFieldType::U63, 0, 1)
+            .incrby(BitFieldType::unsigned(4), 4, -1)
+            .to_request();
+        assert_eq!(
+            encode_request(&req),
+            encode_request(&crate::array![
+                "BITFIELD", "key", "GET", "i16", "8", "OVERFLOW", "FAIL", "SET", "u63", "0", "1",
+                "INCRBY", "u4", "4", "-1"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_bitfield_output() {
+        let result = BitFieldCommand::to_output(Response::Array(vec![
+            Response::Integer(255),
+            Response::Nil,
+        ]))
+        .unwrap();
+        assert_eq!(result, vec![Some(255), None]);
+    }
+}
@@ -14,11 +14,7 @@ impl Command for BulkOutputCommand {
     }
 
     fn to_output(val: Response) -> Result<Self::Output, CommandError> {
-        match val {
-            Response::Nil => Ok(None),
-            Response::Bytes(val) => Ok(Some(val)),
-            _ => Err(CommandError::Output("Cannot parse response", val)),
-        }
+        Ok(Option::try_from(val)?)
     }
 }
 
@@ -52,3 +48,31 @@ impl Command for BoolOutputCommand {
         Ok(bool::try_from(val)?)
     }
 }
+
+pub struct FloatOutputCommand(pub(crate) Request);
+
+impl Command for FloatOutputCommand {
+    type Output = f64;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(f64::try_from(val)?)
+    }
+}
+
+pub struct OptionalFloatOutputCommand(pub(crate) Request);
+
+impl Command for OptionalFloatOutputCommand {
+    type Output = Option<f64>;
+
+    fn to_request(self) -> Request {
+        self.0
+    }
+
+    fn to_output(val: Response) -> Result<Self::Output, CommandError> {
+        Ok(Option::try_from(val)?)
+    }
+}
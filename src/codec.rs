@@ -1,53 +1,127 @@
 //! Redis protocol codec
-use std::{cmp, collections::HashMap, convert::TryFrom, hash::BuildHasher, hash::Hash, str};
+use std::{borrow::Cow, cmp, collections::HashMap, convert::TryFrom, str, sync::Arc};
+use std::{hash::BuildHasher, hash::Hash};
 
 use ntex::codec::{Decoder, Encoder};
 use ntex::util::{Buf, BufMut, ByteString, Bytes, BytesMut};
 
 use super::errors::Error;
 
+/// Default max size of bulk string, 512Mb
+const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+/// Default max number of array elements
+const MAX_ARRAY_LEN: usize = 1 << 31;
+/// Default max depth of nested arrays
+const MAX_DEPTH: usize = 512;
+
+/// Commands that block connection until data is available or timeout expires
+///
+/// Shared client rejects such commands, because all pending commands
+/// of the connection would wait for blocked one. Blocking commands
+/// could be executed with `SimpleClient`.
+pub const BLOCKING_COMMANDS: &[&str] = &[
+    "BLPOP",
+    "BRPOP",
+    "BRPOPLPUSH",
+    "BLMOVE",
+    "BLMPOP",
+    "BZPOPMIN",
+    "BZPOPMAX",
+    "BZMPOP",
+    "WAIT",
+    "WAITAOF",
+];
+
+/// Commands that block connection if `BLOCK` option is specified
+pub const BLOCK_OPTION_COMMANDS: &[&str] = &["XREAD", "XREADGROUP"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Redis protocol version
+pub enum Protocol {
+    /// RESP2 protocol
+    Resp2,
+    /// RESP3 protocol
+    Resp3,
+}
+
+#[derive(Debug, Copy, Clone)]
 /// Codec to read/write redis values
-pub struct Codec;
+pub struct Codec {
+    protocol: Protocol,
+    max_bulk_len: usize,
+    max_array_len: usize,
+    max_depth: usize,
+}
+
+impl Codec {
+    /// Create RESP2 codec with default limits
+    pub const fn new() -> Self {
+        Codec {
+            protocol: Protocol::Resp2,
+            max_bulk_len: MAX_BULK_LEN,
+            max_array_len: MAX_ARRAY_LEN,
+            max_depth: MAX_DEPTH,
+        }
+    }
+
+    /// Create RESP3 codec with default limits
+    ///
+    /// RESP3 attributes are decoded and discarded.
+    pub const fn resp3() -> Self {
+        Codec {
+            protocol: Protocol::Resp3,
+            max_bulk_len: MAX_BULK_LEN,
+            max_array_len: MAX_ARRAY_LEN,
+            max_depth: MAX_DEPTH,
+        }
+    }
+
+    /// Get protocol version
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub(crate) fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Set max size of bulk string response.
+    ///
+    /// By default max size is 512Mb.
+    pub fn max_bulk_len(mut self, size: usize) -> Self {
+        self.max_bulk_len = size;
+        self
+    }
+
+    /// Set max number of elements in array response.
+    ///
+    /// By default max number is 2^31.
+    pub fn max_array_len(mut self, size: usize) -> Self {
+        self.max_array_len = size;
+        self
+    }
+
+    /// Set max depth of nested arrays.
+    ///
+    /// By default max depth is 512.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Codec::new()
+    }
+}
 
 impl Encoder for Codec {
     type Item = Request;
     type Error = Error;
 
     fn encode(&self, msg: Request, buf: &mut BytesMut) -> Result<(), Self::Error> {
-        match msg {
-            Request::Array(ary) => {
-                write_header(b'*', ary.len() as i64, buf, 0);
-                for v in ary {
-                    self.encode(v, buf)?;
-                }
-            }
-            Request::BulkString(bstr) => {
-                let len = bstr.0.len();
-                write_header(b'$', len as i64, buf, len + 2);
-                buf.extend_from_slice(&bstr.0[..]);
-                write_rn(buf);
-            }
-            Request::BulkStatic(bstr) => {
-                let len = bstr.len();
-                write_header(b'$', len as i64, buf, len + 2);
-                buf.extend_from_slice(bstr);
-                write_rn(buf);
-            }
-            Request::BulkInteger(i) => {
-                let mut buffer = itoa::Buffer::new();
-                let rendered = buffer.format(i);
-                write_header(b'$', rendered.len() as i64, buf, rendered.len() + 2);
-                buf.extend_from_slice(rendered.as_bytes());
-                write_rn(buf);
-            }
-            Request::String(ref string) => {
-                write_string(b'+', string, buf);
-            }
-            Request::Integer(val) => {
-                // Simple integer are just the header
-                write_header(b':', val, buf, 0);
-            }
-        }
+        write_request(&msg, buf);
         Ok(())
     }
 }
@@ -57,7 +131,7 @@ impl Decoder for Codec {
     type Error = Error;
 
     fn decode(&self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match decode(buf, 0)? {
+        match decode(buf, 0, self, 0)? {
             Some((pos, item)) => {
                 buf.advance(pos);
                 Ok(Some(item))
@@ -67,6 +141,85 @@ impl Decoder for Codec {
     }
 }
 
+#[derive(Copy, Clone)]
+/// Decoder that splits top level array response into elements
+/// and top level bulk string into chunks
+pub(crate) struct StreamDecoder {
+    pub(crate) codec: Codec,
+    /// Decode array header instead of whole array
+    pub(crate) header: bool,
+    /// Decode bulk string header instead of whole bulk string
+    pub(crate) bulk_header: bool,
+    /// Number of bulk string bytes left for chunked decoding
+    pub(crate) bulk: Option<usize>,
+}
+
+pub(crate) enum StreamItem {
+    /// Array header with number of elements
+    Header(usize),
+    /// Bulk string header with string size
+    BulkHeader(usize),
+    /// Part of bulk string
+    Chunk(Bytes),
+    /// Bulk string terminator
+    BulkEnd,
+    Response(Response),
+}
+
+impl Decoder for StreamDecoder {
+    type Item = StreamItem;
+    type Error = Error;
+
+    fn decode(&self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.bulk {
+            Some(0) if buf.len() < 2 => return Ok(None),
+            Some(0) if &buf[..2] == b"\r\n" => {
+                buf.advance(2);
+                return Ok(Some(StreamItem::BulkEnd));
+            }
+            Some(0) => return Err(Error::Parse("Expected CRLF".to_string())),
+            Some(_) if buf.is_empty() => return Ok(None),
+            Some(size) => {
+                let size = cmp::min(size, buf.len());
+                return Ok(Some(StreamItem::Chunk(buf.split_to(size).freeze())));
+            }
+            None => (),
+        }
+
+        if self.bulk_header && buf.first() == Some(&b'$') {
+            match decode_length(buf, 1)? {
+                Some((pos, len)) if len >= 0 => {
+                    buf.advance(pos);
+                    return Ok(Some(StreamItem::BulkHeader(len as usize)));
+                }
+                // nil string is decoded as regular response
+                Some(_) => (),
+                None => return Ok(None),
+            }
+        }
+        let resp3 = self.codec.protocol == Protocol::Resp3;
+        let header = match buf.first() {
+            Some(b'*') => Some(1),
+            Some(b'~') if resp3 => Some(1),
+            // map entries are streamed as flat key, value sequence
+            Some(b'%') if resp3 => Some(2),
+            _ => None,
+        };
+        if let (true, Some(size)) = (self.header, header) {
+            match decode_length(buf, 1)? {
+                Some((pos, len)) if len >= 0 => {
+                    buf.advance(pos);
+                    return Ok(Some(StreamItem::Header(len as usize * size)));
+                }
+                // nil array is decoded as regular response
+                Some(_) => (),
+                None => return Ok(None),
+            }
+        }
+        Ok(self.codec.decode(buf)?.map(StreamItem::Response))
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 /// A bulk string.
 ///
@@ -152,6 +305,42 @@ impl From<Vec<u8>> for BulkString {
     }
 }
 
+impl<'a> From<Cow<'a, str>> for BulkString {
+    fn from(val: Cow<'a, str>) -> BulkString {
+        match val {
+            Cow::Borrowed(val) => BulkString(Bytes::copy_from_slice(val.as_bytes())),
+            Cow::Owned(val) => BulkString(Bytes::from(val)),
+        }
+    }
+}
+
+impl<'a> From<Cow<'a, [u8]>> for BulkString {
+    fn from(val: Cow<'a, [u8]>) -> BulkString {
+        match val {
+            Cow::Borrowed(val) => BulkString(Bytes::copy_from_slice(val)),
+            Cow::Owned(val) => BulkString(Bytes::from(val)),
+        }
+    }
+}
+
+impl From<Arc<str>> for BulkString {
+    fn from(val: Arc<str>) -> BulkString {
+        BulkString(Bytes::copy_from_slice(val.as_bytes()))
+    }
+}
+
+impl<'a> From<&'a Arc<str>> for BulkString {
+    fn from(val: &'a Arc<str>) -> BulkString {
+        BulkString(Bytes::copy_from_slice(val.as_bytes()))
+    }
+}
+
+impl From<Arc<[u8]>> for BulkString {
+    fn from(val: Arc<[u8]>) -> BulkString {
+        BulkString(Bytes::copy_from_slice(&val))
+    }
+}
+
 /// A single RESP value, this owns the data that is to-be written to Redis.
 ///
 /// It is cloneable to allow multiple copies to be delivered in certain circumstances, e.g. multiple
@@ -191,6 +380,75 @@ impl Request {
         Request::BulkStatic(data)
     }
 
+    /// Command name of the request
+    pub(crate) fn command_name(&self) -> Option<&[u8]> {
+        match self {
+            Request::Array(vals) => vals.first().and_then(|val| val.as_bytes()),
+            val => val.as_bytes(),
+        }
+    }
+
+    /// Content of string request
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Request::BulkString(BulkString(val)) => Some(&val[..]),
+            Request::BulkStatic(val) => Some(val),
+            Request::String(val) => Some(val.as_bytes()),
+            _ => None,
+        }
+    }
+
+    /// Check if request is a subscribe or unsubscribe command
+    pub(crate) fn is_pubsub(&self) -> bool {
+        let name = if let Some(name) = self.command_name() {
+            name
+        } else {
+            return false;
+        };
+        [
+            &b"SUBSCRIBE"[..],
+            b"SSUBSCRIBE",
+            b"PSUBSCRIBE",
+            b"UNSUBSCRIBE",
+            b"SUNSUBSCRIBE",
+            b"PUNSUBSCRIBE",
+        ]
+        .iter()
+        .any(|cmd| cmd.eq_ignore_ascii_case(name))
+    }
+
+    /// Check if request is a blocking command
+    ///
+    /// See `BLOCKING_COMMANDS` and `BLOCK_OPTION_COMMANDS`.
+    pub(crate) fn is_blocking(&self) -> bool {
+        let name = if let Some(name) = self.command_name() {
+            name
+        } else {
+            return false;
+        };
+        let is_name = |cmd: &&str| cmd.as_bytes().eq_ignore_ascii_case(name);
+
+        if BLOCKING_COMMANDS.iter().any(is_name) {
+            true
+        } else if BLOCK_OPTION_COMMANDS.iter().any(is_name) {
+            // options precede STREAMS keyword, keys could be named "BLOCK"
+            let is_arg = |val: &Request, arg: &[u8]| {
+                val.as_bytes()
+                    .map(|val| val.eq_ignore_ascii_case(arg))
+                    .unwrap_or(false)
+            };
+            match self {
+                Request::Array(vals) => vals[1..]
+                    .iter()
+                    .take_while(|val| !is_arg(val, b"STREAMS"))
+                    .any(|val| is_arg(val, b"BLOCK")),
+                _ => false,
+            }
+        } else {
+            false
+        }
+    }
+
     #[allow(clippy::should_implement_trait)]
     /// Convenience function for building dynamic Redis commands with variable numbers of
     /// arguments, e.g. RPUSH
@@ -296,6 +554,12 @@ pub enum Response {
     /// Zero, one or more other `Response`s.
     Array(Vec<Response>),
 
+    /// Map of key-value pairs, RESP3 only
+    Map(Vec<(Response, Response)>),
+
+    /// Out-of-band push message, RESP3 only
+    Push(Vec<Response>),
+
     /// A bulk string. In Redis terminology a string is a byte-array, so this is stored as a
     /// vector of `u8`s to allow clients to interpret the bytes as appropriate.
     Bytes(Bytes),
@@ -306,11 +570,37 @@ pub enum Response {
     /// An error from the Redis server
     Error(ByteString),
 
+    /// RESP3 double as received from the server, could be converted
+    /// with `f64::try_from()`
+    Double(ByteString),
+
+    /// RESP3 big number, decimal digits with optional minus sign
+    BigNumber(ByteString),
+
     /// Redis documentation defines an integer as being a signed 64-bit integer:
     /// https://redis.io/topics/protocol#resp-integers
     Integer(i64),
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+/// Type of redis response
+pub enum ResponseKind {
+    Nil,
+    Array,
+    Map,
+    Push,
+    /// Bulk string, `$` prefixed
+    BulkString,
+    /// Simple string, `+` prefixed
+    SimpleString,
+    Error,
+    Integer,
+    /// RESP3 double, `,` prefixed
+    Double,
+    /// RESP3 big number, `(` prefixed
+    BigNumber,
+}
+
 impl Response {
     /// Extract redis server error to Result
     pub fn into_result(self) -> Result<Response, ByteString> {
@@ -319,6 +609,112 @@ impl Response {
             val => Ok(val),
         }
     }
+
+    /// Get response type
+    pub fn kind(&self) -> ResponseKind {
+        match self {
+            Response::Nil => ResponseKind::Nil,
+            Response::Array(_) => ResponseKind::Array,
+            Response::Map(_) => ResponseKind::Map,
+            Response::Push(_) => ResponseKind::Push,
+            Response::Bytes(_) => ResponseKind::BulkString,
+            Response::String(_) => ResponseKind::SimpleString,
+            Response::Error(_) => ResponseKind::Error,
+            Response::Integer(_) => ResponseKind::Integer,
+            Response::Double(_) => ResponseKind::Double,
+            Response::BigNumber(_) => ResponseKind::BigNumber,
+        }
+    }
+
+    /// Check if server sent simple string (`+OK`) rather than bulk string
+    pub fn is_simple_string(&self) -> bool {
+        matches!(self, Response::String(_))
+    }
+
+    /// Convert response to a string, invalid UTF-8 sequences are replaced
+    /// with `U+FFFD REPLACEMENT CHARACTER`
+    ///
+    /// Arrays, maps and other non-string responses are formatted with `Debug`.
+    pub fn into_string_lossy(self) -> String {
+        match self {
+            Response::Bytes(val) => String::from_utf8_lossy(&val).into_owned(),
+            Response::String(val)
+            | Response::Error(val)
+            | Response::Double(val)
+            | Response::BigNumber(val) => val.to_string(),
+            Response::Integer(val) => val.to_string(),
+            val => format!("{:?}", val),
+        }
+    }
+
+    /// Convert flat array or map response to a list of key-value pairs
+    ///
+    /// Unlike conversion to a `HashMap`, pairs order is preserved.
+    pub fn into_pairs<K, V>(self) -> Result<Vec<(K, V)>, (&'static str, Response)>
+    where
+        K: TryFrom<Response, Error = (&'static str, Response)>,
+        V: TryFrom<Response, Error = (&'static str, Response)>,
+    {
+        match self {
+            Response::Array(ary) => {
+                if ary.len() % 2 != 0 {
+                    return Err((
+                        "Cannot convert an odd number of elements into pairs",
+                        Response::Array(ary),
+                    ));
+                }
+                let mut pairs = Vec::with_capacity(ary.len() / 2);
+                let mut items = ary.into_iter();
+                while let (Some(k), Some(v)) = (items.next(), items.next()) {
+                    pairs.push((K::try_from(k)?, V::try_from(v)?));
+                }
+                Ok(pairs)
+            }
+            Response::Map(items) => {
+                let mut pairs = Vec::with_capacity(items.len());
+                for (k, v) in items {
+                    pairs.push((K::try_from(k)?, V::try_from(v)?));
+                }
+                Ok(pairs)
+            }
+            _ => Err(("Cannot convert into pairs", self)),
+        }
+    }
+}
+
+/// Convert flat array or map response to an ordered list of field-value pairs
+///
+/// Could be used for mapping hash responses to a struct.
+///
+/// ```rust
+/// use ntex::util::Bytes;
+/// use ntex_redis::codec::{response_pairs, Response};
+///
+/// #[derive(Default)]
+/// struct User {
+///     name: Bytes,
+///     email: Bytes,
+/// }
+///
+/// let response = Response::Array(vec![
+///     Response::Bytes(Bytes::from_static(b"name")),
+///     Response::Bytes(Bytes::from_static(b"Jack")),
+///     Response::Bytes(Bytes::from_static(b"email")),
+///     Response::Bytes(Bytes::from_static(b"jack@example.com")),
+/// ]);
+///
+/// let mut user = User::default();
+/// for (field, value) in response_pairs(response).unwrap() {
+///     match &field[..] {
+///         b"name" => user.name = value,
+///         b"email" => user.email = value,
+///         _ => (),
+///     }
+/// }
+/// assert_eq!(user.name, "Jack");
+/// ```
+pub fn response_pairs(val: Response) -> Result<Vec<(Bytes, Bytes)>, (&'static str, Response)> {
+    val.into_pairs()
 }
 
 impl TryFrom<Response> for Bytes {
@@ -338,7 +734,7 @@ impl TryFrom<Response> for ByteString {
 
     fn try_from(val: Response) -> Result<Self, Self::Error> {
         match val {
-            Response::String(val) => Ok(val),
+            Response::String(val) | Response::Double(val) | Response::BigNumber(val) => Ok(val),
             Response::Bytes(val) => {
                 if let Ok(val) = ByteString::try_from(val) {
                     Ok(val)
@@ -351,6 +747,14 @@ impl TryFrom<Response> for ByteString {
     }
 }
 
+impl TryFrom<Response> for String {
+    type Error = (&'static str, Response);
+
+    fn try_from(val: Response) -> Result<Self, Self::Error> {
+        ByteString::try_from(val).map(|val| val.to_string())
+    }
+}
+
 impl TryFrom<Response> for i64 {
     type Error = (&'static str, Response);
 
@@ -363,6 +767,20 @@ impl TryFrom<Response> for i64 {
     }
 }
 
+impl TryFrom<Response> for f64 {
+    type Error = (&'static str, Response);
+
+    fn try_from(val: Response) -> Result<Self, Self::Error> {
+        let parsed = match val {
+            Response::Bytes(ref bytes) => str::from_utf8(bytes).ok().and_then(|s| s.parse().ok()),
+            Response::String(ref string) | Response::Double(ref string) => string.parse().ok(),
+            Response::Integer(i) => Some(i as f64),
+            _ => None,
+        };
+        parsed.ok_or(("Cannot be converted into a f64", val))
+    }
+}
+
 impl TryFrom<Response> for bool {
     type Error = (&'static str, Response);
 
@@ -378,6 +796,20 @@ impl TryFrom<Response> for bool {
     }
 }
 
+impl<T> TryFrom<Response> for Option<T>
+where
+    T: TryFrom<Response, Error = (&'static str, Response)>,
+{
+    type Error = (&'static str, Response);
+
+    fn try_from(val: Response) -> Result<Option<T>, Self::Error> {
+        match val {
+            Response::Nil => Ok(None),
+            val => T::try_from(val).map(Some),
+        }
+    }
+}
+
 impl<T> TryFrom<Response> for Vec<T>
 where
     T: TryFrom<Response, Error = (&'static str, Response)>,
@@ -464,6 +896,66 @@ where
     }
 }
 
+impl<A, B, C, D> TryFrom<Response> for (A, B, C, D)
+where
+    A: TryFrom<Response, Error = (&'static str, Response)>,
+    B: TryFrom<Response, Error = (&'static str, Response)>,
+    C: TryFrom<Response, Error = (&'static str, Response)>,
+    D: TryFrom<Response, Error = (&'static str, Response)>,
+{
+    type Error = (&'static str, Response);
+
+    fn try_from(val: Response) -> Result<(A, B, C, D), Self::Error> {
+        match val {
+            Response::Array(ary) => {
+                if ary.len() == 4 {
+                    let mut ary_iter = ary.into_iter();
+                    Ok((
+                        A::try_from(ary_iter.next().expect("No value"))?,
+                        B::try_from(ary_iter.next().expect("No value"))?,
+                        C::try_from(ary_iter.next().expect("No value"))?,
+                        D::try_from(ary_iter.next().expect("No value"))?,
+                    ))
+                } else {
+                    Err(("Array needs to be 4 elements", Response::Array(ary)))
+                }
+            }
+            _ => Err(("Unexpected value", val)),
+        }
+    }
+}
+
+impl<A, B, C, D, E> TryFrom<Response> for (A, B, C, D, E)
+where
+    A: TryFrom<Response, Error = (&'static str, Response)>,
+    B: TryFrom<Response, Error = (&'static str, Response)>,
+    C: TryFrom<Response, Error = (&'static str, Response)>,
+    D: TryFrom<Response, Error = (&'static str, Response)>,
+    E: TryFrom<Response, Error = (&'static str, Response)>,
+{
+    type Error = (&'static str, Response);
+
+    fn try_from(val: Response) -> Result<(A, B, C, D, E), Self::Error> {
+        match val {
+            Response::Array(ary) => {
+                if ary.len() == 5 {
+                    let mut ary_iter = ary.into_iter();
+                    Ok((
+                        A::try_from(ary_iter.next().expect("No value"))?,
+                        B::try_from(ary_iter.next().expect("No value"))?,
+                        C::try_from(ary_iter.next().expect("No value"))?,
+                        D::try_from(ary_iter.next().expect("No value"))?,
+                        E::try_from(ary_iter.next().expect("No value"))?,
+                    ))
+                } else {
+                    Err(("Array needs to be 5 elements", Response::Array(ary)))
+                }
+            }
+            _ => Err(("Unexpected value", val)),
+        }
+    }
+}
+
 impl<K, T, S> TryFrom<Response> for HashMap<K, T, S>
 where
     K: TryFrom<Response, Error = (&'static str, Response)> + Hash + Eq,
@@ -489,6 +981,13 @@ where
 
                 Ok(map)
             }
+            Response::Map(items) => {
+                let mut map = HashMap::with_capacity_and_hasher(items.len(), S::default());
+                for (k, v) in items {
+                    map.insert(K::try_from(k)?, T::try_from(v)?);
+                }
+                Ok(map)
+            }
             _ => Err(("Cannot be converted into a hashmap", val)),
         }
     }
@@ -503,11 +1002,11 @@ macro_rules! impl_tryfrom_integers {
 
                 fn try_from(val: Response) -> Result<Self, Self::Error> {
                     i64::try_from(val).and_then(|x| {
-                        // $int_ty::max_value() as i64 > 0 should be optimized out. It tests if
+                        // $int_ty::MAX as i64 > 0 should be optimized out. It tests if
                         // the target integer type needs an "upper bounds" check
-                        if x < ($int_ty::min_value() as i64)
-                            || ($int_ty::max_value() as i64 > 0
-                                && x > ($int_ty::max_value() as i64))
+                        if x < ($int_ty::MIN as i64)
+                            || ($int_ty::MAX as i64 > 0
+                                && x > ($int_ty::MAX as i64))
                         {
                             Err((
                                 concat!(
@@ -528,6 +1027,82 @@ macro_rules! impl_tryfrom_integers {
 
 impl_tryfrom_integers!(isize, usize, i32, u32, u64);
 
+/// Encode redis request to its wire representation
+///
+/// ```rust
+/// use ntex_redis::{array, codec::encode_request};
+///
+/// let bytes = encode_request(&array!["PING"]);
+/// assert_eq!(&bytes[..], b"*1\r\n$4\r\nPING\r\n");
+/// ```
+pub fn encode_request(req: &Request) -> Bytes {
+    let mut buf = BytesMut::new();
+    write_request(req, &mut buf);
+    buf.freeze()
+}
+
+/// Decode single redis response from buffer
+///
+/// Returns `None` if buffer does not contain complete response, decoded
+/// bytes are removed from the buffer. Default codec settings are used,
+/// use `Codec` directly for custom limits or RESP3 protocol.
+///
+/// ```rust
+/// use ntex::util::{Bytes, BytesMut};
+/// use ntex_redis::codec::{decode_response, Response};
+///
+/// let mut buf = BytesMut::from(&b"*2\r\n$3\r\nfoo\r\n:1\r\n"[..]);
+/// let response = decode_response(&mut buf).unwrap();
+/// assert_eq!(
+///     response,
+///     Some(Response::Array(vec![
+///         Response::Bytes(Bytes::from_static(b"foo")),
+///         Response::Integer(1),
+///     ]))
+/// );
+/// assert!(buf.is_empty());
+/// ```
+pub fn decode_response(buf: &mut BytesMut) -> Result<Option<Response>, Error> {
+    Codec::new().decode(buf)
+}
+
+fn write_request(msg: &Request, buf: &mut BytesMut) {
+    match msg {
+        Request::Array(ary) => {
+            write_header(b'*', ary.len() as i64, buf, 0);
+            for v in ary {
+                write_request(v, buf);
+            }
+        }
+        Request::BulkString(bstr) => {
+            let len = bstr.0.len();
+            write_header(b'$', len as i64, buf, len + 2);
+            buf.extend_from_slice(&bstr.0[..]);
+            write_rn(buf);
+        }
+        Request::BulkStatic(bstr) => {
+            let len = bstr.len();
+            write_header(b'$', len as i64, buf, len + 2);
+            buf.extend_from_slice(bstr);
+            write_rn(buf);
+        }
+        Request::BulkInteger(i) => {
+            let mut buffer = itoa::Buffer::new();
+            let rendered = buffer.format(*i);
+            write_header(b'$', rendered.len() as i64, buf, rendered.len() + 2);
+            buf.extend_from_slice(rendered.as_bytes());
+            write_rn(buf);
+        }
+        Request::String(ref string) => {
+            write_string(b'+', string, buf);
+        }
+        Request::Integer(val) => {
+            // Simple integer are just the header
+            write_header(b':', *val, buf, 0);
+        }
+    }
+}
+
 fn write_rn(buf: &mut BytesMut) {
     buf.extend_from_slice(b"\r\n");
 }
@@ -551,14 +1126,27 @@ fn write_string(symb: u8, string: &str, buf: &mut BytesMut) {
 
 type DecodeResult = Result<Option<(usize, Response)>, Error>;
 
-fn decode(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+fn decode(buf: &mut BytesMut, idx: usize, codec: &Codec, depth: usize) -> DecodeResult {
     if buf.len() > idx {
         match buf[idx] {
-            b'$' => decode_bytes(buf, idx + 1),
-            b'*' => decode_array(buf, idx + 1),
+            b'$' => decode_bytes(buf, idx + 1, codec),
+            b'*' => decode_array(buf, idx + 1, codec, depth),
             b':' => decode_integer(buf, idx + 1),
             b'+' => decode_string(buf, idx + 1),
             b'-' => decode_error(buf, idx + 1),
+            // RESP3 types
+            b'~' if codec.protocol == Protocol::Resp3 => decode_array(buf, idx + 1, codec, depth),
+            b'%' if codec.protocol == Protocol::Resp3 => decode_map(buf, idx + 1, codec, depth),
+            b'_' if codec.protocol == Protocol::Resp3 => decode_null(buf, idx + 1),
+            b'#' if codec.protocol == Protocol::Resp3 => decode_boolean(buf, idx + 1),
+            b',' if codec.protocol == Protocol::Resp3 => decode_double(buf, idx + 1),
+            b'(' if codec.protocol == Protocol::Resp3 => decode_big_number(buf, idx + 1),
+            b'=' if codec.protocol == Protocol::Resp3 => decode_verbatim(buf, idx + 1, codec),
+            b'!' if codec.protocol == Protocol::Resp3 => decode_blob_error(buf, idx + 1, codec),
+            b'>' if codec.protocol == Protocol::Resp3 => decode_push(buf, idx + 1, codec, depth),
+            b'|' if codec.protocol == Protocol::Resp3 => {
+                decode_attribute(buf, idx + 1, codec, depth)
+            }
             _ => Err(Error::Parse(format!("Unexpected byte: {}", buf[idx]))),
         }
     } else {
@@ -584,13 +1172,16 @@ fn decode_length(buf: &mut BytesMut, idx: usize) -> Result<Option<(usize, i64)>,
     }
 }
 
-fn decode_bytes(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+fn decode_bytes(buf: &mut BytesMut, idx: usize, codec: &Codec) -> DecodeResult {
     match decode_length(buf, idx)? {
         Some((pos, -1)) => Ok(Some((pos, Response::Nil))),
+        Some((_, size)) if size > 0 && size as u64 > codec.max_bulk_len as u64 => {
+            Err(Error::Parse(format!("String size exceeds limit: {}", size)))
+        }
         Some((pos, size)) if size >= 0 => {
             let size = size as usize;
             let remaining = buf.len() - pos;
-            let required_bytes = size + 2;
+            let required_bytes = size.saturating_add(2);
 
             if remaining < required_bytes {
                 return Ok(None);
@@ -603,37 +1194,83 @@ fn decode_bytes(buf: &mut BytesMut, idx: usize) -> DecodeResult {
     }
 }
 
+/// Verbatim string is decoded as a bulk string without `xxx:` format prefix
+fn decode_verbatim(buf: &mut BytesMut, idx: usize, codec: &Codec) -> DecodeResult {
+    match decode_bytes(buf, idx, codec)? {
+        Some((pos, Response::Bytes(mut data))) if data.len() >= 4 && data[3] == b':' => {
+            data.advance(4);
+            Ok(Some((pos, Response::Bytes(data))))
+        }
+        Some(_) => Err(Error::Parse("Invalid verbatim string".to_string())),
+        None => Ok(None),
+    }
+}
+
+fn decode_blob_error(buf: &mut BytesMut, idx: usize, codec: &Codec) -> DecodeResult {
+    match decode_bytes(buf, idx, codec)? {
+        Some((pos, Response::Bytes(data))) => match ByteString::try_from(data) {
+            Ok(s) => Ok(Some((pos, Response::Error(s)))),
+            Err(_) => Err(Error::Parse("Not a valid error string".to_string())),
+        },
+        Some(_) => Err(Error::Parse("Invalid blob error".to_string())),
+        None => Ok(None),
+    }
+}
+
+fn check_array_size(size: i64, codec: &Codec, depth: usize) -> Result<(), Error> {
+    if size > 0 && size as u64 > codec.max_array_len as u64 {
+        Err(Error::Parse(format!("Array size exceeds limit: {}", size)))
+    } else if depth >= codec.max_depth {
+        Err(Error::Parse(format!(
+            "Array nesting exceeds limit: {}",
+            depth
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 fn is_array_ready_to_decode(
     buf: &mut BytesMut,
     idx: usize,
     array_size: usize,
+    codec: &Codec,
+    depth: usize,
 ) -> Result<(bool, usize), Error> {
     let mut items: usize = 0;
     let mut pos = idx;
 
     // counting beginning of array items in buffer by `\r\n<type>`
-    loop {
-        let Some(new_pos) = buf[pos..].windows(2).position(|w| w.starts_with(b"\r\n")) else {
-            break;
-        };
-
+    while let Some(new_pos) = buf[pos..].windows(2).position(|w| w.starts_with(b"\r\n")) {
         if pos + new_pos + 2 >= buf.len() {
             break;
         }
         pos += new_pos + 2;
 
-        items += match &buf[pos] {
-            // check nested array and calc it as item
-            b'*' => match decode_length(buf, pos) {
-                Ok(Some((_, -1))) => 1,
-                Ok(Some((p, size))) if size >= 0 => {
-                    let (ready, end_of_scan) = is_array_ready_to_decode(buf, p, size as usize)?;
+        items += match buf[pos] {
+            // check nested array or map and calc it as item
+            b @ (b'*' | b'~' | b'%' | b'>' | b'|') => match decode_length(buf, pos + 1) {
+                // empty array does not contain items to scan, next item
+                // starts after array header
+                Ok(Some((_, -1 | 0))) => usize::from(b != b'|'),
+                Ok(Some((p, size))) if size > 0 => {
+                    // map and attribute contain key and value for each entry
+                    let size = if b == b'%' || b == b'|' {
+                        size.saturating_mul(2)
+                    } else {
+                        size
+                    };
+                    check_array_size(size, codec, depth + 1)?;
+                    // scan starts from the end of nested array header
+                    let (ready, end_of_scan) =
+                        is_array_ready_to_decode(buf, p - 2, size as usize, codec, depth + 1)?;
                     // nested array isn't ready
                     if !ready {
                         return Ok((false, end_of_scan));
                     }
                     pos = end_of_scan;
-                    1
+                    // attribute is attached to the following item
+                    usize::from(b != b'|')
                 }
                 Ok(Some((_, size))) => {
                     return Err(Error::Parse(format!("Invalid array size: {}", size)))
@@ -641,33 +1278,52 @@ fn is_array_ready_to_decode(
                 _ => 0,
             },
             // array item found
-            b'$' | b':' | b'+' | b'-' => 1,
+            b'$' | b':' | b'+' | b'-' | b'_' | b'#' | b',' | b'(' | b'=' | b'!' => 1,
             _ => 0,
         };
 
         if array_size <= items {
-            return Ok((true, pos));
+            // nested arrays are checked by scan, last scalar item
+            // must be complete too
+            return Ok((is_scalar_complete(buf, pos)?, pos));
         }
     }
 
     Ok((array_size <= items, pos))
 }
 
-fn decode_array(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+/// Check if scalar item at `pos` is completely received
+fn is_scalar_complete(buf: &mut BytesMut, pos: usize) -> Result<bool, Error> {
+    match buf[pos] {
+        b'$' | b'=' | b'!' => match decode_length(buf, pos + 1)? {
+            Some((p, size)) if size >= 0 => {
+                Ok(buf.len() >= p.saturating_add(size as usize).saturating_add(2))
+            }
+            Some(_) => Ok(true),
+            None => Ok(false),
+        },
+        b'*' | b'~' | b'%' | b'>' | b'|' => Ok(true),
+        _ => Ok(buf[pos..].windows(2).any(|w| w == b"\r\n")),
+    }
+}
+
+fn decode_array(buf: &mut BytesMut, idx: usize, codec: &Codec, depth: usize) -> DecodeResult {
     match decode_length(buf, idx)? {
         Some((pos, -1)) => Ok(Some((pos, Response::Nil))),
         Some((pos, size)) if size >= 0 => {
+            check_array_size(size, codec, depth)?;
             let size = size as usize;
 
-            let (is_ready, _) = is_array_ready_to_decode(buf, idx, size)?;
+            let (is_ready, _) = is_array_ready_to_decode(buf, idx, size, codec, depth)?;
             if !is_ready {
                 return Ok(None);
             }
 
             let mut pos = pos;
-            let mut values = Vec::with_capacity(size);
+            // every element takes at least 3 bytes, do not trust array size
+            let mut values = Vec::with_capacity(cmp::min(size, buf.len() / 3));
             for _ in 0..size {
-                match decode(buf, pos) {
+                match decode(buf, pos, codec, depth + 1) {
                     Ok(None) => return Ok(None),
                     Ok(Some((new_pos, value))) => {
                         values.push(value);
@@ -683,10 +1339,137 @@ fn decode_array(buf: &mut BytesMut, idx: usize) -> DecodeResult {
     }
 }
 
-fn decode_integer(buf: &mut BytesMut, idx: usize) -> DecodeResult {
-    if let Some((pos, int)) = decode_length(buf, idx)? {
-        Ok(Some((pos, Response::Integer(int))))
-    } else {
+fn decode_push(buf: &mut BytesMut, idx: usize, codec: &Codec, depth: usize) -> DecodeResult {
+    match decode_array(buf, idx, codec, depth)? {
+        Some((pos, Response::Array(values))) => Ok(Some((pos, Response::Push(values)))),
+        Some((_, _)) => Err(Error::Parse("Invalid push message".to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Attribute is decoded and discarded, the following value is returned
+fn decode_attribute(buf: &mut BytesMut, idx: usize, codec: &Codec, depth: usize) -> DecodeResult {
+    match decode_length(buf, idx)? {
+        Some((pos, size)) if size >= 0 => {
+            let items = size.saturating_mul(2);
+            check_array_size(items, codec, depth)?;
+
+            // attribute entries and attributed value must be in buffer,
+            // decoding of bulk strings consumes buffer
+            let (is_ready, _) =
+                is_array_ready_to_decode(buf, idx, items as usize + 1, codec, depth)?;
+            if !is_ready {
+                return Ok(None);
+            }
+
+            let mut pos = pos;
+            for _ in 0..items {
+                match decode(buf, pos, codec, depth + 1)? {
+                    Some((new_pos, _)) => pos = new_pos,
+                    None => return Ok(None),
+                }
+            }
+            decode(buf, pos, codec, depth)
+        }
+        Some((_, size)) => Err(Error::Parse(format!("Invalid attribute size: {}", size))),
+        None => Ok(None),
+    }
+}
+
+fn decode_map(buf: &mut BytesMut, idx: usize, codec: &Codec, depth: usize) -> DecodeResult {
+    match decode_length(buf, idx)? {
+        Some((pos, size)) if size >= 0 => {
+            let items = size.saturating_mul(2);
+            check_array_size(items, codec, depth)?;
+
+            let (is_ready, _) = is_array_ready_to_decode(buf, idx, items as usize, codec, depth)?;
+            if !is_ready {
+                return Ok(None);
+            }
+
+            let mut pos = pos;
+            let mut values = Vec::with_capacity(cmp::min(size as usize, buf.len() / 6));
+            for _ in 0..size {
+                let key = match decode(buf, pos, codec, depth + 1)? {
+                    Some((new_pos, value)) => {
+                        pos = new_pos;
+                        value
+                    }
+                    None => return Ok(None),
+                };
+                let value = match decode(buf, pos, codec, depth + 1)? {
+                    Some((new_pos, value)) => {
+                        pos = new_pos;
+                        value
+                    }
+                    None => return Ok(None),
+                };
+                values.push((key, value));
+            }
+            Ok(Some((pos, Response::Map(values))))
+        }
+        Some((_, size)) => Err(Error::Parse(format!("Invalid map size: {}", size))),
+        None => Ok(None),
+    }
+}
+
+fn decode_null(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+    if buf.len() < idx + 2 {
+        Ok(None)
+    } else if &buf[idx..idx + 2] == b"\r\n" {
+        Ok(Some((idx + 2, Response::Nil)))
+    } else {
+        Err(Error::Parse("Invalid null value".to_string()))
+    }
+}
+
+fn decode_boolean(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+    if buf.len() < idx + 3 {
+        Ok(None)
+    } else {
+        match &buf[idx..idx + 3] {
+            b"t\r\n" => Ok(Some((idx + 3, Response::Integer(1)))),
+            b"f\r\n" => Ok(Some((idx + 3, Response::Integer(0)))),
+            _ => Err(Error::Parse("Invalid boolean value".to_string())),
+        }
+    }
+}
+
+fn decode_double(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+    if let Some((pos, string)) = scan_string(buf, idx)? {
+        if string.parse::<f64>().is_ok() {
+            Ok(Some((pos, Response::Double(string))))
+        } else {
+            Err(Error::Parse(format!(
+                "Not a double: {:?}",
+                &string[..cmp::min(string.len(), 10)]
+            )))
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+fn decode_big_number(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+    if let Some((pos, string)) = scan_string(buf, idx)? {
+        let digits = string.strip_prefix('-').unwrap_or(&string);
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(Some((pos, Response::BigNumber(string))))
+        } else {
+            Err(Error::Parse(format!(
+                "Not a big number: {:?}",
+                &string[..cmp::min(string.len(), 10)]
+            )))
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+fn decode_integer(buf: &mut BytesMut, idx: usize) -> DecodeResult {
+    if let Some((pos, int)) = decode_length(buf, idx)? {
+        Ok(Some((pos, Response::Integer(int))))
+    } else {
         Ok(None)
     }
 }
@@ -711,11 +1494,12 @@ fn decode_error(buf: &mut BytesMut, idx: usize) -> DecodeResult {
 fn scan_string(buf: &mut BytesMut, idx: usize) -> Result<Option<(usize, ByteString)>, Error> {
     if let Some(pos) = buf[idx..].windows(2).position(|w| w == b"\r\n") {
         buf.advance(idx);
-        match ByteString::try_from(buf.split_to(pos)) {
+        let data = buf.split_to(pos).freeze();
+        match ByteString::try_from(data.clone()) {
             Ok(s) => Ok(Some((2, s))),
             Err(_) => Err(Error::Parse(format!(
                 "Not a valid string: {:?}",
-                &buf[idx..idx + cmp::min(pos, 10)]
+                &data[..cmp::min(pos, 10)]
             ))),
         }
     } else {
@@ -733,20 +1517,25 @@ mod tests {
     use super::*;
     use crate::array;
 
-    fn obj_to_bytes(obj: Request) -> Bytes {
-        let mut bytes = BytesMut::new();
-        Codec.encode(obj, &mut bytes).unwrap();
-        bytes.freeze()
+    #[test]
+    fn test_encode_request() {
+        let bytes = encode_request(&array!["PING"]);
+        assert_eq!(bytes, b"*1\r\n$4\r\nPING\r\n".as_ref());
+
+        // encoder produces the same bytes
+        let mut buf = BytesMut::new();
+        Codec::new().encode(array!["PING"], &mut buf).unwrap();
+        assert_eq!(buf.freeze(), bytes);
     }
 
     #[test]
     fn test_array_macro() {
         let resp_object = array!["SET", "x"];
-        let bytes = obj_to_bytes(resp_object);
+        let bytes = encode_request(&resp_object);
         assert_eq!(bytes, b"*2\r\n$3\r\nSET\r\n$1\r\nx\r\n".as_ref());
 
         let resp_object = array!["RPUSH", "wyz"].extend(vec!["a", "b"]);
-        let bytes = obj_to_bytes(resp_object);
+        let bytes = encode_request(&resp_object);
         assert_eq!(
             bytes,
             b"*4\r\n$5\r\nRPUSH\r\n$3\r\nwyz\r\n$1\r\na\r\n$1\r\nb\r\n".as_ref(),
@@ -754,7 +1543,7 @@ mod tests {
 
         let vals = vec!["a", "b"];
         let resp_object = array!["RPUSH", "xyz"].extend(&vals);
-        let bytes = obj_to_bytes(resp_object);
+        let bytes = encode_request(&resp_object);
         assert_eq!(
             bytes,
             &b"*4\r\n$5\r\nRPUSH\r\n$3\r\nxyz\r\n$1\r\na\r\n$1\r\nb\r\n"[..],
@@ -765,7 +1554,7 @@ mod tests {
     fn test_bulk_string() {
         let req_object = Request::BulkString(Bytes::from_static(b"THISISATEST").into());
         let mut bytes = BytesMut::new();
-        let codec = Codec;
+        let codec = Codec::new();
         codec.encode(req_object.clone(), &mut bytes).unwrap();
         assert_eq!(b"$11\r\nTHISISATEST\r\n".to_vec(), bytes.to_vec());
 
@@ -774,11 +1563,212 @@ mod tests {
         assert_eq!(deserialized, resp_object);
     }
 
+    #[test]
+    fn test_bulk_string_sources() {
+        let expected = b"$4\r\ntest\r\n".as_ref();
+
+        assert_eq!(
+            encode_request(&Request::from(Cow::Borrowed("test"))),
+            expected
+        );
+        assert_eq!(
+            encode_request(&Request::from(Cow::<str>::Owned("test".to_string()))),
+            expected
+        );
+        assert_eq!(
+            encode_request(&Request::from(Cow::Borrowed(&b"test"[..]))),
+            expected
+        );
+        assert_eq!(
+            encode_request(&Request::from(Cow::<[u8]>::Owned(b"test".to_vec()))),
+            expected
+        );
+
+        let val: Arc<str> = Arc::from("test");
+        assert_eq!(encode_request(&Request::from(&val)), expected);
+        assert_eq!(encode_request(&Request::from(val)), expected);
+
+        let val: Arc<[u8]> = Arc::from(&b"test"[..]);
+        assert_eq!(encode_request(&Request::from(val)), expected);
+    }
+
+    #[test]
+    fn test_empty_values() {
+        let codec = Codec::new();
+
+        let bytes = encode_request(&Request::Array(vec![]));
+        assert_eq!(bytes, b"*0\r\n".as_ref());
+        let mut buf = BytesMut::copy_from_slice(&bytes);
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Response::Array(vec![]))
+        );
+        assert!(buf.is_empty());
+
+        let bytes = encode_request(&Request::from(""));
+        assert_eq!(bytes, b"$0\r\n\r\n".as_ref());
+        let mut buf = BytesMut::copy_from_slice(&bytes);
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Response::Bytes(Bytes::new()))
+        );
+        assert!(buf.is_empty());
+
+        // uncomplete empty bulk string
+        let mut buf = BytesMut::copy_from_slice(b"$0\r\n\r");
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+        // nested empty values
+        let bytes = encode_request(&Request::Array(vec![
+            Request::Array(vec![]),
+            Request::from(""),
+            Request::Array(vec![Request::from("")]),
+        ]));
+        assert_eq!(bytes, b"*3\r\n*0\r\n$0\r\n\r\n*1\r\n$0\r\n\r\n".as_ref());
+        let mut buf = BytesMut::copy_from_slice(&bytes);
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Response::Array(vec![
+                Response::Array(vec![]),
+                Response::Bytes(Bytes::new()),
+                Response::Array(vec![Response::Bytes(Bytes::new())]),
+            ]))
+        );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::copy_from_slice(b"*3\r\n%0\r\n~0\r\n:1\r\n");
+        assert_eq!(
+            Codec::resp3().decode(&mut buf).unwrap(),
+            Some(Response::Array(vec![
+                Response::Map(vec![]),
+                Response::Array(vec![]),
+                Response::Integer(1),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_bulk_integer() {
+        let bytes = encode_request(&Request::BulkInteger(10));
+        assert_eq!(bytes, b"$2\r\n10\r\n".as_ref());
+        assert_eq!(bytes, encode_request(&Request::from("10")));
+
+        let bytes = encode_request(&Request::BulkInteger(-1));
+        assert_eq!(bytes, encode_request(&Request::from("-1")));
+    }
+
+    #[test]
+    fn test_is_blocking() {
+        assert!(array!["BLPOP", "key", "0"].is_blocking());
+        assert!(array!["blmove", "a", "b", "LEFT", "LEFT", "0"].is_blocking());
+        assert!(array!["WAIT", "1", "0"].is_blocking());
+        assert!(array!["XREAD", "COUNT", "1", "BLOCK", "0", "STREAMS", "s", "$"].is_blocking());
+        assert!(array![
+            "XREADGROUP",
+            "GROUP",
+            "g",
+            "c",
+            "block",
+            "100",
+            "STREAMS",
+            "s",
+            ">"
+        ]
+        .is_blocking());
+
+        assert!(!array!["LPOP", "key"].is_blocking());
+        assert!(!array!["XREAD", "COUNT", "1", "STREAMS", "s", "0"].is_blocking());
+        assert!(!array!["XREAD", "STREAMS", "BLOCK", "0"].is_blocking());
+        assert!(!Request::Integer(1).is_blocking());
+    }
+
+    #[test]
+    fn test_integer_round_trip() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        const SEED: u64 = 0x7265_6469;
+
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let values = [i64::MIN, i64::MIN + 1, -2, -1, 0, 1, i64::MAX - 1, i64::MAX];
+        let random = (0..1000).map(|_| rng.gen::<i64>());
+
+        for val in values.iter().copied().chain(random) {
+            // integer reply
+            let mut bytes = BytesMut::from(&encode_request(&Request::Integer(val))[..]);
+            assert_eq!(
+                Codec::new().decode(&mut bytes).unwrap(),
+                Some(Response::Integer(val)),
+                "seed: {:#x}, value: {}",
+                SEED,
+                val
+            );
+            assert!(bytes.is_empty());
+
+            // length header
+            let mut bytes = BytesMut::new();
+            write_header(b'$', val, &mut bytes, 0);
+            assert_eq!(
+                decode_length(&mut bytes, 1).unwrap(),
+                Some((bytes.len(), val))
+            );
+
+            // bulk integer
+            let mut bytes = BytesMut::from(&encode_request(&Request::BulkInteger(val))[..]);
+            assert_eq!(
+                Codec::new().decode(&mut bytes).unwrap(),
+                Some(Response::Bytes(Bytes::from(val.to_string())))
+            );
+        }
+    }
+
+    #[test]
+    fn test_nil_sentinels() {
+        // -1 is nil only for bulk strings and arrays
+        for data in [&b"$-1\r\n"[..], b"*-1\r\n"] {
+            let mut bytes = BytesMut::copy_from_slice(data);
+            assert_eq!(
+                Codec::new().decode(&mut bytes).unwrap(),
+                Some(Response::Nil)
+            );
+        }
+        let mut bytes = BytesMut::copy_from_slice(b":-1\r\n");
+        assert_eq!(
+            Codec::new().decode(&mut bytes).unwrap(),
+            Some(Response::Integer(-1))
+        );
+        let mut bytes = BytesMut::copy_from_slice(b"*2\r\n:-1\r\n$-1\r\n");
+        assert_eq!(
+            Codec::new().decode(&mut bytes).unwrap(),
+            Some(Response::Array(vec![Response::Integer(-1), Response::Nil]))
+        );
+
+        // other negative sizes are invalid
+        for data in [&b"$-2\r\n"[..], b"*-2\r\n", b"$-9223372036854775808\r\n"] {
+            let mut bytes = BytesMut::copy_from_slice(data);
+            assert!(matches!(
+                Codec::new().decode(&mut bytes),
+                Err(Error::Parse(_))
+            ));
+        }
+        let mut bytes = BytesMut::copy_from_slice(b":-2\r\n");
+        assert_eq!(
+            Codec::new().decode(&mut bytes).unwrap(),
+            Some(Response::Integer(-2))
+        );
+
+        // out of range integer
+        let mut bytes = BytesMut::copy_from_slice(b":9223372036854775808\r\n");
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+    }
+
     #[test]
     fn test_array() {
         let req_object = Request::Array(vec![b"TEST1".as_ref().into(), b"TEST2".as_ref().into()]);
         let mut bytes = BytesMut::new();
-        let codec = Codec;
+        let codec = Codec::new();
         codec.encode(req_object.clone(), &mut bytes).unwrap();
         assert_eq!(
             b"*2\r\n$5\r\nTEST1\r\n$5\r\nTEST2\r\n".to_vec(),
@@ -795,7 +1785,7 @@ mod tests {
 
     #[test]
     fn test_decode_array() {
-        let codec = Codec;
+        let codec = Codec::new();
 
         let resp = Response::Array(vec![
             Response::Bytes(Bytes::from_static(b"TEST1")),
@@ -853,6 +1843,407 @@ mod tests {
                 Response::Array(vec![Response::Bytes(Bytes::from_static(b"a"))])
             ])]))
         );
+
+        // uncomplete outer array with complete nested array
+        let mut bytes = BytesMut::copy_from_slice(b"*2\r\n*2\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let result = codec.decode(&mut bytes).unwrap();
+        assert_eq!(result, None);
+
+        bytes.extend_from_slice(b":1\r\n");
+        let result = codec.decode(&mut bytes).unwrap();
+        assert_eq!(
+            result,
+            Some(Response::Array(vec![
+                Response::Array(vec![
+                    Response::Bytes(Bytes::from_static(b"a")),
+                    Response::Bytes(Bytes::from_static(b"b"))
+                ]),
+                Response::Integer(1)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_decode_response() {
+        let mut buf =
+            BytesMut::copy_from_slice(b"*3\r\n*2\r\n$1\r\na\r\n:-1\r\n$-1\r\n+OK\r\n*1\r\n");
+        let result = decode_response(&mut buf).unwrap();
+        assert_eq!(
+            result,
+            Some(Response::Array(vec![
+                Response::Array(vec![
+                    Response::Bytes(Bytes::from_static(b"a")),
+                    Response::Integer(-1)
+                ]),
+                Response::Nil,
+                Response::String(ByteString::from_static("OK")),
+            ]))
+        );
+        assert_eq!(&buf[..], b"*1\r\n");
+
+        // incomplete response
+        assert_eq!(decode_response(&mut buf).unwrap(), None);
+        assert_eq!(&buf[..], b"*1\r\n");
+
+        let mut buf = BytesMut::copy_from_slice(b"?garbage\r\n");
+        assert!(decode_response(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let codec = Codec::new().max_bulk_len(10).max_array_len(10).max_depth(2);
+
+        let mut bytes = BytesMut::copy_from_slice(b"$11\r\n");
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+
+        let mut bytes = BytesMut::copy_from_slice(b"$10\r\n0123456789\r\n");
+        assert!(codec.decode(&mut bytes).unwrap().is_some());
+
+        let mut bytes = BytesMut::copy_from_slice(b"*2000000000\r\n");
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+        let mut bytes = BytesMut::copy_from_slice(b"*2000000000\r\n");
+        assert!(matches!(Codec::new().decode(&mut bytes), Ok(None)));
+
+        let mut bytes = BytesMut::copy_from_slice(b"$9223372036854775807\r\n");
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let mut bytes = BytesMut::copy_from_slice(b"*1\r\n*1\r\n*1\r\n:1\r\n");
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+        let mut bytes = BytesMut::copy_from_slice(b"*1\r\n*1\r\n:1\r\n");
+        assert!(codec.decode(&mut bytes).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_decode_garbage() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        const ALPHABET: &[u8] = b"*$:+-%~_#,|(=!t\r\n0123456789-1\xff\x00ab";
+        const SEED: u64 = 0x6e74_6578;
+
+        let mut rng = StdRng::seed_from_u64(SEED);
+        for _ in 0..20_000 {
+            let len = rng.gen_range(0..64);
+            let data: Vec<u8> = (0..len)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())])
+                .collect();
+
+            let res = std::panic::catch_unwind(|| {
+                for codec in [Codec::new(), Codec::resp3()] {
+                    let mut bytes = BytesMut::copy_from_slice(&data);
+                    while let Ok(Some(_)) = codec.decode(&mut bytes) {}
+                }
+            });
+            assert!(
+                res.is_ok(),
+                "Decoder panicked, seed: {:#x}, input: {:?}",
+                SEED,
+                Bytes::copy_from_slice(&data)
+            );
+        }
+    }
+
+    #[test]
+    fn test_resp3_push() {
+        let data = b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nkey\r\n";
+
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap().unwrap(),
+            Response::Push(vec![
+                Response::Bytes(Bytes::from_static(b"invalidate")),
+                Response::Array(vec![Response::Bytes(Bytes::from_static(b"key"))]),
+            ])
+        );
+        assert!(bytes.is_empty());
+
+        // uncomplete push
+        let mut bytes = BytesMut::copy_from_slice(&data[..data.len() - 4]);
+        assert_eq!(codec.decode(&mut bytes).unwrap(), None);
+
+        let mut bytes = BytesMut::copy_from_slice(b">-1\r\n");
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_resp3_map() {
+        let data = b"%2\r\n+first\r\n:1\r\n$6\r\nsecond\r\n#t\r\n";
+
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap().unwrap(),
+            Response::Map(vec![
+                (
+                    Response::String(ByteString::from_static("first")),
+                    Response::Integer(1)
+                ),
+                (
+                    Response::Bytes(Bytes::from_static(b"second")),
+                    Response::Integer(1)
+                ),
+            ])
+        );
+        assert!(bytes.is_empty());
+
+        // uncomplete map
+        let mut bytes = BytesMut::copy_from_slice(&data[..data.len() - 4]);
+        assert_eq!(codec.decode(&mut bytes).unwrap(), None);
+
+        // nested map and set
+        let mut bytes = BytesMut::copy_from_slice(b"*2\r\n%1\r\n:1\r\n_\r\n~1\r\n:2\r\n");
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap().unwrap(),
+            Response::Array(vec![
+                Response::Map(vec![(Response::Integer(1), Response::Nil)]),
+                Response::Array(vec![Response::Integer(2)]),
+            ])
+        );
+
+        let resp_object = Response::Map(vec![(
+            Response::String(ByteString::from_static("KEY1")),
+            Response::String(ByteString::from_static("VALUE1")),
+        )]);
+        let map = HashMap::<ByteString, ByteString>::try_from(resp_object).unwrap();
+        assert_eq!(map.get("KEY1").unwrap(), "VALUE1");
+    }
+
+    #[test]
+    fn test_resp3_double() {
+        let data = b",3.25\r\n";
+
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(b",3.25\r\n*2\r\n,-inf\r\n,1e3\r\n,abc\r\n");
+        let res = codec.decode(&mut bytes).unwrap().unwrap();
+        assert_eq!(res, Response::Double(ByteString::from_static("3.25")));
+        assert_eq!(res.kind(), ResponseKind::Double);
+        assert!(!res.is_simple_string());
+        assert_eq!(f64::try_from(res).unwrap(), 3.25);
+
+        let res = codec.decode(&mut bytes).unwrap().unwrap();
+        let res = Vec::<f64>::try_from(res).unwrap();
+        assert_eq!(res, vec![f64::NEG_INFINITY, 1000.0]);
+
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+
+        // uncomplete double
+        let mut bytes = BytesMut::copy_from_slice(b",3.1");
+        assert_eq!(codec.decode(&mut bytes).unwrap(), None);
+    }
+
+    #[test]
+    fn test_resp3_verbatim_string() {
+        let data = b"=15\r\ntxt:Some string\r\n";
+
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::Bytes(Bytes::from_static(b"Some string")))
+        );
+        assert!(bytes.is_empty());
+
+        // empty verbatim string
+        let mut bytes = BytesMut::copy_from_slice(b"*1\r\n=4\r\nmkd:\r\n");
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::Array(vec![Response::Bytes(Bytes::new())]))
+        );
+
+        // uncomplete verbatim string
+        let mut bytes = BytesMut::copy_from_slice(&data[..data.len() - 3]);
+        assert_eq!(codec.decode(&mut bytes).unwrap(), None);
+
+        // missing format prefix
+        let mut bytes = BytesMut::copy_from_slice(b"=2\r\nab\r\n");
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_resp3_big_number() {
+        let data = b"(3492890328409238509324850943850943825024385\r\n";
+
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::BigNumber(ByteString::from_static(
+                "3492890328409238509324850943850943825024385"
+            )))
+        );
+        assert!(bytes.is_empty());
+
+        let mut bytes = BytesMut::copy_from_slice(b"*2\r\n(-12\r\n:1\r\n(1a\r\n");
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::Array(vec![
+                Response::BigNumber(ByteString::from_static("-12")),
+                Response::Integer(1)
+            ]))
+        );
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+
+        // uncomplete big number
+        let mut bytes = BytesMut::copy_from_slice(&data[..10]);
+        assert_eq!(codec.decode(&mut bytes).unwrap(), None);
+    }
+
+    #[test]
+    fn test_resp3_blob_error() {
+        let data = b"!21\r\nSYNTAX invalid syntax\r\n";
+
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert!(matches!(
+            Codec::new().decode(&mut bytes),
+            Err(Error::Parse(_))
+        ));
+
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(data);
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::Error(ByteString::from_static(
+                "SYNTAX invalid syntax"
+            )))
+        );
+        assert!(bytes.is_empty());
+
+        // uncomplete blob error
+        let mut bytes = BytesMut::copy_from_slice(&data[..data.len() - 1]);
+        assert_eq!(codec.decode(&mut bytes).unwrap(), None);
+
+        let mut bytes = BytesMut::copy_from_slice(b"!-1\r\n");
+        assert!(matches!(codec.decode(&mut bytes), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_resp3_attribute() {
+        let codec = Codec::resp3();
+
+        // attribute is discarded, attributed value is returned
+        let mut bytes = BytesMut::copy_from_slice(
+            b"|1\r\n+key-popularity\r\n%1\r\n$1\r\na\r\n,0.19\r\n:42\r\n",
+        );
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::Integer(42))
+        );
+        assert!(bytes.is_empty());
+
+        // attribute of array element
+        let mut bytes =
+            BytesMut::copy_from_slice(b"*2\r\n:1\r\n|1\r\n+ttl\r\n:3600\r\n$2\r\nab\r\n");
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::Array(vec![
+                Response::Integer(1),
+                Response::Bytes(Bytes::from_static(b"ab"))
+            ]))
+        );
+
+        // empty attribute
+        let mut bytes = BytesMut::copy_from_slice(b"|0\r\n+OK\r\n");
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Response::String("OK".into()))
+        );
+
+        // attributes are not supported by RESP2
+        let mut bytes = BytesMut::copy_from_slice(b"|0\r\n+OK\r\n");
+        assert!(Codec::new().decode(&mut bytes).is_err());
+    }
+
+    #[test]
+    fn test_partial_aggregates() {
+        let data: &[(&[u8], Codec)] = &[
+            (b"*2\r\n$1\r\na\r\n$5\r\nvalue\r\n", Codec::new()),
+            (b"*3\r\n$1\r\na\r\n*1\r\n$2\r\nbc\r\n:10\r\n", Codec::new()),
+            (b"|1\r\n$1\r\na\r\n$1\r\nb\r\n:42\r\n", Codec::resp3()),
+            (
+                b"|1\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nvalue\r\n",
+                Codec::resp3(),
+            ),
+            (
+                b"*3\r\n=7\r\ntxt:abc\r\n(12\r\n!3\r\nERR\r\n",
+                Codec::resp3(),
+            ),
+        ];
+
+        // response is decoded only when it is completely received
+        for (data, codec) in data {
+            let expected = codec
+                .decode(&mut BytesMut::copy_from_slice(data))
+                .unwrap()
+                .unwrap();
+            for split in 1..data.len() {
+                let mut bytes = BytesMut::copy_from_slice(&data[..split]);
+                assert_eq!(codec.decode(&mut bytes).unwrap(), None, "{}", split);
+                bytes.extend_from_slice(&data[split..]);
+                assert_eq!(codec.decode(&mut bytes).unwrap(), Some(expected.clone()));
+                assert!(bytes.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_response_kind() {
+        let codec = Codec::new();
+
+        let mut bytes = BytesMut::copy_from_slice(b"+OK\r\n$2\r\nOK\r\n");
+        let simple = codec.decode(&mut bytes).unwrap().unwrap();
+        assert_eq!(simple.kind(), ResponseKind::SimpleString);
+        assert!(simple.is_simple_string());
+
+        let bulk = codec.decode(&mut bytes).unwrap().unwrap();
+        assert_eq!(bulk.kind(), ResponseKind::BulkString);
+        assert!(!bulk.is_simple_string());
+
+        // both convert to the same string
+        assert_eq!(
+            ByteString::try_from(simple).unwrap(),
+            ByteString::try_from(bulk).unwrap()
+        );
+        let codec = Codec::resp3();
+        let mut bytes = BytesMut::copy_from_slice(b",3.14\r\n(12\r\n");
+        let double = codec.decode(&mut bytes).unwrap().unwrap();
+        assert_eq!(double.kind(), ResponseKind::Double);
+        assert!(!double.is_simple_string());
+        let big = codec.decode(&mut bytes).unwrap().unwrap();
+        assert_eq!(big.kind(), ResponseKind::BigNumber);
+        assert!(!big.is_simple_string());
     }
 
     #[test]
@@ -860,14 +2251,14 @@ mod tests {
         let mut bytes = BytesMut::new();
         bytes.extend_from_slice(&b"$-1\r\n"[..]);
 
-        let codec = Codec;
+        let codec = Codec::new();
         let deserialized = codec.decode(&mut bytes).unwrap().unwrap();
         assert_eq!(deserialized, Response::Nil);
     }
 
     #[test]
     fn test_integer_overflow() {
-        let resp_object = Response::Integer(i64::max_value());
+        let resp_object = Response::Integer(i64::MAX);
         let res = i32::try_from(resp_object);
         assert!(res.is_err());
     }
@@ -886,16 +2277,113 @@ mod tests {
     }
 
     #[test]
-    fn test_hashmap_conversion() {
-        let mut expected = HashMap::default();
-        expected.insert(
-            ByteString::from("KEY1").into(),
-            ByteString::from("VALUE1").into(),
+    fn test_into_string_lossy() {
+        let res = Response::Bytes(Bytes::from_static(b"hello \xF0\x90\x80world"));
+        assert_eq!(res.clone().into_string_lossy(), "hello \u{FFFD}world");
+        assert!(ByteString::try_from(res).is_err());
+
+        let res = Response::Bytes(Bytes::from_static(b"valid"));
+        assert_eq!(res.into_string_lossy(), "valid");
+        let res = Response::String(ByteString::from_static("OK"));
+        assert_eq!(res.into_string_lossy(), "OK");
+        assert_eq!(Response::Integer(10).into_string_lossy(), "10");
+        assert_eq!(Response::Nil.into_string_lossy(), "Nil");
+    }
+
+    #[test]
+    fn test_response_pairs() {
+        let res = Response::Array(vec![
+            Response::Bytes(Bytes::from_static(b"b")),
+            Response::Bytes(Bytes::from_static(b"1")),
+            Response::Bytes(Bytes::from_static(b"a")),
+            Response::Bytes(Bytes::from_static(b"2")),
+        ]);
+        assert_eq!(
+            response_pairs(res).unwrap(),
+            vec![
+                (Bytes::from_static(b"b"), Bytes::from_static(b"1")),
+                (Bytes::from_static(b"a"), Bytes::from_static(b"2")),
+            ]
         );
-        expected.insert(
-            ByteString::from("KEY2").into(),
-            ByteString::from("VALUE2").into(),
+
+        let res = Response::Map(vec![(
+            Response::Bytes(Bytes::from_static(b"key")),
+            Response::Integer(1),
+        )]);
+        let pairs: Vec<(Bytes, i64)> = res.into_pairs().unwrap();
+        assert_eq!(pairs, vec![(Bytes::from_static(b"key"), 1)]);
+
+        let res = Response::Array(vec![Response::Bytes(Bytes::from_static(b"key"))]);
+        assert!(response_pairs(res).is_err());
+        assert!(response_pairs(Response::Nil).is_err());
+    }
+
+    #[test]
+    fn test_tuple_conversion() {
+        let resp_object = Response::Array(vec![
+            Response::String(ByteString::from_static("KEY1")),
+            Response::Integer(1),
+            Response::String(ByteString::from_static("KEY2")),
+            Response::Integer(2),
+        ]);
+        assert_eq!(
+            <(ByteString, i64, ByteString, i64)>::try_from(resp_object).unwrap(),
+            (ByteString::from("KEY1"), 1, ByteString::from("KEY2"), 2)
+        );
+
+        let resp_object = Response::Array(vec![
+            Response::Integer(1),
+            Response::Integer(2),
+            Response::Integer(3),
+            Response::Integer(4),
+            Response::Nil,
+        ]);
+        assert_eq!(
+            <(i64, i64, i64, i64, Option<i64>)>::try_from(resp_object).unwrap(),
+            (1, 2, 3, 4, None)
+        );
+
+        let resp_object = Response::Array(vec![Response::Integer(1), Response::Integer(2)]);
+        assert!(<(i64, i64, i64, i64)>::try_from(resp_object).is_err());
+    }
+
+    #[test]
+    fn test_option_conversion() {
+        assert_eq!(Option::<i64>::try_from(Response::Nil).unwrap(), None);
+        assert_eq!(
+            Option::<i64>::try_from(Response::Integer(10)).unwrap(),
+            Some(10)
+        );
+        assert_eq!(
+            Option::<Bytes>::try_from(Response::Bytes(Bytes::from_static(b"value"))).unwrap(),
+            Some(Bytes::from_static(b"value"))
         );
+        assert!(Option::<i64>::try_from(Response::Bytes(Bytes::new())).is_err());
+    }
+
+    #[test]
+    fn test_float_conversion() {
+        let resp_object = Response::Bytes(Bytes::from_static(b"2.5"));
+        assert_eq!(f64::try_from(resp_object).unwrap(), 2.5);
+
+        let resp_object = Response::Bytes(Bytes::from_static(b"inf"));
+        assert_eq!(f64::try_from(resp_object).unwrap(), f64::INFINITY);
+
+        let resp_object = Response::Bytes(Bytes::from_static(b"-inf"));
+        assert_eq!(f64::try_from(resp_object).unwrap(), f64::NEG_INFINITY);
+
+        let resp_object = Response::String(ByteString::from_static("nan"));
+        assert!(f64::try_from(resp_object).unwrap().is_nan());
+
+        let resp_object = Response::Bytes(Bytes::from_static(b"value"));
+        assert!(f64::try_from(resp_object).is_err());
+    }
+
+    #[test]
+    fn test_hashmap_conversion() {
+        let mut expected = HashMap::default();
+        expected.insert(ByteString::from("KEY1"), ByteString::from("VALUE1"));
+        expected.insert(ByteString::from("KEY2"), ByteString::from("VALUE2"));
 
         let resp_object = Response::Array(vec![
             Response::String(ByteString::from_static("KEY1")),
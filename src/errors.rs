@@ -23,8 +23,12 @@ impl std::error::Error for Error {}
 impl Clone for Error {
     fn clone(&self) -> Self {
         match self {
-            Error::Parse(_) => Error::Parse(String::new()),
-            Error::PeerGone(_) => Error::PeerGone(None),
+            Error::Parse(msg) => Error::Parse(msg.clone()),
+            // io::Error is not cloneable, keep kind and message
+            Error::PeerGone(err) => Error::PeerGone(
+                err.as_ref()
+                    .map(|err| io::Error::new(err.kind(), err.to_string())),
+            ),
         }
     }
 }
@@ -55,28 +59,186 @@ pub enum ConnectError {
 
     /// Io connectivity error
     Connect(connect::ConnectError),
+
+    /// Malformed connection url
+    #[from(ignore)]
+    #[display(fmt = "Invalid redis url: {}", _0)]
+    Url(String),
 }
 
 impl std::error::Error for ConnectError {}
 
-#[derive(Debug, Display, From, Clone)]
+#[derive(Debug, Display, From)]
 /// Redis command execution errors
 pub enum CommandError {
     /// A redis server error response
+    #[from(ignore)]
     Error(ByteString),
 
+    /// Operation against a key holding the wrong kind of value (`WRONGTYPE` error)
+    #[from(ignore)]
+    WrongType(ByteString),
+
     /// A command response parse error
     #[display(fmt = "Command output parse error: {}", _0)]
     Output(&'static str, Response),
 
     /// Redis protocol level errors
+    ///
+    /// If connection is lost after command is sent, command could be
+    /// executed by redis.
     Protocol(Error),
+
+    /// Connection is closed before command is sent, command
+    /// could be safely retried
+    #[display(fmt = "Connection is closed, command is not sent")]
+    NotSent,
+
+    /// Command execution timeout
+    #[display(fmt = "Command execution timeout")]
+    Timeout,
+
+    /// Local io error, i.e. failure of the writer passed to `Client::get_to()`
+    ///
+    /// Redis connection is not affected.
+    #[from(ignore)]
+    #[display(fmt = "Local io error: {}", _0)]
+    Io(io::Error),
+
+    /// Command is rejected by client, command is not sent
+    #[from(ignore)]
+    #[display(fmt = "Command is not supported: {}", _0)]
+    Unsupported(&'static str),
+
+    /// Command argument is invalid, command is not sent
+    #[from(ignore)]
+    #[display(fmt = "Invalid command argument: {}", _0)]
+    InvalidArgument(&'static str),
+
+    /// Redis server version is lower than required, `(version, required)`
+    #[from(ignore)]
+    #[display(
+        fmt = "Redis server version {}.{}.{} is lower than required {}.{}.{}",
+        "_0.0",
+        "_0.1",
+        "_0.2",
+        "_1.0",
+        "_1.1",
+        "_1.2"
+    )]
+    UnsupportedVersion((u32, u32, u32), (u32, u32, u32)),
+
+    /// Connection cannot be established by `RetryClient` or
+    /// `ReconnectSubscriber`, i.e. authentication failed
+    #[from(ignore)]
+    #[display(fmt = "Redis connect error: {}", _0)]
+    Connect(Box<ConnectError>),
 }
 
 impl std::error::Error for CommandError {}
 
+impl Clone for CommandError {
+    fn clone(&self) -> Self {
+        match self {
+            CommandError::Error(err) => CommandError::Error(err.clone()),
+            CommandError::WrongType(err) => CommandError::WrongType(err.clone()),
+            CommandError::Output(msg, val) => CommandError::Output(msg, val.clone()),
+            CommandError::Protocol(err) => CommandError::Protocol(err.clone()),
+            CommandError::NotSent => CommandError::NotSent,
+            CommandError::Timeout => CommandError::Timeout,
+            // io::Error is not cloneable, keep kind and message
+            CommandError::Io(err) => CommandError::Io(io::Error::new(err.kind(), err.to_string())),
+            CommandError::Unsupported(msg) => CommandError::Unsupported(msg),
+            CommandError::InvalidArgument(msg) => CommandError::InvalidArgument(msg),
+            CommandError::UnsupportedVersion(version, required) => {
+                CommandError::UnsupportedVersion(*version, *required)
+            }
+            CommandError::Connect(err) => CommandError::Connect(err.clone()),
+        }
+    }
+}
+
+impl From<ByteString> for CommandError {
+    fn from(err: ByteString) -> CommandError {
+        if err.starts_with("WRONGTYPE ") {
+            CommandError::WrongType(err)
+        } else {
+            CommandError::Error(err)
+        }
+    }
+}
+
 impl From<Either<Error, io::Error>> for CommandError {
     fn from(err: Either<Error, io::Error>) -> CommandError {
         Into::<Error>::into(err).into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_clone() {
+        let err = Error::Parse("bad".into()).clone();
+        assert!(matches!(err, Error::Parse(ref msg) if msg == "bad"));
+        assert_eq!(err.to_string(), "Redis server response error: bad");
+
+        let err = CommandError::Protocol(Error::Parse("bad".into())).clone();
+        assert!(matches!(err, CommandError::Protocol(Error::Parse(ref msg)) if msg == "bad"));
+    }
+
+    #[test]
+    fn test_peer_gone_clone() {
+        let err = Error::PeerGone(Some(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "reset by peer",
+        )))
+        .clone();
+        match err {
+            Error::PeerGone(Some(err)) => {
+                assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+                assert_eq!(err.to_string(), "reset by peer");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+        assert!(matches!(
+            Error::PeerGone(None).clone(),
+            Error::PeerGone(None)
+        ));
+    }
+
+    #[test]
+    fn test_io_error_clone() {
+        let err = CommandError::Io(io::Error::new(io::ErrorKind::WriteZero, "full")).clone();
+        match err {
+            CommandError::Io(err) => {
+                assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+                assert_eq!(err.to_string(), "full");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_client_errors_display() {
+        let err = CommandError::Unsupported("blocking command").clone();
+        assert_eq!(
+            err.to_string(),
+            "Command is not supported: blocking command"
+        );
+
+        let err = CommandError::InvalidArgument("ttl").clone();
+        assert_eq!(err.to_string(), "Invalid command argument: ttl");
+
+        let err = CommandError::UnsupportedVersion((6, 2, 14), (7, 0, 0)).clone();
+        assert!(matches!(
+            err,
+            CommandError::UnsupportedVersion((6, 2, 14), (7, 0, 0))
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Redis server version 6.2.14 is lower than required 7.0.0"
+        );
+    }
+}
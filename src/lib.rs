@@ -36,11 +36,20 @@ pub mod cmd;
 pub mod codec;
 mod connector;
 pub mod errors;
+mod pipeline;
+mod reconnect;
+mod retry;
 mod simple;
 
-pub use self::client::Client;
-pub use self::connector::RedisConnector;
-pub use self::simple::{SimpleClient, SubscriptionClient};
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_support;
+
+pub use self::client::{Client, CommandContext, InvalidationStream};
+pub use self::connector::{RedisConnector, UrlConnector};
+pub use self::pipeline::{Pipeline, PipelineHandle};
+pub use self::reconnect::{ReconnectItem, ReconnectSubscriber};
+pub use self::retry::{RetryClient, RetryPolicy};
+pub use self::simple::{BoundedConsumer, SimpleClient, SubscriptionBroadcast, SubscriptionClient};
 
 /// Macro to create a request array, useful for preparing commands to send. Elements can be any type, or a mixture
 /// of types, that satisfy `Into<Request>`.
@@ -76,14 +85,4 @@ macro_rules! array {
 }
 
 #[cfg(test)]
-pub fn gen_random_key() -> String {
-    use rand::distributions::Alphanumeric;
-    use rand::{thread_rng, Rng};
-
-    let key: String = thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(12)
-        .map(char::from)
-        .collect();
-    key
-}
+pub use self::test_support::gen_random_key;
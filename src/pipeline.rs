@@ -0,0 +1,183 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use ntex::util::join_all;
+
+use super::cmd::Command;
+use super::codec::{Request, Response};
+use super::errors::CommandError;
+use super::Client;
+
+type Slots = Rc<RefCell<Vec<Option<Result<Response, CommandError>>>>>;
+
+/// Batch of commands executed in one round trip
+///
+/// Each queued command returns typed handle, command's result is
+/// available from handle after pipeline is executed. Commands are not
+/// executed atomically, use `MULTI`/`EXEC` for transactions.
+///
+/// ```rust
+/// use ntex_redis::{cmd, RedisConnector};
+///
+/// #[ntex::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let redis = RedisConnector::new("127.0.0.1:6379").connect().await?;
+///
+///     let mut pipe = redis.pipeline();
+///     let set = pipe.queue(cmd::Set("key", "1"));
+///     let incr = pipe.queue(cmd::IncrBy("key", 1));
+///     let get = pipe.queue(cmd::Get("key"));
+///     pipe.exec().await?;
+///
+///     assert!(set.take()?);
+///     assert_eq!(incr.take()?, 2);
+///     assert_eq!(get.take()?.unwrap(), "2");
+///     Ok(())
+/// }
+/// ```
+pub struct Pipeline<'a> {
+    client: &'a Client,
+    requests: Vec<Request>,
+    slots: Slots,
+}
+
+/// Typed handle of a command queued to pipeline
+pub struct PipelineHandle<U> {
+    slots: Slots,
+    index: usize,
+    _cmd: PhantomData<U>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub(crate) fn new(client: &'a Client) -> Self {
+        Pipeline {
+            client,
+            requests: Vec::new(),
+            slots: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Queue redis command
+    pub fn queue<U>(&mut self, cmd: U) -> PipelineHandle<U>
+    where
+        U: Command,
+    {
+        self.requests.push(cmd.to_request());
+        PipelineHandle {
+            slots: self.slots.clone(),
+            index: self.requests.len() - 1,
+            _cmd: PhantomData,
+        }
+    }
+
+    /// Number of queued commands
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns true if pipeline has no queued commands
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Send all queued commands and wait for responses
+    ///
+    /// Returns first connection level error, command errors (redis error
+    /// responses, output conversion errors) are returned by handles.
+    pub async fn exec(self) -> Result<(), CommandError> {
+        let client = self.client;
+        let results = join_all(self.requests.into_iter().map(|req| async move {
+            client.check_request(&req)?;
+            client._call(req).await.map_err(CommandError::Protocol)
+        }))
+        .await;
+
+        let err = results.iter().find_map(|res| match res {
+            Err(err @ CommandError::Protocol(_)) | Err(err @ CommandError::NotSent) => {
+                Some(err.clone())
+            }
+            _ => None,
+        });
+        *self.slots.borrow_mut() = results.into_iter().map(Some).collect();
+
+        if let Some(err) = err {
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<U> PipelineHandle<U>
+where
+    U: Command,
+{
+    /// Take command's result
+    ///
+    /// Returns `CommandError::NotSent` if pipeline is not executed.
+    pub fn take(self) -> Result<U::Output, CommandError> {
+        let res = self
+            .slots
+            .borrow_mut()
+            .get_mut(self.index)
+            .and_then(Option::take);
+
+        match res {
+            Some(res) => U::to_output(res?.into_result().map_err(U::to_error)?),
+            None => Err(CommandError::NotSent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::{io::Io, testing::IoTest, util::join};
+
+    use super::*;
+    use crate::{cmd, codec::Codec};
+
+    fn test_client() -> (Client, IoTest) {
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1024);
+        server.remote_buffer_cap(1024);
+        (
+            Client::new(Io::new(client).into(), Codec::new(), true),
+            server,
+        )
+    }
+
+    #[ntex::test]
+    async fn test_pipeline() {
+        let (redis, server) = test_client();
+
+        let mut pipe = redis.pipeline();
+        let get = pipe.queue(cmd::Get("key"));
+        let incr = pipe.queue(cmd::IncrBy("counter", 1));
+        let set = pipe.queue(cmd::Set("key", "value"));
+        let blocking = pipe.queue(cmd::BLMPop(1.0, ["list"]));
+        assert_eq!(pipe.len(), 4);
+
+        let (res, _) = join(pipe.exec(), async {
+            let req = server.read().await.unwrap();
+            assert!(req.starts_with(b"*2\r\n$3\r\nGET\r\n"));
+            assert!(!req.windows(6).any(|w| w == b"BLMPOP"));
+            server.write(b"$5\r\nvalue\r\n-ERR not an integer\r\n+OK\r\n");
+        })
+        .await;
+        res.unwrap();
+
+        assert_eq!(get.take().unwrap().unwrap(), "value");
+        assert!(matches!(incr.take(), Err(CommandError::Error(_))));
+        assert!(set.take().unwrap());
+        assert!(matches!(blocking.take(), Err(CommandError::Unsupported(_))));
+    }
+
+    #[ntex::test]
+    async fn test_pipeline_not_executed() {
+        let (redis, _server) = test_client();
+
+        let mut pipe = redis.pipeline();
+        let get = pipe.queue(cmd::Get("key"));
+        drop(pipe);
+        assert!(matches!(get.take(), Err(CommandError::NotSent)));
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use ntex::connect::{self, Address, Connect};
+use ntex::{io::IoBoxed, service::Service, time::sleep, util::Bytes};
+
+use super::cmd::{commands::PubSubCommand, commands::SubscribeOutputCommand};
+use super::cmd::{Command, SubscribeItem};
+use super::codec::Request;
+use super::errors::{CommandError, ConnectError};
+use super::retry::{connect_error, RetryPolicy};
+use super::{RedisConnector, SimpleClient};
+
+/// Redis pubsub client that restores subscriptions after reconnect
+///
+/// Client keeps current set of subscribed channels and patterns. If connection
+/// is lost, client reconnects according to retry policy, subscribes to the
+/// same channels and patterns and emits `ReconnectItem::Reconnected` item.
+/// Messages published while client is disconnected are lost.
+///
+/// It is a separate type rather than a `SubscriptionClient` mode, because
+/// `SubscriptionClient` owns a single connection and has no connector to
+/// reconnect with. Reconnect marker is `ReconnectItem` variant, so
+/// `SubscribeItem` of non-reconnecting subscriptions stays unchanged.
+pub struct ReconnectSubscriber<A, T> {
+    connector: RedisConnector<A, T>,
+    policy: RetryPolicy,
+    client: SimpleClient,
+    subscriptions: Subscriptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Item received by `ReconnectSubscriber`
+pub enum ReconnectItem {
+    /// Subscription item
+    Item(SubscribeItem),
+    /// Connection is re-established and subscriptions are restored,
+    /// messages published during reconnect are lost
+    Reconnected,
+}
+
+impl<A, T> ReconnectSubscriber<A, T>
+where
+    A: Address + Clone,
+    T: Service<Connect<A>, Error = connect::ConnectError>,
+    IoBoxed: From<T::Response>,
+{
+    pub(crate) async fn new(
+        connector: RedisConnector<A, T>,
+        policy: RetryPolicy,
+        cmd: SubscribeOutputCommand,
+    ) -> Result<Self, ConnectError> {
+        let client = connector.connect_simple().await?;
+        let req = cmd.to_request();
+        client.send_request(req.clone())?;
+
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.update(&req);
+        Ok(ReconnectSubscriber {
+            connector,
+            policy,
+            client,
+            subscriptions,
+        })
+    }
+
+    /// Send redis subscribe/unsubscribe command
+    ///
+    /// Subscriptions are restored after reconnect.
+    pub fn send<U: Command + PubSubCommand>(&mut self, cmd: U) -> Result<(), CommandError> {
+        let req = cmd.to_request();
+        self.client.send_request(req.clone())?;
+        self.subscriptions.update(&req);
+        Ok(())
+    }
+
+    /// Receive next subscription item
+    ///
+    /// Returns error if connection cannot be restored.
+    pub async fn recv(&mut self) -> Result<ReconnectItem, CommandError> {
+        match self.client.recv::<SubscribeOutputCommand>().await {
+            Err(CommandError::Protocol(err)) => {
+                log::info!("Subscription connection is lost: {:?}", err);
+                self.reconnect().await
+            }
+            item => item.map(ReconnectItem::Item),
+        }
+    }
+
+    async fn reconnect(&mut self) -> Result<ReconnectItem, CommandError> {
+        let mut backoff = self.policy.backoff;
+        let mut attempt = 1;
+
+        loop {
+            match self.try_reconnect().await {
+                Ok(()) => return Ok(ReconnectItem::Reconnected),
+                Err(err) if attempt < self.policy.max_attempts => {
+                    log::trace!("Cannot restore subscription, retrying: {:?}", err);
+                    sleep(backoff).await;
+                    backoff = self.policy.next_backoff(backoff);
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn try_reconnect(&mut self) -> Result<(), CommandError> {
+        let client = self
+            .connector
+            .connect_simple()
+            .await
+            .map_err(connect_error)?;
+        for req in self.subscriptions.requests() {
+            client.send_request(req)?;
+        }
+        self.client = client;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+/// Currently subscribed channels and patterns
+struct Subscriptions {
+    channels: HashSet<Bytes>,
+    patterns: HashSet<Bytes>,
+    shard_channels: HashSet<Bytes>,
+}
+
+impl Subscriptions {
+    fn update(&mut self, req: &Request) {
+        let name = match req.command_name() {
+            Some(name) => name.to_ascii_uppercase(),
+            None => return,
+        };
+        let (set, subscribe) = match &name[..] {
+            b"SUBSCRIBE" => (&mut self.channels, true),
+            b"UNSUBSCRIBE" => (&mut self.channels, false),
+            b"PSUBSCRIBE" => (&mut self.patterns, true),
+            b"PUNSUBSCRIBE" => (&mut self.patterns, false),
+            b"SSUBSCRIBE" => (&mut self.shard_channels, true),
+            b"SUNSUBSCRIBE" => (&mut self.shard_channels, false),
+            _ => return,
+        };
+        let args = match req {
+            Request::Array(vals) => &vals[1..],
+            _ => &[],
+        };
+
+        if subscribe {
+            set.extend(args.iter().filter_map(arg_bytes));
+        } else if args.is_empty() {
+            // unsubscribe without arguments removes all subscriptions
+            set.clear();
+        } else {
+            for arg in args.iter().filter_map(arg_bytes) {
+                set.remove(&arg);
+            }
+        }
+    }
+
+    /// Subscribe commands restoring current subscriptions
+    fn requests(&self) -> Vec<Request> {
+        [
+            ("SUBSCRIBE", &self.channels),
+            ("PSUBSCRIBE", &self.patterns),
+            ("SSUBSCRIBE", &self.shard_channels),
+        ]
+        .iter()
+        .filter(|(_, set)| !set.is_empty())
+        .map(|(name, set)| {
+            set.iter().fold(Request::from_static(name), |req, arg| {
+                req.add(Request::BulkString(arg.clone().into()))
+            })
+        })
+        .collect()
+    }
+}
+
+fn arg_bytes(arg: &Request) -> Option<Bytes> {
+    arg.as_bytes().map(Bytes::copy_from_slice)
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::time::Millis;
+
+    use super::*;
+    use crate::cmd;
+    use crate::connector::tests::{test_io, TestConnector};
+
+    fn message(payload: &str) -> String {
+        format!(
+            "*3\r\n$7\r\nmessage\r\n$4\r\ntest\r\n${}\r\n{}\r\n",
+            payload.len(),
+            payload
+        )
+    }
+
+    #[ntex::test]
+    async fn test_reconnect() {
+        const SUBSCRIBED: &[u8] = b"*3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n";
+
+        let (client1, server1) = test_io();
+        let (client2, server2) = test_io();
+
+        let connector = RedisConnector::new("127.0.0.1:6379")
+            .connector(TestConnector::new(0, vec![client1, client2]).0);
+        let policy = RetryPolicy::new().backoff(Millis(1), Millis(10));
+        let mut subscriber = connector
+            .subscribe_reconnect(cmd::Subscribe(["test"]), policy)
+            .await
+            .unwrap();
+
+        let req = server1.read().await.unwrap();
+        assert!(req.ends_with(b"SUBSCRIBE\r\n$4\r\ntest\r\n"));
+        server1.write(SUBSCRIBED);
+        server1.write(message("1"));
+
+        assert!(matches!(
+            subscriber.recv().await.unwrap(),
+            ReconnectItem::Item(SubscribeItem::Subscribed { .. })
+        ));
+        assert!(matches!(
+            subscriber.recv().await.unwrap(),
+            ReconnectItem::Item(SubscribeItem::Message { payload, .. })
+                if payload == Bytes::from_static(b"1")
+        ));
+
+        // connection is lost, subscription is replayed on new connection
+        server1.close().await;
+        assert_eq!(subscriber.recv().await.unwrap(), ReconnectItem::Reconnected);
+
+        let req = server2.read().await.unwrap();
+        assert!(req.ends_with(b"SUBSCRIBE\r\n$4\r\ntest\r\n"));
+        server2.write(SUBSCRIBED);
+        server2.write(message("2"));
+
+        assert!(matches!(
+            subscriber.recv().await.unwrap(),
+            ReconnectItem::Item(SubscribeItem::Subscribed { .. })
+        ));
+        assert!(matches!(
+            subscriber.recv().await.unwrap(),
+            ReconnectItem::Item(SubscribeItem::Message { payload, .. })
+                if payload == Bytes::from_static(b"2")
+        ));
+
+        // no more connections
+        server2.close().await;
+        assert!(subscriber.recv().await.is_err());
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let mut subs = Subscriptions::default();
+        subs.update(&cmd::Subscribe(["a", "b", "c"]).to_request());
+        subs.update(&cmd::PSubscribe(["p*"]).to_request());
+        subs.update(&cmd::SSubscribe(["s"]).to_request());
+        subs.update(&cmd::UnSubscribe(Some(vec!["a", "c"])).to_request());
+        subs.update(&cmd::SUnSubscribe::<&str>(None).to_request());
+        // duplicate subscription
+        subs.update(&cmd::Subscribe(["b"]).to_request());
+
+        assert_eq!(
+            subs.requests(),
+            vec![
+                cmd::Subscribe(["b"]).to_request(),
+                cmd::PSubscribe(["p*"]).to_request(),
+            ]
+        );
+
+        subs.update(&cmd::UnSubscribe::<&str>(None).to_request());
+        subs.update(&cmd::PUnSubscribe(Some(vec!["p*"])).to_request());
+        assert!(subs.requests().is_empty());
+    }
+}
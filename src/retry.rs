@@ -0,0 +1,480 @@
+use std::{cell::Cell, cell::RefCell, io, rc::Rc, time::Duration};
+
+use ntex::connect::{self, Address, Connect};
+use ntex::time::{sleep, timeout, Millis};
+use ntex::{channel::oneshot, io::IoBoxed, service::Service};
+
+use super::cmd::Command;
+use super::codec::Request;
+use super::errors::{CommandError, ConnectError, Error};
+use super::{Client, RedisConnector};
+
+#[derive(Debug, Copy, Clone)]
+/// Retry policy for idempotent commands
+pub struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) backoff: Millis,
+    max_backoff: Millis,
+    timeout: Millis,
+}
+
+impl RetryPolicy {
+    /// Create new retry policy.
+    ///
+    /// By default command is executed at most 3 times, initial backoff
+    /// is 100 millis and max backoff is 5 seconds. Attempt timeout is disabled.
+    pub const fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff: Millis(100),
+            max_backoff: Millis(5_000),
+            timeout: Millis::ZERO,
+        }
+    }
+
+    /// Set max number of attempts, including the first one.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Set backoff between attempts.
+    ///
+    /// Backoff doubles after each failed attempt, up to `max` value.
+    pub fn backoff(mut self, initial: Millis, max: Millis) -> Self {
+        self.backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Set timeout for each attempt.
+    ///
+    /// Zero value disables timeout. Timed out attempt fails
+    /// with `CommandError::Timeout` error.
+    pub fn timeout(mut self, timeout: Millis) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff for next attempt
+    pub(crate) fn next_backoff(&self, backoff: Millis) -> Millis {
+        Millis(backoff.0.saturating_mul(2).min(self.max_backoff.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new()
+    }
+}
+
+/// Redis client with automatic reconnect and retries
+///
+/// Client connects to redis lazily and re-connects if connection is lost.
+/// Only commands executed with `exec_idempotent()` are retried on transient
+/// errors, retrying non-idempotent commands could apply them more than once.
+pub struct RetryClient<A, T> {
+    inner: Rc<Inner<A, T>>,
+}
+
+struct Inner<A, T> {
+    connector: RedisConnector<A, T>,
+    policy: RetryPolicy,
+    client: RefCell<Option<Client>>,
+    idle_timeout: Cell<Millis>,
+    /// Connect is in progress
+    connecting: Cell<bool>,
+    /// Callers waiting for in-flight connect
+    waiters: RefCell<Vec<oneshot::Sender<Result<Client, CommandError>>>>,
+}
+
+impl<A, T> Clone for RetryClient<A, T> {
+    fn clone(&self) -> Self {
+        RetryClient {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A, T> RetryClient<A, T>
+where
+    A: Address + Clone,
+    T: Service<Connect<A>, Error = connect::ConnectError>,
+    IoBoxed: From<T::Response>,
+{
+    /// Create new retry client
+    pub fn new(connector: RedisConnector<A, T>, policy: RetryPolicy) -> Self {
+        RetryClient {
+            inner: Rc::new(Inner {
+                connector,
+                policy,
+                client: RefCell::new(None),
+                idle_timeout: Cell::new(Millis::ZERO),
+                connecting: Cell::new(false),
+                waiters: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Set idle timeout for connection.
+    ///
+    /// Connection that is not used longer than timeout is closed and
+    /// new connection is established for next command. Zero value
+    /// disables timeout, it is disabled by default.
+    ///
+    /// Crate has no connection pool, idle time is checked when `RetryClient`
+    /// takes its connection for a command. Use `Client::idle_time()` to
+    /// implement same check for custom pools.
+    pub fn idle_timeout(self, timeout: Millis) -> Self {
+        self.inner.idle_timeout.set(timeout);
+        self
+    }
+
+    /// Execute redis command without retries
+    pub async fn exec<U>(&self, cmd: U) -> Result<U::Output, CommandError>
+    where
+        U: Command,
+    {
+        self.attempt::<U>(cmd.to_request()).await
+    }
+
+    /// Execute idempotent redis command
+    ///
+    /// Command is re-executed on transient errors (lost or closed connection
+    /// or attempt timeout) according to retry policy. Protocol parse errors
+    /// are not retried.
+    pub async fn exec_idempotent<U>(&self, cmd: U) -> Result<U::Output, CommandError>
+    where
+        U: Command,
+    {
+        let policy = &self.inner.policy;
+        let req = cmd.to_request();
+        let mut backoff = policy.backoff;
+        let mut attempt = 1;
+
+        loop {
+            match self.attempt::<U>(req.clone()).await {
+                Err(err) if attempt < policy.max_attempts && is_transient(&err) => {
+                    log::trace!("Redis command failed, retrying: {:?}", err);
+                    sleep(backoff).await;
+                    backoff = policy.next_backoff(backoff);
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Returns true if underlying transport is connected to redis
+    pub fn is_connected(&self) -> bool {
+        self.inner
+            .client
+            .borrow()
+            .as_ref()
+            .map(|c| c.is_connected())
+            .unwrap_or(false)
+    }
+
+    async fn attempt<U>(&self, req: Request) -> Result<U::Output, CommandError>
+    where
+        U: Command,
+    {
+        let client = self.client().await?;
+        let timeout_ms = self.inner.policy.timeout;
+
+        if timeout_ms.is_zero() {
+            client.exec_request::<U>(req).await
+        } else {
+            timeout(timeout_ms, client.exec_request::<U>(req))
+                .await
+                .unwrap_or(Err(CommandError::Timeout))
+        }
+    }
+
+    /// Get connected client, concurrent callers share one connect attempt
+    async fn client(&self) -> Result<Client, CommandError> {
+        loop {
+            if let Some(client) = self.connected() {
+                return Ok(client);
+            }
+            if !self.inner.connecting.get() {
+                break;
+            }
+            let (tx, rx) = oneshot::channel();
+            self.inner.waiters.borrow_mut().push(tx);
+            if let Ok(res) = rx.await {
+                return res;
+            }
+            // connecting caller is dropped, try again
+        }
+
+        self.inner.connecting.set(true);
+        let _guard = ConnectGuard(&self.inner.connecting, &self.inner.waiters);
+
+        let res = self.inner.connector.connect().await.map_err(connect_error);
+        if let Ok(ref client) = res {
+            *self.inner.client.borrow_mut() = Some(client.clone());
+        }
+        for tx in self.inner.waiters.borrow_mut().drain(..) {
+            let _ = tx.send(res.clone());
+        }
+        res
+    }
+
+    /// Current client if it is connected and is not idle for too long
+    fn connected(&self) -> Option<Client> {
+        let client = self.inner.client.borrow();
+        let client = client.as_ref().filter(|client| client.is_connected())?;
+
+        let timeout = self.inner.idle_timeout.get();
+        if timeout.is_zero() || !client.is_idle() || client.idle_time() < Duration::from(timeout) {
+            Some(client.clone())
+        } else {
+            log::trace!("Redis connection is idle for too long, reconnecting");
+            client.close();
+            None
+        }
+    }
+}
+
+/// Reset connecting state, even if connect is cancelled
+///
+/// Waiters of cancelled connect are woken up and try to connect again.
+struct ConnectGuard<'a>(
+    &'a Cell<bool>,
+    &'a RefCell<Vec<oneshot::Sender<Result<Client, CommandError>>>>,
+);
+
+impl<'a> Drop for ConnectGuard<'a> {
+    fn drop(&mut self) {
+        self.0.set(false);
+        self.1.borrow_mut().clear();
+    }
+}
+
+/// Convert connect error to command error
+pub(crate) fn connect_error(err: ConnectError) -> CommandError {
+    match err {
+        ConnectError::Command(err) => err,
+        err @ (ConnectError::Unauthorized | ConnectError::Url(_)) => {
+            CommandError::Connect(Box::new(err))
+        }
+        ConnectError::Connect(err) => CommandError::Protocol(Error::PeerGone(Some(
+            io::Error::new(io::ErrorKind::NotConnected, err.to_string()),
+        ))),
+    }
+}
+
+/// Parse errors fail the same way on every attempt, they are not retried
+fn is_transient(err: &CommandError) -> bool {
+    matches!(
+        err,
+        CommandError::Protocol(Error::PeerGone(_)) | CommandError::NotSent | CommandError::Timeout
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use ntex::testing::IoTest;
+
+    use super::*;
+    use crate::cmd;
+    use crate::connector::tests::{test_io, TestConnector};
+
+    /// Wait for request and write response
+    async fn respond(server: &IoTest, response: &'static [u8]) {
+        loop {
+            if !server.read_any().is_empty() {
+                server.write(response);
+                break;
+            }
+            ntex::time::sleep(Millis(5)).await;
+        }
+        ntex::time::sleep(Millis(100)).await;
+    }
+
+    fn retry_client(
+        failures: usize,
+        io: Vec<IoTest>,
+        policy: RetryPolicy,
+    ) -> (RetryClient<&'static str, TestConnector>, Rc<Cell<usize>>) {
+        let (connector, attempts) = TestConnector::new(failures, io);
+        let connector = RedisConnector::new("127.0.0.1:6379").connector(connector);
+        (RetryClient::new(connector, policy), attempts)
+    }
+
+    #[ntex::test]
+    async fn test_unauthorized() {
+        let (client, server) = test_io();
+        ntex::rt::spawn(async move {
+            respond(&server, b"+FAIL\r\n").await;
+        });
+
+        let (connector, attempts) = TestConnector::new(0, vec![client]);
+        let connector = RedisConnector::new("127.0.0.1:6379")
+            .connector(connector)
+            .password("secret");
+        let policy = RetryPolicy::new().backoff(Millis(1), Millis(10));
+        let redis = RetryClient::new(connector, policy);
+
+        // auth failure is not transient and is not a server error reply
+        match redis.exec_idempotent(cmd::Get("key")).await {
+            Err(CommandError::Connect(err)) => {
+                assert!(matches!(*err, ConnectError::Unauthorized))
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_exec_idempotent_retry() {
+        let (client, server) = test_io();
+        ntex::rt::spawn(async move {
+            respond(&server, b"$5\r\nvalue\r\n").await;
+        });
+
+        let policy = RetryPolicy::new().backoff(Millis(1), Millis(10));
+        let (redis, attempts) = retry_client(1, vec![client], policy);
+
+        let result = redis.exec_idempotent(cmd::Get("key")).await.unwrap();
+        assert_eq!(result, Some("value".into()));
+        assert_eq!(attempts.get(), 2);
+        assert!(redis.is_connected());
+    }
+
+    #[ntex::test]
+    async fn test_exec_idempotent_peer_gone() {
+        let (client1, server1) = test_io();
+        server1.close().await;
+
+        let (client2, server2) = test_io();
+        ntex::rt::spawn(async move {
+            respond(&server2, b"+PONG\r\n").await;
+        });
+
+        let policy = RetryPolicy::new().backoff(Millis(1), Millis(10));
+        let (redis, attempts) = retry_client(0, vec![client1, client2], policy);
+
+        let result = redis.exec_idempotent(cmd::Ping()).await.unwrap();
+        assert_eq!(result, "PONG");
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[ntex::test]
+    async fn test_idle_timeout() {
+        let (client1, server1) = test_io();
+        let (client2, server2) = test_io();
+
+        let (redis, attempts) = retry_client(0, vec![client1, client2], RetryPolicy::new());
+        let redis = redis.idle_timeout(Millis(50));
+
+        // dropped IoTest closes connection, servers are shared instead of cloned
+        let server1 = Rc::new(server1);
+        let server2 = Rc::new(server2);
+
+        let server = server1.clone();
+        ntex::rt::spawn(async move {
+            respond(&server, b"+PONG\r\n").await;
+        });
+        assert_eq!(redis.exec(cmd::Ping()).await.unwrap(), "PONG");
+        assert_eq!(attempts.get(), 1);
+
+        // connection is still fresh
+        let server = server1.clone();
+        ntex::rt::spawn(async move {
+            respond(&server, b"+PONG\r\n").await;
+        });
+        assert_eq!(redis.exec(cmd::Ping()).await.unwrap(), "PONG");
+        assert_eq!(attempts.get(), 1);
+
+        // idle connection stays open until next command
+        ntex::time::sleep(Millis(200)).await;
+        assert!(!server1.is_closed());
+
+        // idle connection is closed and replaced
+        let server = server2.clone();
+        ntex::rt::spawn(async move {
+            respond(&server, b"+PONG\r\n").await;
+        });
+        assert_eq!(redis.exec(cmd::Ping()).await.unwrap(), "PONG");
+        assert_eq!(attempts.get(), 2);
+        assert!(server1.is_closed());
+        assert!(!server2.is_closed());
+    }
+
+    #[ntex::test]
+    async fn test_parse_error_no_retry() {
+        let (client, server) = test_io();
+        ntex::rt::spawn(async move {
+            respond(&server, b"@malformed\r\n").await;
+        });
+
+        let policy = RetryPolicy::new().backoff(Millis(1), Millis(10));
+        let (redis, attempts) = retry_client(0, vec![client], policy);
+
+        let result = redis.exec_idempotent(cmd::Get("key")).await;
+        assert!(matches!(
+            result,
+            Err(CommandError::Protocol(Error::Parse(_)))
+        ));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_concurrent_connect() {
+        let (client, server) = test_io();
+        ntex::rt::spawn(async move {
+            // connect is suspended until AUTH response
+            respond(&server, b"+OK\r\n").await;
+
+            let mut data = Vec::new();
+            while data.windows(3).filter(|w| w == b"GET").count() < 2 {
+                data.extend_from_slice(&server.read().await.unwrap());
+            }
+            server.write(b"$1\r\na\r\n$1\r\nb\r\n");
+        });
+
+        let (connector, attempts) = TestConnector::new(0, vec![client]);
+        let connector = RedisConnector::new("127.0.0.1:6379")
+            .connector(connector)
+            .password("secret");
+        let redis = RetryClient::new(connector, RetryPolicy::new());
+
+        let (a, b) = timeout(
+            Millis(1_000),
+            ntex::util::join(redis.exec(cmd::Get("a")), redis.exec(cmd::Get("b"))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(a.unwrap(), Some("a".into()));
+        assert_eq!(b.unwrap(), Some("b".into()));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_exec_no_retry() {
+        let policy = RetryPolicy::new().backoff(Millis(1), Millis(10));
+        let (redis, attempts) = retry_client(1, vec![], policy);
+
+        let result = redis.exec(cmd::Get("key")).await;
+        assert!(matches!(result, Err(CommandError::Protocol(_))));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_max_attempts() {
+        let policy = RetryPolicy::new()
+            .max_attempts(2)
+            .backoff(Millis(1), Millis(10));
+        let (redis, attempts) = retry_client(5, vec![], policy);
+
+        let result = redis.exec_idempotent(cmd::Get("key")).await;
+        assert!(matches!(result, Err(CommandError::Protocol(_))));
+        assert_eq!(attempts.get(), 2);
+    }
+}
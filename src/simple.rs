@@ -1,20 +1,35 @@
-use std::pin::Pin;
-use std::{future::poll_fn, task::Context, task::Poll};
+use std::{cell::Cell, cell::RefCell, collections::HashSet, collections::VecDeque};
+use std::{future::poll_fn, future::Future, pin::Pin, rc::Rc, task::Context, task::Poll};
 
 use super::cmd::{commands::PubSubCommand, commands::SubscribeOutputCommand, Command};
-use super::codec::Codec;
+use super::cmd::{Hello, PUnSubscribe, Reset, SUnSubscribe, SubscribeItem, UnSubscribe};
+use super::codec::{Codec, Protocol, Request, Response};
 use super::errors::{CommandError, Error};
-use ntex::{io::IoBoxed, io::RecvError, util::ready, util::Stream};
+use ntex::service::{Service, ServiceCtx};
+use ntex::util::{ready, Bytes, Stream};
+use ntex::{channel::mpsc, io::IoBoxed, io::RecvError, task::LocalWaker, time::Seconds};
 
 /// Redis client
 pub struct SimpleClient {
     io: IoBoxed,
+    codec: Codec,
+    /// Service call is in progress
+    busy: Cell<bool>,
+    waker: LocalWaker,
+    /// Number of late responses of timed out commands
+    stale: Cell<usize>,
 }
 
 impl SimpleClient {
     /// Create new simple client
-    pub(crate) fn new(io: IoBoxed) -> Self {
-        SimpleClient { io }
+    pub(crate) fn new(io: IoBoxed, codec: Codec) -> Self {
+        SimpleClient {
+            io,
+            codec,
+            busy: Cell::new(false),
+            waker: LocalWaker::new(),
+            stale: Cell::new(0),
+        }
     }
 
     /// Execute redis command and wait result
@@ -23,20 +38,105 @@ impl SimpleClient {
         U: Command,
     {
         self.send(cmd)?;
-        loop {
-            if let Some(result) = self.recv::<U>().await {
-                return result;
+        self.recv::<U>().await
+    }
+
+    /// Switch connection protocol with `HELLO` command.
+    ///
+    /// Codec protocol is updated for all subsequent responses.
+    pub async fn hello(&mut self, protocol: Protocol) -> Result<(), CommandError> {
+        let prev = self.codec.protocol();
+
+        // RESP3 decoder is able to parse RESP2 responses
+        self.codec.set_protocol(Protocol::Resp3);
+        let res = self.exec(Hello(protocol)).await;
+        self.codec
+            .set_protocol(if res.is_ok() { protocol } else { prev });
+        res
+    }
+
+    /// Returns current protocol version
+    pub fn protocol(&self) -> Protocol {
+        self.codec.protocol()
+    }
+
+    /// Execute redis command and wait result with timeout
+    ///
+    /// Returns `CommandError::Timeout` if response is not received in time.
+    /// Late response is skipped, so client could be used for next commands.
+    pub async fn exec_timeout<U>(
+        &self,
+        cmd: U,
+        timeout: Seconds,
+    ) -> Result<U::Output, CommandError>
+    where
+        U: Command,
+    {
+        match ntex::time::timeout(timeout, self.exec(cmd)).await {
+            Ok(res) => res,
+            Err(_) => {
+                // late response must not be delivered to next command
+                self.stale.set(self.stale.get() + 1);
+                Err(CommandError::Timeout)
             }
         }
     }
 
+    /// Execute batch of redis commands and wait results
+    ///
+    /// All commands are sent first, then responses are read in order.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+    ///
+    ///     let results = redis.exec_all(vec![
+    ///         cmd::Set("key1", "1"),
+    ///         cmd::Set("key2", "2"),
+    ///     ]).await?;
+    ///
+    ///     assert_eq!(results.len(), 2);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn exec_all<U>(
+        &self,
+        cmds: impl IntoIterator<Item = U>,
+    ) -> Result<Vec<Result<U::Output, CommandError>>, CommandError>
+    where
+        U: Command,
+    {
+        let mut count = 0;
+        for cmd in cmds {
+            self.send(cmd)?;
+            count += 1;
+        }
+
+        let mut results = Vec::with_capacity(count);
+        for _ in 0..count {
+            results.push(self.recv::<U>().await);
+        }
+        Ok(results)
+    }
+
     /// Send redis command
     pub fn send<U>(&self, cmd: U) -> Result<(), CommandError>
     where
         U: Command,
     {
-        self.io.encode(cmd.to_request(), &Codec)?;
-        Ok(())
+        self.send_request(cmd.to_request())
+    }
+
+    pub(crate) fn send_request(&self, req: Request) -> Result<(), CommandError> {
+        if self.io.is_closed() {
+            Err(CommandError::NotSent)
+        } else {
+            self.io.encode(req, &self.codec)?;
+            Ok(())
+        }
     }
 
     /// Execute redis SUBSCRIBE command and act with output as stream
@@ -55,39 +155,92 @@ impl SimpleClient {
         self.io
     }
 
-    async fn recv<U: Command>(&self) -> Option<Result<U::Output, CommandError>> {
+    /// Receive next response
+    ///
+    /// Closed connection is reported as `CommandError::Protocol` error.
+    pub(crate) async fn recv<U: Command>(&self) -> Result<U::Output, CommandError> {
         poll_fn(|cx| self.poll_recv::<U>(cx)).await
     }
 
     fn poll_recv<U: Command>(
         &self,
         cx: &mut Context<'_>,
-    ) -> Poll<Option<Result<U::Output, CommandError>>> {
-        match ready!(self.io.poll_recv(&Codec, cx)) {
+    ) -> Poll<Result<U::Output, CommandError>> {
+        match ready!(self.poll_recv_response(cx)) {
             Ok(item) => match item.into_result() {
-                Ok(result) => Poll::Ready(Some(U::to_output(result))),
-                Err(err) => Poll::Ready(Some(Err(CommandError::Error(err)))),
+                Ok(result) => Poll::Ready(U::to_output(result)),
+                Err(err) => Poll::Ready(Err(U::to_error(err))),
             },
-            Err(RecvError::KeepAlive) | Err(RecvError::Stop) => {
-                unreachable!()
-            }
-            Err(RecvError::WriteBackpressure) => {
-                if let Err(err) = ready!(self.io.poll_flush(cx, false))
-                    .map_err(|e| CommandError::Protocol(Error::PeerGone(Some(e))))
-                {
-                    Poll::Ready(Some(Err(err)))
-                } else {
-                    Poll::Pending
+            Err(err) => Poll::Ready(Err(CommandError::Protocol(err))),
+        }
+    }
+
+    /// Receive next response, closed connection is reported as `Error::PeerGone`
+    fn poll_recv_response(&self, cx: &mut Context<'_>) -> Poll<Result<Response, Error>> {
+        loop {
+            return match ready!(self.io.poll_recv(&self.codec, cx)) {
+                Ok(item) => {
+                    let stale = self.stale.get();
+                    if stale > 0 {
+                        log::trace!("Skip late response of timed out command: {:?}", item);
+                        self.stale.set(stale - 1);
+                        continue;
+                    }
+                    Poll::Ready(Ok(item))
                 }
-            }
-            Err(RecvError::Decoder(err)) => Poll::Ready(Some(Err(CommandError::Protocol(err)))),
-            Err(RecvError::PeerGone(err)) => {
-                Poll::Ready(Some(Err(CommandError::Protocol(Error::PeerGone(err)))))
-            }
+                Err(RecvError::KeepAlive) | Err(RecvError::Stop) => {
+                    unreachable!()
+                }
+                Err(RecvError::WriteBackpressure) => {
+                    if let Err(err) = ready!(self.io.poll_flush(cx, false)) {
+                        Poll::Ready(Err(Error::PeerGone(Some(err))))
+                    } else {
+                        Poll::Pending
+                    }
+                }
+                Err(RecvError::Decoder(err)) => Poll::Ready(Err(err)),
+                Err(RecvError::PeerGone(err)) => Poll::Ready(Err(Error::PeerGone(err))),
+            };
         }
     }
 }
 
+/// Service calls are executed one by one, next call is ready
+/// only after previous response is received.
+impl Service<Request> for SimpleClient {
+    type Response = Response;
+    type Error = Error;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.io.is_closed() {
+            Poll::Ready(Err(Error::PeerGone(None)))
+        } else if self.busy.get() {
+            self.waker.register(cx.waker());
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    async fn call(&self, req: Request, _: ServiceCtx<'_, Self>) -> Result<Response, Error> {
+        self.io.encode(req, &self.codec)?;
+
+        self.busy.set(true);
+        let _guard = BusyGuard(self);
+        poll_fn(|cx| self.poll_recv_response(cx)).await
+    }
+}
+
+/// Release client for next service call, even if call is cancelled
+struct BusyGuard<'a>(&'a SimpleClient);
+
+impl<'a> Drop for BusyGuard<'a> {
+    fn drop(&mut self) {
+        self.0.busy.set(false);
+        self.0.waker.wake();
+    }
+}
+
 /// Redis pubsub client to receive push messages
 pub struct SubscriptionClient<U: Command + PubSubCommand> {
     client: SimpleClient,
@@ -130,23 +283,706 @@ impl<U: Command + PubSubCommand> SubscriptionClient<U> {
         self.client
     }
 
+    /// Reset connection and get client back.
+    ///
+    /// Sends `RESET` command and drops all pending subscription messages
+    /// until `RESET` response is received.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+    ///
+    ///     let subscriber = redis.subscribe(cmd::Subscribe(["test"]))?;
+    ///     // do some work
+    ///
+    ///     // go back to normal client
+    ///     let redis = subscriber.into_client_reset().await?;
+    ///     redis.exec(cmd::Get("test")).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn into_client_reset(self) -> Result<SimpleClient, CommandError> {
+        self.client.send(Reset())?;
+        loop {
+            match poll_fn(|cx| self.client.poll_recv_response(cx)).await {
+                Ok(Response::String(s)) if s == "RESET" => return Ok(self.client),
+                Ok(Response::Error(err)) => return Err(err.into()),
+                // pending subscription message
+                Ok(_) => continue,
+                Err(err) => return Err(CommandError::Protocol(err)),
+            }
+        }
+    }
+
+    /// Unsubscribe from all channels, patterns and shard channels and
+    /// close connection.
+    ///
+    /// Sends `UNSUBSCRIBE`, `PUNSUBSCRIBE` and `SUNSUBSCRIBE` commands, drops
+    /// pending subscription messages until all confirmations are received and
+    /// then gracefully shuts down connection. Returns unsubscribed channels,
+    /// patterns and shard channels.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+    ///
+    ///     let subscriber = redis.subscribe(cmd::Subscribe(["test"]))?;
+    ///     // do some work
+    ///
+    ///     let channels = subscriber.close().await?;
+    ///     assert_eq!(channels, vec!["test"]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn close(self) -> Result<Vec<Bytes>, CommandError> {
+        self.client.send(UnSubscribe::<&str>(None))?;
+        self.client.send(PUnSubscribe::<&str>(None))?;
+        self.client.send(SUnSubscribe::<&str>(None))?;
+
+        // commands are processed in order, every command is confirmed
+        // by at least one reply
+        let mut confirmed = (false, false);
+        let mut channels = Vec::new();
+        loop {
+            match poll_fn(|cx| self.client.poll_recv_response(cx)).await {
+                Ok(Response::Array(ary) | Response::Push(ary)) => match &ary[..] {
+                    [Response::Bytes(mtype), channel, Response::Integer(count)]
+                        if &mtype[..] == b"unsubscribe"
+                            || &mtype[..] == b"punsubscribe"
+                            || &mtype[..] == b"sunsubscribe" =>
+                    {
+                        if let Response::Bytes(channel) = channel {
+                            channels.push(channel.clone());
+                        }
+                        match &mtype[..] {
+                            b"unsubscribe" => confirmed.0 = true,
+                            b"punsubscribe" => confirmed.1 = true,
+                            // SUNSUBSCRIBE is sent last, its zero count means
+                            // all shard channels are released as well
+                            _ if *count == 0 && confirmed == (true, true) => break,
+                            _ => (),
+                        }
+                    }
+                    // pending subscription message
+                    _ => continue,
+                },
+                // servers without sharded pubsub (redis < 7.0) reject SUNSUBSCRIBE
+                Ok(Response::Error(_)) if confirmed == (true, true) => break,
+                Ok(Response::Error(err)) => return Err(err.into()),
+                Ok(_) => continue,
+                Err(err) => return Err(CommandError::Protocol(err)),
+            }
+        }
+
+        self.client
+            .io
+            .shutdown()
+            .await
+            .map_err(|e| CommandError::Protocol(Error::PeerGone(Some(e))))?;
+        Ok(channels)
+    }
+
     /// Send redis subscribe/unsubscribe command
     pub fn send<T: Command + PubSubCommand>(&self, cmd: T) -> Result<(), CommandError> {
         self.client.send(cmd)
     }
 
     /// Attempt to pull out the next value of this stream.
+    ///
+    /// Closed connection is reported as `CommandError::Protocol` error,
+    /// `None` is never returned.
     pub async fn recv(&self) -> Option<Result<U::Output, CommandError>> {
-        poll_fn(|cx| self.client.poll_recv::<U>(cx)).await
+        Some(poll_fn(|cx| self.client.poll_recv::<U>(cx)).await)
     }
 
     /// Attempt to pull out the next value of this stream, registering
-    /// the current task for wakeup if the value is not yet available,
-    /// and returning None if the payload is exhausted.
+    /// the current task for wakeup if the value is not yet available.
+    ///
+    /// Closed connection is reported as `CommandError::Protocol` error,
+    /// `None` is never returned.
     pub fn poll_recv(
         &self,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<U::Output, CommandError>>> {
-        self.client.poll_recv::<U>(cx)
+        self.client.poll_recv::<U>(cx).map(Some)
+    }
+}
+
+impl SubscriptionClient<SubscribeOutputCommand> {
+    /// Wait for `n` subscription confirmations.
+    ///
+    /// Returns set of confirmed channels. Messages received before
+    /// all confirmations are arrived get dropped.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+    ///
+    ///     let subscriber = redis.subscribe(cmd::Subscribe(["test1", "test2"]))?;
+    ///     let channels = subscriber.wait_subscribed(2).await?;
+    ///     assert_eq!(channels.len(), 2);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn wait_subscribed(&self, n: usize) -> Result<HashSet<Bytes>, CommandError> {
+        let mut channels = HashSet::with_capacity(n);
+        while channels.len() < n {
+            match self.client.recv::<SubscribeOutputCommand>().await? {
+                SubscribeItem::Subscribed { channel, .. } => {
+                    channels.insert(channel);
+                }
+                _ => continue,
+            }
+        }
+        Ok(channels)
+    }
+
+    /// Call `f` for every received message.
+    ///
+    /// Callback is invoked for `Message` and `IntMessage` items, subscription
+    /// confirmations are skipped. Next message is not received until callback's
+    /// future completes. Returns on the first error, lost connection is
+    /// reported as `CommandError::Protocol` error.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, cmd::SubscribeItem, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+    ///
+    ///     let subscriber = redis.subscribe(cmd::Subscribe(["test"]))?;
+    ///     subscriber
+    ///         .for_each(|item| async move {
+    ///             if let SubscribeItem::Message { channel, payload, .. } = item {
+    ///                 println!("{:?}: {:?}", channel, payload);
+    ///             }
+    ///         })
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn for_each<F, R>(&self, mut f: F) -> Result<(), CommandError>
+    where
+        F: FnMut(SubscribeItem) -> R,
+        R: Future<Output = ()>,
+    {
+        loop {
+            match self.client.recv::<SubscribeOutputCommand>().await? {
+                item @ SubscribeItem::Message { .. } | item @ SubscribeItem::IntMessage { .. } => {
+                    f(item).await
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Convert subscription client to a broadcast handle.
+    ///
+    /// Every subscription message is delivered to all consumers created with
+    /// `SubscriptionBroadcast::consumer()` or `SubscriptionBroadcast::bounded_consumer()`.
+    /// Subscribe and unsubscribe confirmations
+    /// and errors are delivered to the returned control receiver.
+    ///
+    /// ```rust
+    /// use ntex_redis::{cmd, RedisConnector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let redis = RedisConnector::new("127.0.0.1:6379").connect_simple().await?;
+    ///
+    ///     let (broadcast, control) = redis.subscribe(cmd::Subscribe(["test"]))?.broadcast();
+    ///     let consumer1 = broadcast.consumer();
+    ///     let consumer2 = broadcast.consumer();
+    ///
+    ///     // wait for subscription confirmation
+    ///     control.recv().await.unwrap()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn broadcast(
+        self,
+    ) -> (
+        SubscriptionBroadcast,
+        mpsc::Receiver<Result<SubscribeItem, CommandError>>,
+    ) {
+        let (control_tx, control_rx) = mpsc::channel();
+        let inner = Rc::new(BroadcastInner {
+            client: self.client,
+            consumers: RefCell::new(Vec::new()),
+            stopped: Cell::new(false),
+            waker: LocalWaker::new(),
+        });
+
+        let inner2 = inner.clone();
+        ntex::rt::spawn(async move {
+            loop {
+                let item = poll_fn(|cx| {
+                    // all handles are dropped
+                    if inner2.stopped.get() {
+                        return Poll::Ready(None);
+                    }
+                    inner2.waker.register(cx.waker());
+                    inner2
+                        .client
+                        .poll_recv::<SubscribeOutputCommand>(cx)
+                        .map(Some)
+                })
+                .await;
+
+                match item {
+                    Some(Ok(
+                        item @ (SubscribeItem::Message { .. } | SubscribeItem::IntMessage { .. }),
+                    )) => {
+                        inner2
+                            .consumers
+                            .borrow_mut()
+                            .retain(|consumer| consumer.send(item.clone()));
+                    }
+                    Some(Ok(item)) => {
+                        let _ = control_tx.send(Ok(item));
+                    }
+                    Some(Err(err)) => {
+                        let closed = matches!(err, CommandError::Protocol(_));
+                        let _ = control_tx.send(Err(err));
+                        if closed {
+                            break;
+                        }
+                    }
+                    // all handles are dropped
+                    None => break,
+                }
+            }
+            for consumer in inner2.consumers.borrow_mut().drain(..) {
+                consumer.close();
+            }
+        });
+
+        (
+            SubscriptionBroadcast(Rc::new(BroadcastHandle(inner))),
+            control_rx,
+        )
+    }
+}
+
+#[derive(Clone)]
+/// Cloneable handle delivering subscription messages to multiple consumers
+///
+/// Subscription connection is closed when all handles are dropped.
+pub struct SubscriptionBroadcast(Rc<BroadcastHandle>);
+
+/// Stops broadcast task when last handle is dropped
+struct BroadcastHandle(Rc<BroadcastInner>);
+
+struct BroadcastInner {
+    client: SimpleClient,
+    consumers: RefCell<Vec<Consumer>>,
+    stopped: Cell<bool>,
+    waker: LocalWaker,
+}
+
+impl Drop for BroadcastHandle {
+    fn drop(&mut self) {
+        self.0.stopped.set(true);
+        self.0.waker.wake();
+    }
+}
+
+enum Consumer {
+    Unbounded(mpsc::Sender<SubscribeItem>),
+    Bounded(Rc<BoundedInner>),
+}
+
+impl Consumer {
+    /// Deliver item to consumer, returns false if consumer is dropped
+    fn send(&self, item: SubscribeItem) -> bool {
+        match self {
+            Consumer::Unbounded(tx) => tx.send(item).is_ok(),
+            Consumer::Bounded(inner) => {
+                if Rc::strong_count(inner) == 1 {
+                    return false;
+                }
+                let mut queue = inner.queue.borrow_mut();
+                if queue.len() >= inner.capacity {
+                    queue.pop_front();
+                    inner.dropped.set(inner.dropped.get() + 1);
+                }
+                queue.push_back(item);
+                inner.waker.wake();
+                true
+            }
+        }
+    }
+
+    fn close(self) {
+        if let Consumer::Bounded(inner) = self {
+            inner.closed.set(true);
+            inner.waker.wake();
+        }
+    }
+}
+
+impl SubscriptionBroadcast {
+    /// Create new consumer of subscription messages
+    ///
+    /// Consumer's queue is unbounded, slow consumer accumulates
+    /// all undelivered messages.
+    pub fn consumer(&self) -> mpsc::Receiver<SubscribeItem> {
+        let (tx, rx) = mpsc::channel();
+        self.0
+             .0
+            .consumers
+            .borrow_mut()
+            .push(Consumer::Unbounded(tx));
+        rx
+    }
+
+    /// Create new consumer of subscription messages with bounded queue
+    ///
+    /// Consumer keeps at most `capacity` undelivered messages. Broadcast
+    /// never waits for slow consumer, if queue is full the oldest message
+    /// is dropped and counted in `BoundedConsumer::dropped_count()`.
+    pub fn bounded_consumer(&self, capacity: usize) -> BoundedConsumer {
+        let capacity = capacity.max(1);
+        let inner = Rc::new(BoundedInner {
+            capacity,
+            queue: RefCell::new(VecDeque::with_capacity(capacity)),
+            dropped: Cell::new(0),
+            closed: Cell::new(false),
+            waker: LocalWaker::new(),
+        });
+        self.0
+             .0
+            .consumers
+            .borrow_mut()
+            .push(Consumer::Bounded(inner.clone()));
+        BoundedConsumer(inner)
+    }
+
+    /// Send redis subscribe/unsubscribe command
+    pub fn send<T: Command + PubSubCommand>(&self, cmd: T) -> Result<(), CommandError> {
+        self.0 .0.client.send(cmd)
+    }
+}
+
+/// Subscription messages consumer with bounded queue
+///
+/// Created by `SubscriptionBroadcast::bounded_consumer()`.
+pub struct BoundedConsumer(Rc<BoundedInner>);
+
+struct BoundedInner {
+    capacity: usize,
+    queue: RefCell<VecDeque<SubscribeItem>>,
+    dropped: Cell<usize>,
+    closed: Cell<bool>,
+    waker: LocalWaker,
+}
+
+impl BoundedConsumer {
+    /// Receive next subscription message
+    ///
+    /// Returns `None` if subscription connection is closed.
+    pub async fn recv(&self) -> Option<SubscribeItem> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempt to pull out the next subscription message
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<SubscribeItem>> {
+        if let Some(item) = self.0.queue.borrow_mut().pop_front() {
+            Poll::Ready(Some(item))
+        } else if self.0.closed.get() {
+            Poll::Ready(None)
+        } else {
+            self.0.waker.register(cx.waker());
+            Poll::Pending
+        }
+    }
+
+    /// Number of messages dropped because consumer's queue was full
+    pub fn dropped_count(&self) -> usize {
+        self.0.dropped.get()
+    }
+}
+
+impl Stream for BoundedConsumer {
+    type Item = SubscribeItem;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SubscribeItem>> {
+        self.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::{io::Io, testing::IoTest};
+
+    use super::*;
+    use crate::cmd;
+
+    /// Create simple client connected to test io
+    fn test_client(codec: Codec) -> (SimpleClient, IoTest) {
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1 << 20);
+        server.remote_buffer_cap(1 << 20);
+        (
+            SimpleClient::new(IoBoxed::from(Io::new(client)), codec),
+            server,
+        )
+    }
+
+    #[ntex::test]
+    async fn test_subscription_close() {
+        let (redis, server) = test_client(Codec::new());
+        let subscriber = redis.subscribe(cmd::Subscribe(["test1", "test2"])).unwrap();
+        server.read().await.unwrap();
+        server.write(
+            "*3\r\n$9\r\nsubscribe\r\n$5\r\ntest1\r\n:1\r\n\
+             *3\r\n$9\r\nsubscribe\r\n$5\r\ntest2\r\n:2\r\n",
+        );
+        assert_eq!(subscriber.wait_subscribed(2).await.unwrap().len(), 2);
+
+        ntex::rt::spawn(async move {
+            let req = server.read().await.unwrap();
+            assert!(req.ends_with(b"UNSUBSCRIBE\r\n"));
+            server.write(
+                "*3\r\n$7\r\nmessage\r\n$5\r\ntest1\r\n$1\r\n1\r\n\
+                 *3\r\n$11\r\nunsubscribe\r\n$5\r\ntest1\r\n:1\r\n\
+                 *3\r\n$11\r\nunsubscribe\r\n$5\r\ntest2\r\n:0\r\n\
+                 *3\r\n$12\r\npunsubscribe\r\n$-1\r\n:0\r\n\
+                 *3\r\n$12\r\nsunsubscribe\r\n$-1\r\n:0\r\n",
+            );
+        });
+
+        let channels = subscriber.close().await.unwrap();
+        assert_eq!(channels, vec!["test1", "test2"]);
+    }
+
+    #[ntex::test]
+    async fn test_subscription_close_shard() {
+        let (redis, server) = test_client(Codec::new());
+        let subscriber = redis.subscribe(cmd::SSubscribe(["shard"])).unwrap();
+        subscriber.send(cmd::Subscribe(["test"])).unwrap();
+        server.read().await.unwrap();
+        server.write(
+            "*3\r\n$10\r\nssubscribe\r\n$5\r\nshard\r\n:1\r\n\
+             *3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n",
+        );
+        assert_eq!(subscriber.wait_subscribed(2).await.unwrap().len(), 2);
+
+        ntex::rt::spawn(async move {
+            let req = server.read().await.unwrap();
+            assert!(req.ends_with(b"SUNSUBSCRIBE\r\n"));
+            // shard channel is included into counts of regular confirmations
+            server.write(
+                "*3\r\n$11\r\nunsubscribe\r\n$4\r\ntest\r\n:1\r\n\
+                 *3\r\n$12\r\npunsubscribe\r\n$-1\r\n:1\r\n\
+                 *3\r\n$12\r\nsunsubscribe\r\n$5\r\nshard\r\n:0\r\n",
+            );
+        });
+
+        let channels = subscriber.close().await.unwrap();
+        assert_eq!(channels, vec!["test", "shard"]);
+    }
+
+    #[ntex::test]
+    async fn test_subscription_close_no_sharded_pubsub() {
+        let (redis, server) = test_client(Codec::new());
+        let subscriber = redis.subscribe(cmd::Subscribe(["test"])).unwrap();
+        server.read().await.unwrap();
+        server.write("*3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n");
+        assert_eq!(subscriber.wait_subscribed(1).await.unwrap().len(), 1);
+
+        ntex::rt::spawn(async move {
+            server.read().await.unwrap();
+            server.write(
+                "*3\r\n$11\r\nunsubscribe\r\n$4\r\ntest\r\n:0\r\n\
+                 *3\r\n$12\r\npunsubscribe\r\n$-1\r\n:0\r\n\
+                 -ERR unknown command 'SUNSUBSCRIBE'\r\n",
+            );
+        });
+        assert_eq!(subscriber.close().await.unwrap(), vec!["test"]);
+    }
+
+    #[ntex::test]
+    async fn test_subscription_resp3() {
+        let (redis, server) = test_client(Codec::resp3());
+        let subscriber = redis.subscribe(cmd::Subscribe(["test"])).unwrap();
+        server.read().await.unwrap();
+        server.write(
+            ">3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n\
+             >3\r\n$7\r\nmessage\r\n$4\r\ntest\r\n$5\r\nhello\r\n",
+        );
+        assert_eq!(subscriber.wait_subscribed(1).await.unwrap().len(), 1);
+        assert!(matches!(
+            subscriber.recv().await.unwrap().unwrap(),
+            SubscribeItem::Message { channel, payload, .. } if channel == "test" && payload == "hello"
+        ));
+
+        ntex::rt::spawn(async move {
+            server.read().await.unwrap();
+            server.write(
+                ">3\r\n$11\r\nunsubscribe\r\n$4\r\ntest\r\n:0\r\n\
+                 >3\r\n$12\r\npunsubscribe\r\n_\r\n:0\r\n\
+                 >3\r\n$12\r\nsunsubscribe\r\n_\r\n:0\r\n",
+            );
+        });
+        assert_eq!(subscriber.close().await.unwrap(), vec!["test"]);
+    }
+
+    #[ntex::test]
+    async fn test_subscription_for_each() {
+        let (redis, server) = test_client(Codec::new());
+        let subscriber = redis.subscribe(cmd::Subscribe(["test"])).unwrap();
+        server.read().await.unwrap();
+        server.write(
+            "*3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n\
+             *3\r\n$7\r\nmessage\r\n$4\r\ntest\r\n$1\r\n1\r\n\
+             *3\r\n$7\r\nmessage\r\n$4\r\ntest\r\n$1\r\n2\r\n",
+        );
+        ntex::rt::spawn(async move {
+            ntex::time::sleep(ntex::time::Millis(50)).await;
+            server.close().await;
+        });
+
+        let messages = RefCell::new(Vec::new());
+        let res = subscriber
+            .for_each(|item| {
+                let messages = &messages;
+                async move {
+                    if let SubscribeItem::Message { payload, .. } = item {
+                        messages.borrow_mut().push(payload);
+                    }
+                }
+            })
+            .await;
+        assert!(matches!(res, Err(CommandError::Protocol(_))));
+        assert_eq!(messages.into_inner(), vec!["1", "2"]);
+    }
+
+    #[ntex::test]
+    async fn test_service() {
+        let (redis, server) = test_client(Codec::new());
+        let redis = ntex::service::Pipeline::new(redis);
+
+        let (res, _) = ntex::util::join(redis.call(crate::array!["GET", "key"]), async {
+            let req = server.read().await.unwrap();
+            assert_eq!(req, b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n"[..]);
+            server.write("$5\r\nvalue\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Response::Bytes(Bytes::from_static(b"value")));
+
+        // error response is returned as is
+        let (res, _) = ntex::util::join(redis.call(crate::array!["GET", "key"]), async {
+            server.read().await.unwrap();
+            server.write("-ERR error\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap(), Response::Error("ERR error".into()));
+
+        server.close().await;
+        assert!(redis.call(crate::array!["GET", "key"]).await.is_err());
+    }
+
+    #[ntex::test]
+    async fn test_blocking_command() {
+        let (redis, server) = test_client(Codec::new());
+
+        let cmd = cmd::BLMove("src", "dst", cmd::ListSide::Left, cmd::ListSide::Right, 1.0);
+        let (res, _) = ntex::util::join(redis.exec(cmd), async {
+            let req = server.read().await.unwrap();
+            assert!(req.starts_with(b"*6\r\n$6\r\nBLMOVE\r\n"));
+            // element is pushed by other connection
+            ntex::time::sleep(ntex::time::Millis(50)).await;
+            server.write("$5\r\nvalue\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap().unwrap(), "value");
+    }
+
+    #[ntex::test]
+    async fn test_bounded_consumer() {
+        let (redis, server) = test_client(Codec::new());
+        let (broadcast, control) = redis
+            .subscribe(cmd::Subscribe(["test"]))
+            .unwrap()
+            .broadcast();
+        let consumer = broadcast.bounded_consumer(4);
+        server.read().await.unwrap();
+        server.write("*3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n");
+        assert!(matches!(
+            control.recv().await.unwrap().unwrap(),
+            SubscribeItem::Subscribed { .. }
+        ));
+
+        // fast publisher, consumer does not read
+        for i in 0..100 {
+            let payload = i.to_string();
+            server.write(format!(
+                "*3\r\n$7\r\nmessage\r\n$4\r\ntest\r\n${}\r\n{}\r\n",
+                payload.len(),
+                payload
+            ));
+        }
+        ntex::time::sleep(ntex::time::Millis(100)).await;
+
+        assert_eq!(consumer.dropped_count(), 96);
+        for i in 96..100 {
+            assert!(matches!(
+                consumer.recv().await.unwrap(),
+                SubscribeItem::Message { payload, .. } if payload == i.to_string()
+            ));
+        }
+
+        server.close().await;
+        assert!(consumer.recv().await.is_none());
+    }
+
+    #[ntex::test]
+    async fn test_exec_timeout_late_response() {
+        let (redis, server) = test_client(Codec::new());
+
+        let res = redis.exec_timeout(cmd::Get("key1"), Seconds(1)).await;
+        assert!(matches!(res, Err(CommandError::Timeout)));
+
+        let (res, _) = ntex::util::join(redis.exec(cmd::Get("key2")), async {
+            server.read().await.unwrap();
+            server.write("$6\r\nvalue1\r\n$6\r\nvalue2\r\n");
+        })
+        .await;
+        assert_eq!(res.unwrap().unwrap(), "value2");
+    }
+
+    #[ntex::test]
+    async fn test_broadcast_dropped() {
+        let (redis, server) = test_client(Codec::new());
+        let (broadcast, control) = redis
+            .subscribe(cmd::Subscribe(["test"]))
+            .unwrap()
+            .broadcast();
+        let consumer = broadcast.consumer();
+        let bounded = broadcast.clone().bounded_consumer(4);
+        server.read().await.unwrap();
+        server.write("*3\r\n$9\r\nsubscribe\r\n$4\r\ntest\r\n:1\r\n");
+        assert!(control.recv().await.unwrap().is_ok());
+
+        // no traffic, all handles are dropped
+        drop(broadcast);
+        assert!(consumer.recv().await.is_none());
+        assert!(bounded.recv().await.is_none());
+        assert!(control.recv().await.is_none());
+        ntex::time::sleep(ntex::time::Millis(50)).await;
+        assert!(server.is_client_dropped());
     }
 }
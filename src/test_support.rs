@@ -0,0 +1,57 @@
+//! Helpers for testing code that uses redis
+//!
+//! Module is available with `test-util` feature.
+use ntex::{connect::Address, util::ByteString};
+
+use super::errors::{CommandError, ConnectError};
+use super::{array, cmd, codec::Response, Client, RedisConnector};
+
+/// Generate random key
+pub fn gen_random_key() -> String {
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect()
+}
+
+/// Connect to redis server, select dedicated `db` and flush it
+pub async fn connect_db<A>(address: A, db: u32) -> Result<Client, ConnectError>
+where
+    A: Address + Clone,
+{
+    let client = RedisConnector::new(address).connect().await?;
+    if !client.exec(cmd::Select(db)).await? {
+        return Err(ConnectError::Command(CommandError::Output(
+            "Cannot select database",
+            Response::Nil,
+        )));
+    }
+    client.flushdb().await?;
+    Ok(client)
+}
+
+/// Assert internal encoding of the value stored at `key`
+///
+/// Encoding is checked with `OBJECT ENCODING` command.
+///
+/// # Panics
+///
+/// Panics if key does not exist or encoding does not match `expected`.
+pub async fn assert_encoding(client: &Client, key: &str, expected: &str) {
+    let enc = client
+        .exec(cmd::Raw::<Option<ByteString>>(array![
+            "OBJECT", "ENCODING", key
+        ]))
+        .await
+        .expect("OBJECT ENCODING command failed");
+    assert_eq!(
+        enc.as_deref(),
+        Some(expected),
+        "Unexpected encoding for {:?}",
+        key
+    );
+}
@@ -1,7 +1,11 @@
-use ntex::util::{Bytes, HashMap};
-use ntex_redis::{cmd, Client, RedisConnector};
+use ntex::service::Pipeline;
+use ntex::util::{ByteString, Bytes, HashMap};
+use ntex_redis::{array, cmd, codec, errors::CommandError, Client, RedisConnector};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use std::num::NonZeroU32;
 use std::time::{Duration, SystemTime};
+use std::{future::poll_fn, future::Future, task::Poll};
+use std::{io::Read, io::Write, net::TcpStream, sync::Mutex, sync::MutexGuard};
 
 async fn connect() -> Client {
     RedisConnector::new("127.0.0.1:6379")
@@ -18,6 +22,68 @@ fn new_key() -> String {
         .collect()
 }
 
+/// Serializes tests that depend on server-wide `maxmemory-policy`
+static MAXMEMORY_POLICY: Mutex<()> = Mutex::new(());
+
+fn lock_maxmemory_policy() -> MutexGuard<'static, ()> {
+    MAXMEMORY_POLICY
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// Restores `maxmemory-policy` on drop, even if test fails
+struct MaxMemoryPolicy {
+    policy: String,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl MaxMemoryPolicy {
+    // every test runs on its own thread, lock blocks other tests only
+    #[allow(clippy::await_holding_lock)]
+    async fn set(redis: &Client, policy: &str) -> Self {
+        let lock = lock_maxmemory_policy();
+        let pipeline = Pipeline::new(redis.clone());
+        let res = pipeline
+            .call(array!["CONFIG", "GET", "maxmemory-policy"])
+            .await
+            .unwrap();
+        let orig = match res {
+            codec::Response::Array(items) => match &items[..] {
+                [_, codec::Response::Bytes(policy)] => {
+                    String::from_utf8_lossy(policy).into_owned()
+                }
+                _ => panic!("Unexpected CONFIG GET response: {:?}", items),
+            },
+            res => panic!("Unexpected CONFIG GET response: {:?}", res),
+        };
+        pipeline
+            .call(array!["CONFIG", "SET", "maxmemory-policy", policy])
+            .await
+            .unwrap();
+        MaxMemoryPolicy {
+            policy: orig,
+            _lock: lock,
+        }
+    }
+}
+
+impl Drop for MaxMemoryPolicy {
+    fn drop(&mut self) {
+        // async runtime could be unavailable while panicking, use blocking connection
+        let mut conn = TcpStream::connect("127.0.0.1:6379").unwrap();
+        write!(
+            conn,
+            "*4\r\n$6\r\nCONFIG\r\n$3\r\nSET\r\n$16\r\nmaxmemory-policy\r\n${}\r\n{}\r\n",
+            self.policy.len(),
+            self.policy
+        )
+        .unwrap();
+        let mut buf = [0; 5];
+        conn.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"+OK\r\n", "Cannot restore maxmemory-policy");
+    }
+}
+
 #[ntex::test]
 async fn test_auth() {
     let result = RedisConnector::new("127.0.0.1:6379")
@@ -52,6 +118,114 @@ async fn test_strings() {
 
     let resp = redis.exec(cmd::Get("unknown")).await.unwrap();
     assert_eq!(resp, None);
+
+    let resp = redis
+        .exec(cmd::Set(&key, "2").if_not_exists().status())
+        .await
+        .unwrap();
+    assert_eq!(resp, cmd::SetOutcome::NotPerformed);
+
+    let resp = redis
+        .exec(cmd::Set(&key, "2").if_exists().status())
+        .await
+        .unwrap();
+    assert_eq!(resp, cmd::SetOutcome::Set);
+}
+
+#[ntex::test]
+async fn test_exec_raw() {
+    let redis = connect().await;
+    let key = new_key();
+
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    match redis.exec_raw(cmd::Get(&key)).await.unwrap() {
+        codec::Response::Bytes(val) => assert_eq!(val, "value"),
+        res => panic!("Unexpected response: {:?}", res),
+    }
+    redis.exec(cmd::Del(&key)).await.unwrap();
+
+    let res = redis.exec_raw(cmd::Get(&key)).await.unwrap();
+    assert_eq!(res, codec::Response::Nil);
+}
+
+#[ntex::test]
+async fn test_getset() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let resp = redis.exec(cmd::GetSet(&key, "1")).await.unwrap();
+    assert_eq!(resp, None);
+
+    let resp = redis.exec(cmd::GetSet(&key, "2")).await.unwrap();
+    assert_eq!(resp, Some(Bytes::from_static(b"1")));
+
+    let resp = redis.exec(cmd::Get(&key)).await.unwrap();
+    assert_eq!(resp, Some(Bytes::from_static(b"2")));
+}
+
+#[ntex::test]
+async fn test_msetnx() {
+    let redis = connect().await;
+    let key1 = new_key();
+    let key2 = new_key();
+    let key3 = new_key();
+
+    let resp = redis
+        .exec(cmd::MSetNx([(&key1, "1"), (&key2, "2")]))
+        .await
+        .unwrap();
+    assert!(resp);
+
+    // key2 exists, nothing is set
+    let resp = redis
+        .exec(cmd::MSetNx([(&key2, "3"), (&key3, "3")]))
+        .await
+        .unwrap();
+    assert!(!resp);
+
+    let resp = redis.exec(cmd::Get(&key2)).await.unwrap();
+    assert_eq!(resp, Some(Bytes::from_static(b"2")));
+    let resp = redis.exec(cmd::Get(&key3)).await.unwrap();
+    assert_eq!(resp, None);
+}
+
+#[ntex::test]
+async fn test_bitfield() {
+    use cmd::{BitFieldOverflow, BitFieldType};
+
+    let redis = connect().await;
+    let key = new_key();
+
+    let result = redis
+        .exec(cmd::BitField(&key).set(BitFieldType::U8, 0, 255))
+        .await
+        .unwrap();
+    assert_eq!(result, vec![Some(0)]);
+
+    let result = redis
+        .exec(
+            cmd::BitField(&key)
+                .overflow(BitFieldOverflow::Sat)
+                .incrby(BitFieldType::U8, 0, 10)
+                .get(BitFieldType::U8, 0),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result, vec![Some(255), Some(255)]);
+
+    let result = redis
+        .exec(
+            cmd::BitField(&key)
+                .overflow(BitFieldOverflow::Fail)
+                .incrby(BitFieldType::U8, 0, 1)
+                .overflow(BitFieldOverflow::Wrap)
+                .incrby(BitFieldType::U8, 0, 1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result, vec![None, Some(0)]);
+
+    redis.exec(cmd::Del(&key)).await.unwrap();
 }
 
 #[ntex::test]
@@ -69,6 +243,8 @@ async fn test_keys() {
     assert_eq!(resp, 0);
     let resp = redis.exec(cmd::Exists(&key1).key(&key2)).await.unwrap();
     assert_eq!(resp, 1);
+    assert!(redis.exec(cmd::KeyExists(&key1)).await.unwrap());
+    assert!(!redis.exec(cmd::KeyExists(&key2)).await.unwrap());
 
     let resp = redis.exec(cmd::Ttl(&key1)).await.unwrap();
     assert_eq!(resp, cmd::TtlResult::NoExpire);
@@ -103,221 +279,1385 @@ async fn test_keys() {
 }
 
 #[ntex::test]
-async fn test_strings_simple() {
-    let redis = RedisConnector::new("127.0.0.1:6379")
-        .connect_simple()
-        .await
-        .unwrap();
+async fn test_get_with_ttl() {
+    let redis = connect().await;
     let key = new_key();
 
-    let result = redis.exec(cmd::Set(&key, "value")).await.unwrap();
-    assert!(result);
-
-    let resp = redis.exec(cmd::Get(&key)).await.unwrap().unwrap();
-    assert_eq!(resp, "value");
+    redis
+        .exec(cmd::Set(&key, "value").expire_secs(100))
+        .await
+        .unwrap();
+    let (value, ttl) = redis.get_with_ttl(&key).await.unwrap();
+    assert_eq!(value, Some(Bytes::from_static(b"value")));
+    assert!(matches!(ttl, cmd::TtlResult::Seconds(secs) if secs > 0 && secs <= 100));
 
-    let resp = redis.exec(cmd::Get("unknown")).await.unwrap();
-    assert_eq!(resp, None);
+    redis.exec(cmd::Del(&key)).await.unwrap();
+    let (value, ttl) = redis.get_with_ttl(&key).await.unwrap();
+    assert_eq!(value, None);
+    assert_eq!(ttl, cmd::TtlResult::NotFound);
 }
 
 #[ntex::test]
-async fn test_lists() {
+async fn test_pexpireat() {
     let redis = connect().await;
     let key = new_key();
 
-    let result = redis
-        .exec(cmd::LPush(&key, "l_value").if_exists())
-        .await
-        .unwrap();
-    assert_eq!(result, 0);
-    let result = redis
-        .exec(cmd::RPush(&key, "l_value").if_exists())
-        .await
-        .unwrap();
-    assert_eq!(result, 0);
+    let resp = redis.exec(cmd::PTtl(&key)).await.unwrap();
+    assert_eq!(resp, cmd::PTtlResult::NotFound);
 
-    let result = redis.exec(cmd::LPush(&key, "l_value")).await.unwrap();
-    assert_eq!(result, 1);
-    let result = redis.exec(cmd::RPush(&key, "r_value")).await.unwrap();
-    assert_eq!(result, 2);
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    let resp = redis.exec(cmd::PTtl(&key)).await.unwrap();
+    assert_eq!(resp, cmd::PTtlResult::NoExpire);
 
-    let resp = redis.exec(cmd::LIndex(&key, 0)).await.unwrap().unwrap();
-    assert_eq!(resp, "l_value");
-    let resp = redis.exec(cmd::LIndex(&key, 1)).await.unwrap().unwrap();
-    assert_eq!(resp, "r_value");
+    let expire_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        + Duration::from_millis(5000);
+    let resp = redis
+        .exec(cmd::PExpireAt(&key, expire_at.as_millis() as i64))
+        .await
+        .unwrap();
+    assert!(resp);
 
-    let resp = redis.exec(cmd::LPop(&key)).await.unwrap();
-    assert_eq!(resp.unwrap(), "l_value");
-    let resp = redis.exec(cmd::RPop(&key)).await.unwrap();
-    assert_eq!(resp.unwrap(), "r_value");
-    let resp = redis.exec(cmd::LPop(&key)).await.unwrap();
-    assert_eq!(resp, None);
+    match redis.exec(cmd::PTtl(&key)).await.unwrap() {
+        cmd::PTtlResult::Millis(ms) => assert!(ms > 4000 && ms <= 5000),
+        res => panic!("Unexpected result: {:?}", res),
+    }
 }
 
 #[ntex::test]
-async fn test_hashes() {
+async fn test_scan_type() {
+    use cmd::RedisType;
+
     let redis = connect().await;
-    let key = new_key();
+    let prefix = new_key();
+    let string_key = format!("{}:string", prefix);
+    let list_key = format!("{}:list", prefix);
+    let hash_key = format!("{}:hash", prefix);
 
-    let result = redis
-        .exec(cmd::HSet(&key, "field1", "1").entry("field2", "2"))
+    redis.exec(cmd::Set(&string_key, "value")).await.unwrap();
+    redis.exec(cmd::LPush(&list_key, "value")).await.unwrap();
+    redis
+        .exec(cmd::HSet(&hash_key, "field", "value"))
         .await
         .unwrap();
-    assert_eq!(result, 2);
-
-    let result = redis.exec(cmd::HIncrBy(&key, "field1", 10)).await.unwrap();
-    assert_eq!(result, 11);
 
-    let result = redis.exec(cmd::HGet(&key, "field1")).await.unwrap();
-    assert_eq!(result.unwrap(), "11");
-
-    let result = redis.exec(cmd::HGetAll(&key)).await.unwrap();
-    let mut expected = HashMap::default();
-    expected.insert("field1".into(), "11".into());
-    expected.insert("field2".into(), "2".into());
-    assert_eq!(result, expected);
+    let pattern = format!("{}:*", prefix);
+    for (tp, expected) in [
+        (RedisType::String, &string_key),
+        (RedisType::List, &list_key),
+        (RedisType::Hash, &hash_key),
+    ] {
+        let mut keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, batch) = redis
+                .exec(cmd::Scan(cursor).pattern(&pattern).type_filter(tp))
+                .await
+                .unwrap();
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert_eq!(keys, vec![Bytes::from(expected.clone())]);
+    }
 
-    let result = redis
-        .exec(cmd::HDel(&key, "field1").remove("field2"))
+    redis
+        .exec(cmd::Del(&string_key).key(&list_key).key(&hash_key))
         .await
         .unwrap();
-    assert_eq!(result, 2);
+}
 
-    let result = redis.exec(cmd::HGet(&key, "field1")).await.unwrap();
-    assert_eq!(result, None);
+#[ntex::test]
+async fn test_keys_escape_pattern() {
+    let redis = connect().await;
+    let prefix = new_key();
+    let literal = format!("{}a*b[c]", prefix);
+    let other = format!("{}aXXb{}", prefix, "c");
 
-    let result = redis.exec(cmd::HGetAll(&key)).await.unwrap();
-    assert!(result.is_empty());
+    redis.exec(cmd::Set(&literal, "1")).await.unwrap();
+    redis.exec(cmd::Set(&other, "2")).await.unwrap();
+
+    let keys = redis
+        .exec(cmd::Keys(cmd::keys::escape_pattern(&literal)))
+        .await
+        .unwrap();
+    assert_eq!(keys, vec![ByteString::from(literal)]);
 }
 
 #[ntex::test]
-async fn test_connection() {
+async fn test_wrong_type_error() {
     let redis = connect().await;
+    let key = new_key();
 
-    let result = redis.exec(cmd::Ping()).await.unwrap();
-    assert_eq!(result, "PONG");
+    redis.exec(cmd::LPush(&key, "value")).await.unwrap();
 
-    let result = redis.exec(cmd::Select(1)).await.unwrap();
-    assert!(result);
+    let result = redis.exec(cmd::Get(&key)).await;
+    assert!(matches!(result, Err(CommandError::WrongType(_))));
+
+    let result = redis.exec(cmd::IncrBy(&key, 1)).await;
+    assert!(matches!(result, Err(CommandError::WrongType(_))));
 }
 
 #[ntex::test]
-async fn test_subscribe() {
+async fn test_sort() {
+    let redis = connect().await;
     let key = new_key();
-    let channel = Bytes::from(key);
+    let dest = new_key();
 
-    let subscriber = RedisConnector::new("127.0.0.1:6379")
-        .connect_simple()
+    redis
+        .exec(cmd::RPush(&key, "3").extend(vec!["1", "2"]))
         .await
         .unwrap();
 
-    // sub
-    let pubsub = subscriber
-        .subscribe(cmd::Subscribe(vec![&channel]))
-        .unwrap();
-    let message = pubsub.recv().await;
-    assert_eq!(
-        message.unwrap().unwrap(),
-        cmd::SubscribeItem::Subscribed(channel.clone())
-    );
-
-    let publisher = connect().await;
-
-    // pub
-    let result = publisher.exec(cmd::Publish(&channel, "1")).await.unwrap();
-    assert_eq!(result, 1);
-
-    // receive message
-    let message = pubsub.recv().await;
+    let result = redis.exec(cmd::Sort(&key)).await.unwrap();
+    let result_ro = redis.exec(cmd::SortRo(&key)).await.unwrap();
     assert_eq!(
-        message.unwrap().unwrap(),
-        cmd::SubscribeItem::Message {
-            pattern: None,
-            channel: channel.clone(),
-            payload: Bytes::from_static(b"1")
-        }
+        result,
+        vec![
+            Some(Bytes::from_static(b"1")),
+            Some(Bytes::from_static(b"2")),
+            Some(Bytes::from_static(b"3"))
+        ]
     );
+    assert_eq!(result, result_ro);
 
-    // unsub
-    pubsub.send(cmd::UnSubscribe(Some(vec![&channel]))).unwrap();
-    let message = pubsub.recv().await;
+    let result = redis
+        .exec(cmd::Sort(&key).desc().limit(0, 2))
+        .await
+        .unwrap();
+    let result_ro = redis
+        .exec(cmd::SortRo(&key).desc().limit(0, 2))
+        .await
+        .unwrap();
     assert_eq!(
-        message.unwrap().unwrap(),
-        cmd::SubscribeItem::UnSubscribed(channel.clone())
+        result,
+        vec![
+            Some(Bytes::from_static(b"3")),
+            Some(Bytes::from_static(b"2"))
+        ]
     );
+    assert_eq!(result, result_ro);
 
-    // back to client state
-    let client = pubsub.into_client();
-    client.exec(cmd::Reset()).await.unwrap();
+    let result = redis.exec(cmd::Sort(&key).store(&dest)).await.unwrap();
+    assert_eq!(result, 3);
 }
 
 #[ntex::test]
-async fn test_ssubscribe() {
-    let key = new_key();
-    let channel = Bytes::from(key);
-
-    let subscriber = RedisConnector::new("127.0.0.1:6379")
+async fn test_strings_simple() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
         .connect_simple()
         .await
         .unwrap();
+    let key = new_key();
 
-    // sub
-    let pubsub = subscriber
-        .subscribe(cmd::SSubscribe(vec![&channel]))
-        .unwrap();
-    let message = pubsub.recv().await;
-    assert_eq!(
-        message.unwrap().unwrap(),
-        cmd::SubscribeItem::Subscribed(channel.clone())
-    );
-
-    let publisher = connect().await;
-
-    // pub
-    let result = publisher.exec(cmd::SPublish(&channel, "1")).await.unwrap();
-    assert_eq!(result, 1);
+    let result = redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    assert!(result);
 
-    // receive message
-    let message = pubsub.recv().await;
-    assert_eq!(
-        message.unwrap().unwrap(),
-        cmd::SubscribeItem::Message {
-            pattern: None,
-            channel: channel.clone(),
-            payload: Bytes::from_static(b"1")
-        }
-    );
+    let resp = redis.exec(cmd::Get(&key)).await.unwrap().unwrap();
+    assert_eq!(resp, "value");
 
-    // unsub
-    pubsub
-        .send(cmd::SUnSubscribe(Some(vec![&channel])))
-        .unwrap();
-    let message = pubsub.recv().await;
-    assert_eq!(
-        message.unwrap().unwrap(),
-        cmd::SubscribeItem::UnSubscribed(channel.clone())
-    );
+    let resp = redis.exec(cmd::Get("unknown")).await.unwrap();
+    assert_eq!(resp, None);
 }
 
 #[ntex::test]
-async fn test_psubscribe() {
-    let part = new_key();
-    let channel = Bytes::from(format!("channel:{}", part));
-    let pattern = Bytes::from("channel:*");
-
-    let subscriber = RedisConnector::new("127.0.0.1:6379")
+async fn test_exec_all_simple() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
         .connect_simple()
         .await
         .unwrap();
+    let key1 = new_key();
+    let key2 = new_key();
+    let key3 = new_key();
 
-    // sub
-    let pubsub = subscriber
-        .subscribe(cmd::PSubscribe(vec![&pattern]))
-        .unwrap();
+    let result = redis
+        .exec_all(vec![
+            cmd::Set(&key1, "1"),
+            cmd::Set(&key2, "2"),
+            cmd::Set(&key3, "3"),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 3);
+    for res in result {
+        assert!(res.unwrap());
+    }
+
+    let result = redis
+        .exec_all(vec![cmd::Get(&key1), cmd::Get(&key2), cmd::Get(&key3)])
+        .await
+        .unwrap();
+    let result: Vec<_> = result.into_iter().map(|r| r.unwrap().unwrap()).collect();
+    assert_eq!(result, vec!["1", "2", "3"]);
+}
+
+#[ntex::test]
+async fn test_blmove() {
+    let src = new_key();
+    let dst = new_key();
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let other = connect().await;
+
+    let (value, _) = ntex::util::join(
+        redis.exec(cmd::BLMove(
+            &src,
+            &dst,
+            cmd::ListSide::Left,
+            cmd::ListSide::Right,
+            5.0,
+        )),
+        async {
+            ntex::time::sleep(ntex::time::Millis(100)).await;
+            other.exec(cmd::RPush(&src, "value")).await.unwrap();
+        },
+    )
+    .await;
+    assert_eq!(value.unwrap().unwrap(), "value");
+    let values = other.exec(cmd::LRange(&dst, 0, -1)).await.unwrap();
+    assert_eq!(values, vec!["value"]);
+
+    // timeout
+    let value = redis
+        .exec(cmd::BLMove(
+            &src,
+            &dst,
+            cmd::ListSide::Left,
+            cmd::ListSide::Right,
+            0.1,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(value, None);
+
+    let (value, _) = ntex::util::join(redis.exec(cmd::BLMPop(5.0, [&src]).count(2)), async {
+        ntex::time::sleep(ntex::time::Millis(100)).await;
+        other.exec(cmd::RPush(&src, "a").value("b")).await.unwrap();
+    })
+    .await;
+    let (key, values) = value.unwrap().unwrap();
+    assert_eq!(key, src);
+    assert_eq!(values, vec!["a", "b"]);
+
+    // shared client rejects blocking commands
+    let res = other.exec(cmd::BLMPop(0.1, [&src])).await;
+    assert!(matches!(res, Err(CommandError::Unsupported(_))));
+}
+
+#[ntex::test]
+async fn test_blpop() {
+    let redis = connect().await;
+    let simple = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let key = new_key();
+
+    let res = simple.exec(cmd::BLPop([&key], 0.1)).await.unwrap();
+    assert_eq!(res, None);
+
+    redis.exec(cmd::RPush(&key, "a")).await.unwrap();
+    redis.exec(cmd::RPush(&key, "b")).await.unwrap();
+    let (list, value) = simple.exec(cmd::BRPop([&key], 0.1)).await.unwrap().unwrap();
+    assert_eq!(list, key);
+    assert_eq!(value, "b");
+
+    // shared client rejects blocking commands
+    match redis.exec(cmd::BLPop([&key], 0.1)).await {
+        Err(CommandError::Unsupported(err)) => assert!(err.contains("SimpleClient")),
+        res => panic!("unexpected result: {:?}", res),
+    }
+    assert_eq!(redis.exec(cmd::LPop(&key)).await.unwrap().unwrap(), "a");
+}
+
+#[ntex::test]
+async fn test_require_version() {
+    let redis = connect().await;
+
+    let version = redis.server_version().await.unwrap();
+    assert!(version >= (2, 0, 0));
+    redis.require_version(version).await.unwrap();
+
+    let err = redis.require_version((99, 0, 0)).await.unwrap_err();
+    assert!(matches!(
+        err,
+        CommandError::UnsupportedVersion(v, (99, 0, 0)) if v == version
+    ));
+    assert!(err.to_string().contains("lower than required 99.0.0"));
+}
+
+#[ntex::test]
+async fn test_del_if_type() {
+    let redis = connect().await;
+    let key = new_key();
+
+    redis.exec(cmd::LPush(&key, "value")).await.unwrap();
+    assert!(!redis
+        .del_if_type(&key, cmd::RedisType::String)
+        .await
+        .unwrap());
+    assert_eq!(redis.exec(cmd::Exists(&key)).await.unwrap(), 1);
+
+    assert!(redis.del_if_type(&key, cmd::RedisType::List).await.unwrap());
+    assert_eq!(redis.exec(cmd::Exists(&key)).await.unwrap(), 0);
+
+    // missing key
+    assert!(!redis.del_if_type(&key, cmd::RedisType::List).await.unwrap());
+}
+
+#[ntex::test]
+async fn test_scan_keys() {
+    let redis = connect().await;
+    let prefix = new_key();
+
+    let mut expected: Vec<String> = (0..100).map(|i| format!("{}:{}", prefix, i)).collect();
+    for key in &expected {
+        redis.exec(cmd::Set(key, "value")).await.unwrap();
+    }
+
+    let mut keys: Vec<String> = redis
+        .scan_keys(format!("{}:*", prefix))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|key| String::from_utf8(key.to_vec()).unwrap())
+        .collect();
+    keys.sort();
+    expected.sort();
+    assert_eq!(keys, expected);
+
+    redis.del_many(expected).await.unwrap();
+    assert!(redis
+        .scan_keys(format!("{}:*", prefix))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[ntex::test]
+async fn test_wait_aof() {
+    let redis = connect().await;
+    if redis.require_version((7, 2, 0)).await.is_err() {
+        return;
+    }
+    let simple = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    simple.exec(cmd::Set(new_key(), "value")).await.unwrap();
+
+    let config = redis
+        .exec(cmd::ArrayRaw(array!["CONFIG", "GET", "appendonly"]))
+        .await
+        .unwrap();
+    let aof = matches!(config.get(1), Some(codec::Response::Bytes(val)) if val == "yes");
+
+    if aof {
+        // local fsync is acknowledged
+        let res = simple.exec(cmd::WaitAof(1, 0, 1000)).await.unwrap();
+        assert_eq!(res, (1, 0));
+    } else {
+        let res = simple.exec(cmd::WaitAof(0, 0, 100)).await.unwrap();
+        assert_eq!(res, (0, 0));
+        assert!(simple.exec(cmd::WaitAof(1, 0, 100)).await.is_err());
+    }
+}
+
+#[ntex::test]
+async fn test_push_capped() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let max = NonZeroU32::new(5).unwrap();
+    for i in 0..10 {
+        let len = redis
+            .exec(cmd::RPush(&key, i.to_string()).capped(max))
+            .await
+            .unwrap();
+        assert_eq!(len, (i + 1).min(5));
+    }
+    let len: i64 = redis
+        .exec(cmd::IntRaw(array!["LLEN", key.clone()]))
+        .await
+        .unwrap();
+    assert_eq!(len, 5);
+    let values = redis.exec(cmd::LRange(&key, 0, -1)).await.unwrap();
+    assert_eq!(values, vec!["5", "6", "7", "8", "9"]);
+
+    let key = new_key();
+    let len = redis
+        .exec(
+            cmd::LPush(&key, "1")
+                .extend(["2", "3"])
+                .capped(NonZeroU32::new(2).unwrap()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(len, 2);
+    let values = redis.exec(cmd::LRange(&key, 0, -1)).await.unwrap();
+    assert_eq!(values, vec!["3", "2"]);
+}
+
+#[ntex::test]
+async fn test_lists() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let result = redis
+        .exec(cmd::LPush(&key, "l_value").if_exists())
+        .await
+        .unwrap();
+    assert_eq!(result, 0);
+    let result = redis
+        .exec(cmd::RPush(&key, "l_value").if_exists())
+        .await
+        .unwrap();
+    assert_eq!(result, 0);
+
+    let result = redis.exec(cmd::LPush(&key, "l_value")).await.unwrap();
+    assert_eq!(result, 1);
+    let result = redis.exec(cmd::RPush(&key, "r_value")).await.unwrap();
+    assert_eq!(result, 2);
+
+    let resp = redis.exec(cmd::LIndex(&key, 0)).await.unwrap().unwrap();
+    assert_eq!(resp, "l_value");
+    let resp = redis.exec(cmd::LIndex(&key, 1)).await.unwrap().unwrap();
+    assert_eq!(resp, "r_value");
+
+    let resp = redis.exec(cmd::LPop(&key)).await.unwrap();
+    assert_eq!(resp.unwrap(), "l_value");
+    let resp = redis.exec(cmd::RPop(&key)).await.unwrap();
+    assert_eq!(resp.unwrap(), "r_value");
+    let resp = redis.exec(cmd::LPop(&key)).await.unwrap();
+    assert_eq!(resp, None);
+
+    let result = redis
+        .exec(cmd::RPush(&key, "1").extend(vec!["2", "3", "4", "5"]))
+        .await
+        .unwrap();
+    assert_eq!(result, 5);
+    let resp = redis.exec(cmd::LPop(&key).count(3)).await.unwrap();
+    assert_eq!(resp, vec!["1", "2", "3"]);
+    let resp = redis.exec(cmd::RPop(&key).count(3)).await.unwrap();
+    assert_eq!(resp, vec!["5", "4"]);
+    let resp = redis.exec(cmd::LPop(&key).count(3)).await.unwrap();
+    assert!(resp.is_empty());
+}
+
+#[ntex::test]
+async fn test_exec_stream() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let values: Vec<_> = (0..10_000).map(|i| i.to_string()).collect();
+    for chunk in values.chunks(1000) {
+        redis
+            .exec(cmd::RPush(&key, &chunk[0]).extend(&chunk[1..]))
+            .await
+            .unwrap();
+    }
+
+    let elements = redis.exec_stream(cmd::LRange(&key, 0, -1)).unwrap();
+    let mut count = 0;
+    while let Some(item) = elements.recv().await {
+        assert_eq!(
+            item.unwrap(),
+            codec::Response::Bytes(count.to_string().into())
+        );
+        count += 1;
+    }
+    assert_eq!(count, 10_000);
+    assert!(redis.is_idle());
+
+    redis.exec(cmd::Del(&key)).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_lpos() {
+    let redis = connect().await;
+    let key = new_key();
+
+    redis
+        .exec(cmd::RPush(&key, "a").extend(vec!["b", "c", "b", "b"]))
+        .await
+        .unwrap();
+
+    let resp = redis.exec(cmd::LPos(&key, "b")).await.unwrap();
+    assert_eq!(resp, Some(1));
+    let resp = redis.exec(cmd::LPos(&key, "b").rank(2)).await.unwrap();
+    assert_eq!(resp, Some(3));
+    let resp = redis.exec(cmd::LPos(&key, "b").rank(-1)).await.unwrap();
+    assert_eq!(resp, Some(4));
+    let resp = redis.exec(cmd::LPos(&key, "x")).await.unwrap();
+    assert_eq!(resp, None);
+
+    let resp = redis.exec(cmd::LPos(&key, "b").count(0)).await.unwrap();
+    assert_eq!(resp, vec![1, 3, 4]);
+    let resp = redis.exec(cmd::LPos(&key, "x").count(0)).await.unwrap();
+    assert!(resp.is_empty());
+}
+
+#[ntex::test]
+async fn test_linsert() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let resp = redis.exec(cmd::LInsert(&key, "b", "a")).await.unwrap();
+    assert_eq!(resp, 0);
+
+    redis.exec(cmd::RPush(&key, "b")).await.unwrap();
+    let resp = redis.exec(cmd::LInsert(&key, "b", "a")).await.unwrap();
+    assert_eq!(resp, 2);
+    let resp = redis
+        .exec(cmd::LInsert(&key, "b", "c").after())
+        .await
+        .unwrap();
+    assert_eq!(resp, 3);
+    let resp = redis
+        .exec(cmd::LInsert(&key, "x", "d").before())
+        .await
+        .unwrap();
+    assert_eq!(resp, -1);
+
+    let resp = redis.exec(cmd::LRange(&key, 0, -1)).await.unwrap();
+    assert_eq!(resp, vec!["a", "b", "c"]);
+}
+
+#[ntex::test]
+async fn test_sets() {
+    let redis = connect().await;
+    let key1 = new_key();
+    let key2 = new_key();
+
+    let result = redis
+        .exec(cmd::SAdd(&key1, "a").members(vec!["b", "c"]))
+        .await
+        .unwrap();
+    assert_eq!(result, 3);
+    let result = redis
+        .exec(cmd::SAdd(&key2, "b").member("c").member("d"))
+        .await
+        .unwrap();
+    assert_eq!(result, 3);
+
+    let result = redis
+        .exec(cmd::SInterCard(vec![&key1, &key2]))
+        .await
+        .unwrap();
+    assert_eq!(result, 2);
+    let result = redis
+        .exec(cmd::SInterCard(vec![&key1, &key2]).limit(1))
+        .await
+        .unwrap();
+    assert_eq!(result, 1);
+}
+
+#[ntex::test]
+async fn test_hset_map() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let map: HashMap<String, usize> = (0..50).map(|i| (format!("field{}", i), i)).collect();
+    let added = redis.exec(cmd::HSetMap(&key, map)).await.unwrap();
+    assert_eq!(added, 50);
+    let len = redis.exec(cmd::HLen(&key)).await.unwrap();
+    assert_eq!(len, 50);
+    let value = redis.exec(cmd::HGet(&key, "field42")).await.unwrap();
+    assert_eq!(value.unwrap(), "42");
+}
+
+#[ntex::test]
+async fn test_hscan() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let map: HashMap<String, usize> = (0..20).map(|i| (format!("field{}", i), i)).collect();
+    redis.exec(cmd::HSetMap(&key, map.clone())).await.unwrap();
+
+    let mut entries = HashMap::default();
+    let mut cursor = 0;
+    loop {
+        let (next, batch) = redis.exec(cmd::HScan(&key, cursor)).await.unwrap();
+        entries.extend(batch);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    assert_eq!(entries.len(), 20);
+    assert_eq!(entries[&Bytes::from_static(b"field7")], "7");
+
+    if redis.require_version((7, 4, 0)).await.is_err() {
+        return;
+    }
+    let mut fields = Vec::new();
+    let mut cursor = 0;
+    loop {
+        let (next, batch) = redis
+            .exec(cmd::HScan(&key, cursor).novalues())
+            .await
+            .unwrap();
+        fields.extend(batch);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    let mut fields: Vec<String> = fields
+        .into_iter()
+        .map(|f| String::from_utf8(f.to_vec()).unwrap())
+        .collect();
+    let mut expected: Vec<String> = map.into_keys().collect();
+    fields.sort();
+    expected.sort();
+    assert_eq!(fields, expected);
+}
+
+#[ntex::test]
+async fn test_smembers_collect() {
+    let redis = connect().await;
+    let key = new_key();
+
+    redis
+        .exec(cmd::SAdd(&key, "a").members(["b", "c"]))
+        .await
+        .unwrap();
+    let members = redis
+        .exec(cmd::SMembers(&key).collect::<std::collections::HashSet<ByteString>>())
+        .await
+        .unwrap();
+    assert_eq!(members.len(), 3);
+    assert!(members.contains("a"));
+    assert!(!members.contains("d"));
+}
+
+#[ntex::test]
+async fn test_validate_on_connect() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .validate_on_connect(true)
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(redis.exec(cmd::Ping()).await.unwrap(), "PONG");
+
+    let simple = RedisConnector::new("127.0.0.1:6379")
+        .validate_on_connect(true)
+        .connect_simple()
+        .await
+        .unwrap();
+    assert_eq!(simple.exec(cmd::Ping()).await.unwrap(), "PONG");
+}
+
+#[ntex::test]
+#[allow(clippy::await_holding_lock)]
+async fn test_client_no_touch() {
+    let redis = connect().await;
+    if redis.require_version((7, 2, 0)).await.is_err() {
+        return;
+    }
+    // OBJECT IDLETIME fails while LFU policy is selected
+    let _lock = lock_maxmemory_policy();
+    let key = new_key();
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+
+    let no_touch = connect().await;
+    no_touch.exec(cmd::ClientNoTouch(true)).await.unwrap();
+    no_touch.exec(cmd::ClientNoEvict(true)).await.unwrap();
+
+    ntex::time::sleep(Duration::from_millis(2100)).await;
+
+    // GET does not reset idle time
+    no_touch.exec(cmd::Get(&key)).await.unwrap();
+    let idle = redis.exec(cmd::ObjectIdleTime(&key)).await.unwrap();
+    assert!(idle.unwrap() >= 2);
+
+    no_touch.exec(cmd::ClientNoTouch(false)).await.unwrap();
+    no_touch.exec(cmd::ClientNoEvict(false)).await.unwrap();
+    no_touch.exec(cmd::Get(&key)).await.unwrap();
+    let idle = redis.exec(cmd::ObjectIdleTime(&key)).await.unwrap();
+    assert!(idle.unwrap() < 2);
+
+    assert_eq!(
+        redis.exec(cmd::ObjectIdleTime(new_key())).await.unwrap(),
+        None
+    );
+}
+
+#[ntex::test]
+async fn test_get_str() {
+    let redis = connect().await;
+    let key = new_key();
+
+    assert_eq!(redis.exec(cmd::GetStr(&key)).await.unwrap(), None);
+
+    redis.exec(cmd::Set(&key, "значение")).await.unwrap();
+    let value: Option<ByteString> = redis.exec(cmd::GetStr(&key)).await.unwrap();
+    assert_eq!(value.unwrap(), "значение");
+
+    redis
+        .exec(cmd::Set(&key, Bytes::from_static(b"\xff\x00")))
+        .await
+        .unwrap();
+    let res = redis.exec(cmd::GetStr(&key)).await;
+    assert!(matches!(res, Err(CommandError::Output(_, _))));
+
+    // connection is still usable
+    assert!(redis.exec(cmd::Get(&key)).await.unwrap().is_some());
+}
+
+#[ntex::test]
+async fn test_setrange_padding() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let len = redis.exec(cmd::SetRange(&key, 5, "value")).await.unwrap();
+    assert_eq!(len, 10);
+
+    let prefix = redis.exec(cmd::GetRange(&key, 0, 4)).await.unwrap();
+    assert_eq!(prefix.unwrap(), &[0u8; 5][..]);
+    let value = redis.exec(cmd::Get(&key)).await.unwrap();
+    assert_eq!(value.unwrap(), &b"\0\0\0\0\0value"[..]);
+
+    // binary value overwrites part of the string
+    let data = Bytes::from_static(b"\0\xff");
+    let len = redis.exec(cmd::SetRange(&key, 7, data)).await.unwrap();
+    assert_eq!(len, 10);
+    let value = redis.exec(cmd::GetRange(&key, 5, -1)).await.unwrap();
+    assert_eq!(value.unwrap(), &b"va\0\xffe"[..]);
+}
+
+#[ntex::test]
+async fn test_numeric_args() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let result = redis
+        .exec(cmd::SAdd(&key, 1i64).members([2i64, 3]).member(1.5))
+        .await
+        .unwrap();
+    assert_eq!(result, 4);
+
+    let key = new_key();
+    redis.exec(cmd::HSet(&key, 10, 20)).await.unwrap();
+    let value = redis.exec(cmd::HGet(&key, 10)).await.unwrap();
+    assert_eq!(value.unwrap(), "20");
+}
+
+#[ntex::test]
+async fn test_hashes() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let result = redis
+        .exec(cmd::HSet(&key, "field1", "1").entry("field2", "2"))
+        .await
+        .unwrap();
+    assert_eq!(result, 2);
+
+    let result = redis.exec(cmd::HIncrBy(&key, "field1", 10)).await.unwrap();
+    assert_eq!(result, 11);
+
+    let result = redis.exec(cmd::HGet(&key, "field1")).await.unwrap();
+    assert_eq!(result.unwrap(), "11");
+
+    let result = redis.exec(cmd::HGetAll(&key)).await.unwrap();
+    let mut expected = HashMap::default();
+    expected.insert("field1".into(), "11".into());
+    expected.insert("field2".into(), "2".into());
+    assert_eq!(result, expected);
+
+    let result = redis
+        .exec(cmd::HDel(&key, "field1").remove("field2"))
+        .await
+        .unwrap();
+    assert_eq!(result, 2);
+
+    let result = redis.exec(cmd::HGet(&key, "field1")).await.unwrap();
+    assert_eq!(result, None);
+
+    let result = redis.exec(cmd::HGetAll(&key)).await.unwrap();
+    assert!(result.is_empty());
+}
+
+#[ntex::test]
+async fn test_hgetall_collect() {
+    use std::collections::BTreeMap;
+
+    let redis = connect().await;
+    let key = new_key();
+
+    redis
+        .exec(cmd::HSet(&key, "c", "3").entry("a", "1").entry("b", "2"))
+        .await
+        .unwrap();
+
+    let map = redis
+        .exec(cmd::HGetAll(&key).collect::<BTreeMap<String, String>>())
+        .await
+        .unwrap();
+    let items: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    assert_eq!(items, vec![("a", "1"), ("b", "2"), ("c", "3")]);
+
+    let map = redis
+        .exec(cmd::HGetAll(&key).collect::<Vec<(ByteString, Bytes)>>())
+        .await
+        .unwrap();
+    assert_eq!(map.len(), 3);
+
+    redis.exec(cmd::Del(&key)).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_hrandfield() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let result = redis.exec(cmd::HRandField(&key)).await.unwrap();
+    assert_eq!(result, None);
+
+    redis
+        .exec(cmd::HSet(&key, "field1", "1").entry("field2", "2"))
+        .await
+        .unwrap();
+
+    let result = redis.exec(cmd::HRandField(&key)).await.unwrap().unwrap();
+    assert!(result == "field1" || result == "field2");
+
+    let mut result = redis.exec(cmd::HRandField(&key).count(2)).await.unwrap();
+    result.sort();
+    assert_eq!(result, vec!["field1", "field2"]);
+
+    let result = redis.exec(cmd::HRandField(&key).count(-5)).await.unwrap();
+    assert_eq!(result.len(), 5);
+
+    let mut result = redis
+        .exec(cmd::HRandField(&key).count(2).with_values())
+        .await
+        .unwrap();
+    result.sort();
+    assert_eq!(
+        result,
+        vec![
+            (Bytes::from_static(b"field1"), Bytes::from_static(b"1")),
+            (Bytes::from_static(b"field2"), Bytes::from_static(b"2"))
+        ]
+    );
+}
+
+#[ntex::test]
+async fn test_connection() {
+    let redis = connect().await;
+
+    let result = redis.exec(cmd::Ping()).await.unwrap();
+    assert_eq!(result, "PONG");
+
+    let result = redis.exec(cmd::Select(1)).await.unwrap();
+    assert!(result);
+}
+
+#[ntex::test]
+async fn test_status_raw() {
+    let redis = connect().await;
+
+    let status = redis.exec(cmd::StatusRaw(array!["PING"])).await.unwrap();
+    assert_eq!(status, "PONG");
+
+    let res = redis.exec(cmd::StatusRaw(array!["ECHO", "test"])).await;
+    assert!(matches!(res, Err(CommandError::Output(_, _))));
+}
+
+#[ntex::test]
+async fn test_get_to() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let mut sink = Vec::new();
+    assert_eq!(redis.get_to(&key, &mut sink).await.unwrap(), None);
+
+    let value = Bytes::from(vec![b'x'; 1 << 20]);
+    redis.exec(cmd::Set(&key, value.clone())).await.unwrap();
+    assert_eq!(redis.get_to(&key, &mut sink).await.unwrap(), Some(1 << 20));
+    assert_eq!(sink, value);
+}
+
+#[ntex::test]
+async fn test_typed_raw() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let size = redis.exec(cmd::IntRaw(array!["DBSIZE"])).await.unwrap();
+    assert!(size >= 0);
+
+    let value = redis
+        .exec(cmd::BulkRaw(array!["GET", key.clone()]))
+        .await
+        .unwrap();
+    assert_eq!(value, None);
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    let value = redis
+        .exec(cmd::BulkRaw(array!["GET", key.clone()]))
+        .await
+        .unwrap();
+    assert_eq!(value, Some(Bytes::from_static(b"value")));
+
+    let items = redis
+        .exec(cmd::ArrayRaw(array!["MGET", key.clone(), new_key()]))
+        .await
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![codec::Response::Bytes("value".into()), codec::Response::Nil]
+    );
+}
+
+#[ntex::test]
+async fn test_pipeline() {
+    let redis = connect().await;
+    let key = new_key();
+    let counter = new_key();
+
+    let mut pipe = redis.pipeline();
+    let set = pipe.queue(cmd::Set(&key, "value"));
+    let incr = pipe.queue(cmd::IncrBy(&counter, 5));
+    let get = pipe.queue(cmd::Get(&key));
+    let wrong = pipe.queue(cmd::IncrBy(&key, 1));
+    pipe.exec().await.unwrap();
+
+    assert!(set.take().unwrap());
+    assert_eq!(incr.take().unwrap(), 5);
+    assert_eq!(get.take().unwrap().unwrap(), "value");
+    assert!(matches!(wrong.take(), Err(CommandError::Error(_))));
+}
+
+#[ntex::test]
+async fn test_raw_command() {
+    let redis = connect().await;
+    let key = new_key();
+
+    let size = redis.exec(cmd::Raw::<i64>(array!["DBSIZE"])).await.unwrap();
+    assert!(size >= 0);
+
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    let value = redis
+        .exec(cmd::Raw::<Option<String>>(array!["GET", key.clone()]))
+        .await
+        .unwrap();
+    assert_eq!(value, Some("value".to_string()));
+
+    let res = redis.exec(cmd::Raw::<i64>(array!["GET", key])).await;
+    assert!(matches!(res, Err(CommandError::Output(_, _))));
+}
+
+#[ntex::test]
+async fn test_command_getkeys() {
+    let redis = connect().await;
+
+    let result = redis
+        .exec(cmd::CommandGetKeys(array!["SET", "k", "v"]))
+        .await
+        .unwrap();
+    assert_eq!(result, vec!["k"]);
+
+    let result = redis
+        .exec(cmd::CommandGetKeys(array!["MSET", "a", "1", "b", "2"]))
+        .await
+        .unwrap();
+    assert_eq!(result, vec!["a", "b"]);
+}
+
+#[cfg(feature = "test-util")]
+#[ntex::test]
+async fn test_support_encoding() {
+    use ntex_redis::test_support;
+
+    let redis = test_support::connect_db("127.0.0.1:6379", 9).await.unwrap();
+    let key = test_support::gen_random_key();
+
+    redis.exec(cmd::Set(&key, "short")).await.unwrap();
+    test_support::assert_encoding(&redis, &key, "embstr").await;
+
+    redis.exec(cmd::Set(&key, "12345")).await.unwrap();
+    test_support::assert_encoding(&redis, &key, "int").await;
+
+    // strings longer than 44 bytes are not embedded
+    redis.exec(cmd::Set(&key, "x".repeat(45))).await.unwrap();
+    test_support::assert_encoding(&redis, &key, "raw").await;
+}
+
+#[cfg(feature = "test-util")]
+#[ntex::test]
+#[should_panic(expected = "Unexpected encoding")]
+async fn test_support_encoding_mismatch() {
+    use ntex_redis::test_support;
+
+    let redis = test_support::connect_db("127.0.0.1:6379", 9).await.unwrap();
+    let key = test_support::gen_random_key();
+
+    redis.exec(cmd::Set(&key, "12345")).await.unwrap();
+    test_support::assert_encoding(&redis, &key, "embstr").await;
+}
+
+#[cfg(feature = "debug-commands")]
+#[ntex::test]
+async fn test_debug() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let key = new_key();
+
+    let start = std::time::Instant::now();
+    redis.exec(cmd::DebugSleep(0.1)).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    let result = redis.exec(cmd::DebugObject(&key)).await.unwrap();
+    assert!(result.contains("encoding:"));
+}
+
+#[ntex::test]
+async fn test_exec_timeout() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let key = new_key();
+
+    // BLPOP blocks only its own connection
+    let result = redis
+        .exec_timeout(cmd::BLPop([&key], 2.0), ntex::time::Seconds(1))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ntex_redis::errors::CommandError::Timeout)
+    ));
+
+    // late BLPOP response is skipped
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+    let result = redis.exec(cmd::Get(&key)).await.unwrap();
+    assert_eq!(result.unwrap(), "value");
+}
+
+#[ntex::test]
+async fn test_subscribe() {
+    let key = new_key();
+    let channel = Bytes::from(key);
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+
+    // sub
+    let pubsub = subscriber
+        .subscribe(cmd::Subscribe(vec![&channel]))
+        .unwrap();
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Subscribed {
+            channel: channel.clone(),
+            count: 1
+        }
+    );
+
+    let publisher = connect().await;
+
+    // pub
+    let result = publisher.exec(cmd::Publish(&channel, "1")).await.unwrap();
+    assert_eq!(result, 1);
+
+    // receive message
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Message {
+            pattern: None,
+            channel: channel.clone(),
+            payload: Bytes::from_static(b"1")
+        }
+    );
+
+    // unsub
+    pubsub.send(cmd::UnSubscribe(Some(vec![&channel]))).unwrap();
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::UnSubscribed {
+            channel: channel.clone(),
+            count: 0
+        }
+    );
+
+    // back to client state
+    let client = pubsub.into_client();
+    client.exec(cmd::Reset()).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_subscribe_channels() {
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+
+    // single channel literal
+    let pubsub = subscriber.subscribe(cmd::Subscribe(["channel1"])).unwrap();
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Subscribed {
+            channel: Bytes::from_static(b"channel1"),
+            count: 1
+        }
+    );
+
+    // vec of channels
+    pubsub
+        .send(cmd::Subscribe(vec!["channel2", "channel3"]))
+        .unwrap();
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Subscribed {
+            channel: Bytes::from_static(b"channel2"),
+            count: 2
+        }
+    );
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Subscribed {
+            channel: Bytes::from_static(b"channel3"),
+            count: 3
+        }
+    );
+}
+
+#[ntex::test]
+async fn test_subscribe_wait_subscribed() {
+    let channels = [new_key(), new_key(), new_key()];
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap()
+        .subscribe(cmd::Subscribe(channels.iter()))
+        .unwrap();
+
+    let confirmed = subscriber.wait_subscribed(3).await.unwrap();
+    assert_eq!(confirmed.len(), 3);
+    for channel in &channels {
+        assert!(confirmed.contains(channel.as_bytes()));
+    }
+}
+
+#[ntex::test]
+async fn test_subscribe_for_each() {
+    let channel = new_key();
+    let publisher = connect().await;
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap()
+        .subscribe(cmd::Subscribe([&channel]))
+        .unwrap();
+    subscriber.wait_subscribed(1).await.unwrap();
+
+    publisher.exec(cmd::Publish(&channel, "1")).await.unwrap();
+    publisher.exec(cmd::Publish(&channel, "2")).await.unwrap();
+
+    let messages = std::cell::RefCell::new(Vec::new());
+    let res = ntex::time::timeout(
+        ntex::time::Millis(500),
+        subscriber.for_each(|item| {
+            let messages = &messages;
+            async move {
+                if let cmd::SubscribeItem::Message { payload, .. } = item {
+                    messages.borrow_mut().push(payload);
+                }
+            }
+        }),
+    )
+    .await;
+    assert!(res.is_err(), "for_each returns only on error");
+    assert_eq!(messages.into_inner(), vec!["1", "2"]);
+}
+
+#[ntex::test]
+async fn test_subscribe_broadcast() {
+    let channel = Bytes::from(new_key());
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+
+    let (broadcast, control) = subscriber
+        .subscribe(cmd::Subscribe([&channel]))
+        .unwrap()
+        .broadcast();
+    let consumer1 = broadcast.consumer();
+    let consumer2 = broadcast.clone().consumer();
+
+    let message = control.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Subscribed {
+            channel: channel.clone(),
+            count: 1
+        }
+    );
+
+    let publisher = connect().await;
+    let result = publisher.exec(cmd::Publish(&channel, "1")).await.unwrap();
+    assert_eq!(result, 1);
+
+    let expected = cmd::SubscribeItem::Message {
+        pattern: None,
+        channel: channel.clone(),
+        payload: Bytes::from_static(b"1"),
+    };
+    assert_eq!(consumer1.recv().await.unwrap(), expected);
+    assert_eq!(consumer2.recv().await.unwrap(), expected);
+}
+
+#[ntex::test]
+async fn test_subscribe_into_client_reset() {
+    let channel = Bytes::from(new_key());
+    let key = new_key();
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let pubsub = subscriber.subscribe(cmd::Subscribe([&channel])).unwrap();
+
+    let publisher = connect().await;
+    publisher.exec(cmd::Set(&key, "value")).await.unwrap();
+    let result = publisher.exec(cmd::Publish(&channel, "1")).await.unwrap();
+    assert_eq!(result, 1);
+
+    // confirmation and message are pending
+    let client = pubsub.into_client_reset().await.unwrap();
+    let resp = client.exec(cmd::Get(&key)).await.unwrap().unwrap();
+    assert_eq!(resp, "value");
+}
+
+#[ntex::test]
+async fn test_subscribe_close() {
+    let channel = Bytes::from(new_key());
+    let pattern = Bytes::from(format!("{}*", new_key()));
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let pubsub = subscriber.subscribe(cmd::Subscribe([&channel])).unwrap();
+    pubsub.send(cmd::PSubscribe([&pattern])).unwrap();
+    pubsub.wait_subscribed(2).await.unwrap();
+
+    let channels = pubsub.close().await.unwrap();
+    assert_eq!(channels, vec![channel, pattern]);
+}
+
+#[ntex::test]
+async fn test_subscribe_close_shard() {
+    let channel = Bytes::from(new_key());
+    let shard = Bytes::from(new_key());
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let pubsub = subscriber.subscribe(cmd::Subscribe([&channel])).unwrap();
+    pubsub.send(cmd::SSubscribe([&shard])).unwrap();
+    pubsub.wait_subscribed(2).await.unwrap();
+
+    let channels = pubsub.close().await.unwrap();
+    assert_eq!(channels, vec![channel, shard]);
+}
+
+#[ntex::test]
+async fn test_ssubscribe() {
+    let key = new_key();
+    let channel = Bytes::from(key);
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+
+    // sub
+    let pubsub = subscriber
+        .subscribe(cmd::SSubscribe(vec![&channel]))
+        .unwrap();
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Subscribed {
+            channel: channel.clone(),
+            count: 1
+        }
+    );
+
+    let publisher = connect().await;
+
+    // pub
+    let result = publisher.exec(cmd::SPublish(&channel, "1")).await.unwrap();
+    assert_eq!(result, 1);
+
+    // receive message
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::Message {
+            pattern: None,
+            channel: channel.clone(),
+            payload: Bytes::from_static(b"1")
+        }
+    );
+
+    // unsub
+    pubsub
+        .send(cmd::SUnSubscribe(Some(vec![&channel])))
+        .unwrap();
+    let message = pubsub.recv().await;
+    assert_eq!(
+        message.unwrap().unwrap(),
+        cmd::SubscribeItem::UnSubscribed {
+            channel: channel.clone(),
+            count: 0
+        }
+    );
+}
+
+#[ntex::test]
+async fn test_psubscribe() {
+    let part = new_key();
+    let channel = Bytes::from(format!("channel:{}", part));
+    let pattern = Bytes::from("channel:*");
+
+    let subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+
+    // sub
+    let pubsub = subscriber
+        .subscribe(cmd::PSubscribe(vec![&pattern]))
+        .unwrap();
     let message = pubsub.recv().await;
     assert_eq!(
         message.unwrap().unwrap(),
-        cmd::SubscribeItem::Subscribed(pattern.clone()),
+        cmd::SubscribeItem::Subscribed {
+            channel: pattern.clone(),
+            count: 1
+        },
     );
 
     let publisher = connect().await;
@@ -344,6 +1684,533 @@ async fn test_psubscribe() {
     let message = pubsub.recv().await;
     assert_eq!(
         message.unwrap().unwrap(),
-        cmd::SubscribeItem::UnSubscribed(pattern.clone())
+        cmd::SubscribeItem::UnSubscribed {
+            channel: pattern.clone(),
+            count: 0
+        }
+    );
+}
+
+#[ntex::test]
+async fn test_queue_capacity() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .queue_capacity(10_000)
+        .connect()
+        .await
+        .unwrap();
+    let key = new_key();
+
+    let results =
+        ntex::util::join_all((0..10_000).map(|_| redis.exec(cmd::IncrBy(&key, 1)))).await;
+    for (i, res) in results.into_iter().enumerate() {
+        assert_eq!(res.unwrap(), i as i64 + 1);
+    }
+    assert!(redis.is_idle());
+
+    redis.exec(cmd::Del(&key)).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_pending() {
+    let redis = connect().await;
+    let key = new_key();
+    assert!(redis.is_idle());
+
+    let mut f1 = Box::pin(redis.exec(cmd::Set(&key, "1")));
+    let mut f2 = Box::pin(redis.exec(cmd::IncrBy(&key, 1)));
+    let mut f3 = Box::pin(redis.exec(cmd::Get(&key)));
+
+    // poll each future once to put commands on the wire
+    poll_fn(|cx| {
+        let _ = f1.as_mut().poll(cx);
+        let _ = f2.as_mut().poll(cx);
+        let _ = f3.as_mut().poll(cx);
+        Poll::Ready(())
+    })
+    .await;
+    assert_eq!(redis.pending(), 3);
+    assert!(!redis.is_idle());
+
+    assert!(f1.await.unwrap());
+    assert_eq!(f2.await.unwrap(), 2);
+    assert_eq!(f3.await.unwrap().unwrap(), "2");
+    assert_eq!(redis.pending(), 0);
+    assert!(redis.is_idle());
+}
+
+#[ntex::test]
+async fn test_hash_response_pairs() {
+    let redis = connect().await;
+    let key = new_key();
+
+    redis
+        .exec(cmd::HSet(&key, "c", "1").entry("a", "2").entry("b", "3"))
+        .await
+        .unwrap();
+
+    // small hashes keep insertion order
+    let res = Pipeline::new(redis.clone())
+        .call(array!["HGETALL", &key])
+        .await
+        .unwrap();
+    let fields: Vec<_> = codec::response_pairs(res)
+        .unwrap()
+        .into_iter()
+        .map(|(field, _)| field)
+        .collect();
+    assert_eq!(fields, vec!["c", "a", "b"]);
+}
+
+#[ntex::test]
+async fn test_hgetall_ordered() {
+    let redis = connect().await;
+    let key = new_key();
+
+    redis
+        .exec(cmd::HSet(&key, "c", "1").entry("a", "2").entry("b", "3"))
+        .await
+        .unwrap();
+
+    let expected = Pipeline::new(redis.clone())
+        .call(array!["HGETALL", &key])
+        .await
+        .unwrap();
+    let expected = codec::response_pairs(expected).unwrap();
+
+    let result = redis.exec(cmd::HGetAllOrdered(&key)).await.unwrap();
+    assert_eq!(result, expected);
+    assert_eq!(
+        result,
+        vec![
+            (Bytes::from_static(b"c"), Bytes::from_static(b"1")),
+            (Bytes::from_static(b"a"), Bytes::from_static(b"2")),
+            (Bytes::from_static(b"b"), Bytes::from_static(b"3")),
+        ]
+    );
+
+    let result = redis.exec(cmd::HGetAllOrdered(new_key())).await.unwrap();
+    assert!(result.is_empty());
+}
+
+#[ntex::test]
+async fn test_hello() {
+    let redis = connect().await;
+    let key = new_key();
+    assert_eq!(redis.protocol(), codec::Protocol::Resp2);
+
+    redis.exec(cmd::HSet(&key, "field", "value")).await.unwrap();
+
+    let res = Pipeline::new(redis.clone())
+        .call(array!["HGETALL", &key])
+        .await
+        .unwrap();
+    assert!(matches!(res, codec::Response::Array(_)));
+
+    redis.hello(codec::Protocol::Resp3).await.unwrap();
+    assert_eq!(redis.protocol(), codec::Protocol::Resp3);
+
+    let res = Pipeline::new(redis.clone())
+        .call(array!["HGETALL", &key])
+        .await
+        .unwrap();
+    assert!(matches!(res, codec::Response::Map(_)));
+
+    let res = redis.exec(cmd::HGetAll(&key)).await.unwrap();
+    assert_eq!(res.get(&Bytes::from_static(b"field")).unwrap(), "value");
+
+    redis.hello(codec::Protocol::Resp2).await.unwrap();
+    assert_eq!(redis.protocol(), codec::Protocol::Resp2);
+}
+
+#[ntex::test]
+async fn test_float_commands() {
+    let redis = connect().await;
+    let key = new_key();
+    let zkey = new_key();
+    let geokey = new_key();
+
+    let pipeline = Pipeline::new(redis.clone());
+    pipeline
+        .call(array![
+            "GEOADD",
+            &geokey,
+            "13.361389",
+            "38.115556",
+            "Palermo",
+            "15.087269",
+            "37.502669",
+            "Catania"
+        ])
+        .await
+        .unwrap();
+
+    let mut results = Vec::new();
+    for protocol in [codec::Protocol::Resp2, codec::Protocol::Resp3] {
+        redis.hello(protocol).await.unwrap();
+        redis.exec(cmd::Set(&key, "1.5")).await.unwrap();
+        pipeline
+            .call(array!["ZADD", &zkey, "1.5", "member"])
+            .await
+            .unwrap();
+
+        let incr = redis.exec(cmd::IncrByFloat(&key, 0.25)).await.unwrap();
+        let zincr = redis
+            .exec(cmd::ZIncrBy(&zkey, 0.25, "member"))
+            .await
+            .unwrap();
+        let score = redis.exec(cmd::ZScore(&zkey, "member")).await.unwrap();
+        let missing = redis.exec(cmd::ZScore(&zkey, "missing")).await.unwrap();
+        let dist = redis
+            .exec(cmd::GeoDist(&geokey, "Palermo", "Catania").unit(cmd::GeoUnit::Km))
+            .await
+            .unwrap();
+        results.push((incr, zincr, score, missing, dist));
+    }
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[0].0, 1.75);
+    assert_eq!(results[0].1, 1.75);
+    assert_eq!(results[0].2, Some(1.75));
+    assert_eq!(results[0].3, None);
+    assert!((results[0].4.unwrap() - 166.27).abs() < 0.01);
+
+    redis
+        .exec(cmd::Del(&key).key(&zkey).key(&geokey))
+        .await
+        .unwrap();
+}
+
+#[ntex::test]
+async fn test_connect_resp3() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .codec(codec::Codec::resp3())
+        .connect()
+        .await
+        .unwrap();
+    let key = new_key();
+    assert_eq!(redis.protocol(), codec::Protocol::Resp3);
+
+    redis.exec(cmd::HSet(&key, "field", "value")).await.unwrap();
+    let res = Pipeline::new(redis.clone())
+        .call(array!["HGETALL", &key])
+        .await
+        .unwrap();
+    assert!(matches!(res, codec::Response::Map(_)));
+}
+
+#[ntex::test]
+async fn test_hello_simple() {
+    let mut redis = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    let key = new_key();
+
+    redis.exec(cmd::HSet(&key, "field", "value")).await.unwrap();
+    redis.hello(codec::Protocol::Resp3).await.unwrap();
+    assert_eq!(redis.protocol(), codec::Protocol::Resp3);
+
+    let res = redis.exec(cmd::HGetAll(&key)).await.unwrap();
+    assert_eq!(res.get(&Bytes::from_static(b"field")).unwrap(), "value");
+}
+
+#[ntex::test]
+async fn test_subscribe_resp3() {
+    let channel = new_key();
+    let mut subscriber = RedisConnector::new("127.0.0.1:6379")
+        .connect_simple()
+        .await
+        .unwrap();
+    subscriber.hello(codec::Protocol::Resp3).await.unwrap();
+
+    let pubsub = subscriber.subscribe(cmd::Subscribe([&channel])).unwrap();
+    assert_eq!(pubsub.wait_subscribed(1).await.unwrap().len(), 1);
+
+    let publisher = connect().await;
+    publisher.exec(cmd::Publish(&channel, "1")).await.unwrap();
+    assert_eq!(
+        pubsub.recv().await.unwrap().unwrap(),
+        cmd::SubscribeItem::Message {
+            pattern: None,
+            channel: Bytes::from(channel.clone()),
+            payload: Bytes::from_static(b"1")
+        }
+    );
+
+    let channels = pubsub.close().await.unwrap();
+    assert_eq!(channels, vec![Bytes::from(channel)]);
+}
+
+#[ntex::test]
+async fn test_invalidations() {
+    let redis = connect().await;
+    let key = new_key();
+    redis.hello(codec::Protocol::Resp3).await.unwrap();
+    let invalidations = redis.invalidations();
+
+    redis.exec(cmd::ClientTracking().on()).await.unwrap();
+
+    // read key to track it
+    redis.exec(cmd::Set(&key, "1")).await.unwrap();
+    redis.exec(cmd::Get(&key)).await.unwrap();
+
+    // modify key from other connection
+    let other = connect().await;
+    other.exec(cmd::Set(&key, "2")).await.unwrap();
+
+    let keys = invalidations.recv().await.unwrap();
+    assert_eq!(keys, Some(vec![Bytes::from(key)]));
+}
+
+#[ntex::test]
+async fn test_invalidation_stream() {
+    let redis = connect().await;
+    let key = new_key();
+    redis.hello(codec::Protocol::Resp3).await.unwrap();
+    let invalidations = redis.invalidation_stream();
+
+    redis.exec(cmd::ClientTracking().on()).await.unwrap();
+    redis.exec(cmd::Set(&key, "1")).await.unwrap();
+    redis.exec(cmd::Get(&key)).await.unwrap();
+
+    let other = connect().await;
+    other.exec(cmd::Set(&key, "2")).await.unwrap();
+
+    assert_eq!(invalidations.recv().await.unwrap(), key);
+}
+
+#[ntex::test]
+async fn test_client_tracking() {
+    use ntex_redis::cmd::Command;
+
+    let redis = connect().await;
+    let prefix = new_key();
+    redis.hello(codec::Protocol::Resp3).await.unwrap();
+
+    let res = Pipeline::new(redis.clone())
+        .call(
+            cmd::ClientTracking()
+                .on()
+                .bcast()
+                .prefix(&prefix)
+                .to_request(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res, codec::Response::String("OK".into()));
+
+    redis.exec(cmd::ClientTracking().off()).await.unwrap();
+    redis
+        .exec(cmd::ClientTracking().bcast().prefix(&prefix))
+        .await
+        .unwrap();
+    redis.exec(cmd::ClientTracking().off()).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_del_many() {
+    let redis = connect().await;
+    let prefix = new_key();
+    let keys: Vec<_> = (0..2000).map(|i| format!("{}:{}", prefix, i)).collect();
+
+    for key in keys.iter().take(1500) {
+        redis.exec(cmd::Set(key, "1")).await.unwrap();
+    }
+
+    let deleted = redis.del_many(&keys).await.unwrap();
+    assert_eq!(deleted, 1500);
+
+    let deleted = redis.del_many_batched(&keys, 300).await.unwrap();
+    assert_eq!(deleted, 0);
+}
+
+#[ntex::test]
+async fn test_swapdb() {
+    // use dedicated databases, other tests do not touch db 14 and db 15
+    let db14 = connect().await;
+    assert!(db14.exec(cmd::Select(14)).await.unwrap());
+    let db15 = connect().await;
+    assert!(db15.exec(cmd::Select(15)).await.unwrap());
+
+    let key = new_key();
+    db15.exec(cmd::Set(&key, "value")).await.unwrap();
+    assert_eq!(db14.exec(cmd::Get(&key)).await.unwrap(), None);
+
+    db14.exec(cmd::SwapDb(14, 15)).await.unwrap();
+    assert_eq!(
+        db14.exec(cmd::Get(&key)).await.unwrap(),
+        Some(Bytes::from_static(b"value"))
+    );
+    assert_eq!(db15.exec(cmd::Get(&key)).await.unwrap(), None);
+
+    let res = db14.exec(cmd::SwapDb(14, 100_000)).await;
+    assert!(matches!(res, Err(CommandError::Error(_))));
+}
+
+#[ntex::test]
+async fn test_move() {
+    let db0 = connect().await;
+    let db1 = connect().await;
+    assert!(db1.exec(cmd::Select(1)).await.unwrap());
+
+    let key = new_key();
+    db0.exec(cmd::Set(&key, "value")).await.unwrap();
+    assert!(db0.exec(cmd::Move(&key, 1)).await.unwrap());
+
+    assert_eq!(db0.exec(cmd::Get(&key)).await.unwrap(), None);
+    assert_eq!(
+        db1.exec(cmd::Get(&key)).await.unwrap(),
+        Some(Bytes::from_static(b"value"))
+    );
+
+    // key does not exist
+    assert!(!db0.exec(cmd::Move(&key, 1)).await.unwrap());
+
+    // key exists in destination db
+    db0.exec(cmd::Set(&key, "value2")).await.unwrap();
+    assert!(!db0.exec(cmd::Move(&key, 1)).await.unwrap());
+
+    db0.exec(cmd::Del(&key)).await.unwrap();
+    db1.exec(cmd::Del(&key)).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_notify() {
+    let redis = connect().await;
+    let channel = new_key();
+    let key = new_key();
+
+    for _ in 0..100 {
+        redis.notify(cmd::Publish(&channel, "message")).unwrap();
+    }
+    redis.notify(cmd::Set(&key, "value")).unwrap();
+
+    let res = redis.exec(cmd::Get(&key)).await.unwrap();
+    assert_eq!(res, Some(Bytes::from_static(b"value")));
+    assert!(redis.is_idle());
+}
+
+#[ntex::test]
+async fn test_lmpop() {
+    let redis = connect().await;
+    let key1 = new_key();
+    let key2 = new_key();
+
+    let res = redis.exec(cmd::LMPop([&key1, &key2])).await.unwrap();
+    assert_eq!(res, None);
+
+    redis
+        .exec(cmd::RPush(&key2, "a").extend(vec!["b", "c"]))
+        .await
+        .unwrap();
+
+    let res = redis.exec(cmd::LMPop([&key1, &key2])).await.unwrap();
+    assert_eq!(
+        res,
+        Some((Bytes::from(key2.clone()), vec![Bytes::from_static(b"a")]))
+    );
+
+    let res = redis
+        .exec(cmd::LMPop([&key1, &key2]).right().count(5))
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        Some((
+            Bytes::from(key2.clone()),
+            vec![Bytes::from_static(b"c"), Bytes::from_static(b"b")]
+        ))
+    );
+}
+
+#[ntex::test]
+async fn test_zmpop() {
+    let redis = connect().await;
+    let key1 = new_key();
+    let key2 = new_key();
+
+    let res = redis.exec(cmd::ZMPop([&key1, &key2])).await.unwrap();
+    assert_eq!(res, None);
+
+    Pipeline::new(redis.clone())
+        .call(array!["ZADD", &key2, "1", "a", "2", "b", "3.5", "c"])
+        .await
+        .unwrap();
+
+    let res = redis.exec(cmd::ZMPop([&key1, &key2])).await.unwrap();
+    assert_eq!(
+        res,
+        Some((
+            Bytes::from(key2.clone()),
+            vec![(Bytes::from_static(b"a"), 1.0)]
+        ))
+    );
+
+    let res = redis
+        .exec(cmd::ZMPop([&key1, &key2]).max().count(5))
+        .await
+        .unwrap();
+    assert_eq!(
+        res,
+        Some((
+            Bytes::from(key2.clone()),
+            vec![
+                (Bytes::from_static(b"c"), 3.5),
+                (Bytes::from_static(b"b"), 2.0)
+            ]
+        ))
+    );
+}
+
+#[ntex::test]
+async fn test_on_connect() {
+    let redis = RedisConnector::new("127.0.0.1:6379")
+        .on_connect(|client| {
+            Box::pin(async move {
+                assert!(client.exec(cmd::Select(2)).await?);
+                client.exec(cmd::ClientSetName("on-connect")).await
+            })
+        })
+        .connect()
+        .await
+        .unwrap();
+
+    let name = redis.exec(cmd::ClientGetName()).await.unwrap();
+    assert_eq!(name, Some(Bytes::from_static(b"on-connect")));
+
+    let key = new_key();
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+
+    let db2 = connect().await;
+    assert!(db2.exec(cmd::Select(2)).await.unwrap());
+    assert_eq!(
+        db2.exec(cmd::Get(&key)).await.unwrap(),
+        Some(Bytes::from_static(b"value"))
     );
+    assert_eq!(connect().await.exec(cmd::Get(&key)).await.unwrap(), None);
+    redis.exec(cmd::Del(&key)).await.unwrap();
+}
+
+#[ntex::test]
+async fn test_object_freq() {
+    let redis = connect().await;
+    let key = new_key();
+    redis.exec(cmd::Set(&key, "value")).await.unwrap();
+
+    let policy = MaxMemoryPolicy::set(&redis, "allkeys-lfu").await;
+    redis.exec(cmd::Get(&key)).await.unwrap();
+    let result = redis.exec(cmd::ObjectFreq(&key)).await.unwrap();
+    assert!(result.is_some());
+
+    let result = redis.exec(cmd::ObjectFreq(new_key())).await.unwrap();
+    assert_eq!(result, None);
+    drop(policy);
+
+    let _policy = MaxMemoryPolicy::set(&redis, "noeviction").await;
+    match redis.exec(cmd::ObjectFreq(&key)).await {
+        Err(CommandError::Error(err)) => assert_eq!(
+            err,
+            "OBJECT FREQ requires an LFU maxmemory-policy (allkeys-lfu or volatile-lfu)"
+        ),
+        res => panic!("Unexpected result: {:?}", res),
+    }
 }